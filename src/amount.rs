@@ -0,0 +1,363 @@
+use std::fmt;
+
+use anyhow::{anyhow, bail, Result};
+
+/// Spark markets quote prices in base 1e9 unless the market config says otherwise.
+pub const DEFAULT_PRICE_DECIMALS: u32 = 9;
+
+/// How to treat digits that do not fit in the target precision.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rounding {
+    /// Towards zero. Use when the result must never exceed what is available.
+    Down,
+    /// Away from zero. Use when the result must always cover a requirement.
+    Up,
+    /// To the nearest unit, halves away from zero.
+    Nearest,
+}
+
+/// An amount of an asset in base units, together with the asset's decimals.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Quantity {
+    raw: u64,
+    decimals: u32,
+}
+
+/// A market price in base units of `10^-decimals` quote per whole base unit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Price {
+    raw: u64,
+    decimals: u32,
+}
+
+impl Quantity {
+    pub fn from_raw(raw: u64, decimals: u32) -> Self {
+        Self { raw, decimals }
+    }
+
+    pub fn zero(decimals: u32) -> Self {
+        Self::from_raw(0, decimals)
+    }
+
+    /// Whole tokens to base units, e.g. `from_units(5, 6)` is 5 USDC.
+    pub fn from_units(units: u64, decimals: u32) -> Result<Self> {
+        let raw = to_u64(scale_units(units, decimals)?, "quantity")?;
+        Ok(Self::from_raw(raw, decimals))
+    }
+
+    /// Parses a human readable amount such as `"0.0015"` without going through `f64`.
+    pub fn from_decimal_str(value: &str, decimals: u32, rounding: Rounding) -> Result<Self> {
+        let raw = to_u64(parse_decimal(value, decimals, rounding)?, "quantity")?;
+        Ok(Self::from_raw(raw, decimals))
+    }
+
+    /// Converts a float using its shortest exact decimal representation,
+    /// so `0.1` becomes exactly `100_000` at 6 decimals.
+    pub fn from_f64(value: f64, decimals: u32, rounding: Rounding) -> Result<Self> {
+        Self::from_decimal_str(&float_to_string(value)?, decimals, rounding)
+    }
+
+    pub fn raw(&self) -> u64 {
+        self.raw
+    }
+
+    pub fn decimals(&self) -> u32 {
+        self.decimals
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.raw == 0
+    }
+
+    /// Lossy conversion for display and heuristics only.
+    pub fn to_f64(&self) -> f64 {
+        self.raw as f64 / 10f64.powi(self.decimals as i32)
+    }
+
+    pub fn checked_add(&self, other: Quantity) -> Result<Self> {
+        self.same_decimals(&other)?;
+        let raw = self
+            .raw
+            .checked_add(other.raw)
+            .ok_or_else(|| anyhow!("Quantity overflow: {} + {}", self, other))?;
+        Ok(Self::from_raw(raw, self.decimals))
+    }
+
+    pub fn checked_sub(&self, other: Quantity) -> Result<Self> {
+        self.same_decimals(&other)?;
+        let raw = self
+            .raw
+            .checked_sub(other.raw)
+            .ok_or_else(|| anyhow!("Quantity underflow: {} - {}", self, other))?;
+        Ok(Self::from_raw(raw, self.decimals))
+    }
+
+    pub fn saturating_sub(&self, other: Quantity) -> Result<Self> {
+        self.same_decimals(&other)?;
+        Ok(Self::from_raw(
+            self.raw.saturating_sub(other.raw),
+            self.decimals,
+        ))
+    }
+
+    /// Multiplies by `numerator / denominator`, e.g. to take a share of a budget.
+    pub fn mul_div(&self, numerator: u64, denominator: u64, rounding: Rounding) -> Result<Self> {
+        if denominator == 0 {
+            bail!("Division by zero while scaling {}", self);
+        }
+        let raw = div_round(
+            u128::from(self.raw) * u128::from(numerator),
+            u128::from(denominator),
+            rounding,
+        );
+        Ok(Self::from_raw(to_u64(raw, "quantity")?, self.decimals))
+    }
+
+    /// Expresses the same amount with a different number of decimals.
+    pub fn rescale(&self, decimals: u32, rounding: Rounding) -> Result<Self> {
+        let raw = rescale(u128::from(self.raw), self.decimals, decimals, rounding)?;
+        Ok(Self::from_raw(to_u64(raw, "quantity")?, decimals))
+    }
+
+    /// Rounds to a multiple of `step` base units (a lot size).
+    pub fn round_to_step(&self, step: u64, rounding: Rounding) -> Result<Self> {
+        let raw = round_to_step(self.raw, step, rounding)?;
+        Ok(Self::from_raw(raw, self.decimals))
+    }
+
+    fn same_decimals(&self, other: &Quantity) -> Result<()> {
+        if self.decimals != other.decimals {
+            bail!(
+                "Cannot combine quantities with {} and {} decimals",
+                self.decimals,
+                other.decimals
+            );
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Quantity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&format_decimal(self.raw, self.decimals))
+    }
+}
+
+impl Price {
+    pub fn from_raw(raw: u64, decimals: u32) -> Self {
+        Self { raw, decimals }
+    }
+
+    /// A whole number price, e.g. `from_units(3_200, 9)` is 3200 quote per base.
+    pub fn from_units(units: u64, decimals: u32) -> Result<Self> {
+        let raw = to_u64(scale_units(units, decimals)?, "price")?;
+        Ok(Self::from_raw(raw, decimals))
+    }
+
+    /// Parses a human readable price such as `"3201.5"`.
+    pub fn from_decimal_str(value: &str, decimals: u32, rounding: Rounding) -> Result<Self> {
+        let raw = to_u64(parse_decimal(value, decimals, rounding)?, "price")?;
+        Ok(Self::from_raw(raw, decimals))
+    }
+
+    pub fn from_f64(value: f64, decimals: u32, rounding: Rounding) -> Result<Self> {
+        Self::from_decimal_str(&float_to_string(value)?, decimals, rounding)
+    }
+
+    pub fn raw(&self) -> u64 {
+        self.raw
+    }
+
+    pub fn decimals(&self) -> u32 {
+        self.decimals
+    }
+
+    /// Lossy conversion for display and heuristics only.
+    pub fn to_f64(&self) -> f64 {
+        self.raw as f64 / 10f64.powi(self.decimals as i32)
+    }
+
+    /// Shifts the price by `bps` basis points (1 bps = 0.01%), e.g. `-50` is 0.5% lower.
+    pub fn offset_bps(&self, bps: i64, rounding: Rounding) -> Result<Self> {
+        let factor = 10_000i128 + i128::from(bps);
+        if factor < 0 {
+            bail!("Price offset of {} bps would make the price negative", bps);
+        }
+        let raw = div_round(u128::from(self.raw) * factor as u128, 10_000, rounding);
+        Ok(Self::from_raw(to_u64(raw, "price")?, self.decimals))
+    }
+
+    /// Rounds to a multiple of `tick` base units.
+    pub fn round_to_tick(&self, tick: u64, rounding: Rounding) -> Result<Self> {
+        let raw = round_to_step(self.raw, tick, rounding)?;
+        Ok(Self::from_raw(raw, self.decimals))
+    }
+
+    /// Quote needed to buy (or received for selling) `base` at this price:
+    /// `base * price / 10^(base_decimals + price_decimals - quote_decimals)`,
+    /// which is the conversion the market contract performs.
+    pub fn quote_for(
+        &self,
+        base: Quantity,
+        quote_decimals: u32,
+        rounding: Rounding,
+    ) -> Result<Quantity> {
+        let product = u128::from(base.raw) * u128::from(self.raw);
+        let raw = rescale(
+            product,
+            base.decimals + self.decimals,
+            quote_decimals,
+            rounding,
+        )?;
+        Ok(Quantity::from_raw(
+            to_u64(raw, "quote quantity")?,
+            quote_decimals,
+        ))
+    }
+
+    /// Base amount that `quote` buys at this price, the inverse of `quote_for`.
+    pub fn base_for(
+        &self,
+        quote: Quantity,
+        base_decimals: u32,
+        rounding: Rounding,
+    ) -> Result<Quantity> {
+        if self.raw == 0 {
+            bail!("Cannot convert {} at a zero price", quote);
+        }
+        // base = quote * 10^(base_decimals + price_decimals) / (price * 10^quote_decimals)
+        let numerator = u128::from(quote.raw)
+            .checked_mul(pow10(base_decimals + self.decimals)?)
+            .ok_or_else(|| anyhow!("Overflow converting {} to base", quote))?;
+        let denominator = u128::from(self.raw)
+            .checked_mul(pow10(quote.decimals)?)
+            .ok_or_else(|| anyhow!("Overflow converting {} to base at {}", quote, self))?;
+        let raw = div_round(numerator, denominator, rounding);
+        Ok(Quantity::from_raw(
+            to_u64(raw, "base quantity")?,
+            base_decimals,
+        ))
+    }
+}
+
+impl fmt::Display for Price {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&format_decimal(self.raw, self.decimals))
+    }
+}
+
+fn pow10(exp: u32) -> Result<u128> {
+    10u128
+        .checked_pow(exp)
+        .ok_or_else(|| anyhow!("10^{} does not fit in 128 bits", exp))
+}
+
+fn scale_units(units: u64, decimals: u32) -> Result<u128> {
+    u128::from(units)
+        .checked_mul(pow10(decimals)?)
+        .ok_or_else(|| anyhow!("Overflow scaling {} to {} decimals", units, decimals))
+}
+
+fn to_u64(value: u128, what: &str) -> Result<u64> {
+    u64::try_from(value).map_err(|_| anyhow!("{} {} does not fit in u64", what, value))
+}
+
+fn div_round(numerator: u128, denominator: u128, rounding: Rounding) -> u128 {
+    let quotient = numerator / denominator;
+    let remainder = numerator % denominator;
+    if remainder == 0 {
+        return quotient;
+    }
+    match rounding {
+        Rounding::Down => quotient,
+        Rounding::Up => quotient + 1,
+        Rounding::Nearest => {
+            if remainder >= denominator - remainder {
+                quotient + 1
+            } else {
+                quotient
+            }
+        }
+    }
+}
+
+fn rescale(value: u128, from: u32, to: u32, rounding: Rounding) -> Result<u128> {
+    if to >= from {
+        value.checked_mul(pow10(to - from)?).ok_or_else(|| {
+            anyhow!(
+                "Overflow rescaling {} from {} to {} decimals",
+                value,
+                from,
+                to
+            )
+        })
+    } else {
+        Ok(div_round(value, pow10(from - to)?, rounding))
+    }
+}
+
+fn round_to_step(raw: u64, step: u64, rounding: Rounding) -> Result<u64> {
+    if step == 0 {
+        bail!("Step size must be greater than zero");
+    }
+    let steps = div_round(u128::from(raw), u128::from(step), rounding);
+    to_u64(steps * u128::from(step), "rounded value")
+}
+
+fn float_to_string(value: f64) -> Result<String> {
+    if !value.is_finite() || value < 0.0 {
+        bail!("Expected a finite, non-negative amount, got {}", value);
+    }
+    // `Display` for floats prints the shortest decimal that round-trips, never in exponent form.
+    Ok(format!("{}", value))
+}
+
+fn parse_decimal(value: &str, decimals: u32, rounding: Rounding) -> Result<u128> {
+    let value = value.trim().replace('_', "");
+    let (int_part, frac_part) = value.split_once('.').unwrap_or((&value, ""));
+    if (int_part.is_empty() && frac_part.is_empty())
+        || !int_part.chars().all(|c| c.is_ascii_digit())
+        || !frac_part.chars().all(|c| c.is_ascii_digit())
+    {
+        bail!("Invalid decimal amount {:?}", value);
+    }
+
+    let kept_len = frac_part.len().min(decimals as usize);
+    let (kept, dropped) = frac_part.split_at(kept_len);
+
+    let mut digits = String::with_capacity(int_part.len() + decimals as usize);
+    digits.push_str(int_part);
+    digits.push_str(kept);
+    for _ in kept_len..decimals as usize {
+        digits.push('0');
+    }
+    let digits = digits.trim_start_matches('0');
+    let mut raw = if digits.is_empty() {
+        0
+    } else {
+        digits
+            .parse::<u128>()
+            .map_err(|_| anyhow!("Amount {:?} is too large", value))?
+    };
+
+    let round_up = match rounding {
+        Rounding::Down => false,
+        Rounding::Up => dropped.chars().any(|c| c != '0'),
+        Rounding::Nearest => dropped.chars().next().is_some_and(|c| c >= '5'),
+    };
+    if round_up {
+        raw = raw
+            .checked_add(1)
+            .ok_or_else(|| anyhow!("Amount {:?} is too large", value))?;
+    }
+    Ok(raw)
+}
+
+fn format_decimal(raw: u64, decimals: u32) -> String {
+    if decimals == 0 {
+        return raw.to_string();
+    }
+    let digits = format!("{:0>width$}", raw, width = decimals as usize + 1);
+    let (int_part, frac_part) = digits.split_at(digits.len() - decimals as usize);
+    format!("{}.{}", int_part, frac_part)
+}
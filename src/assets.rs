@@ -0,0 +1,138 @@
+use std::collections::BTreeMap;
+
+use anyhow::{anyhow, Result};
+use fuels::{
+    accounts::wallet::WalletUnlocked,
+    prelude::abigen,
+    programs::calls::Execution,
+    types::{AssetId, ContractId},
+};
+
+use crate::{
+    amount::{Quantity, Rounding},
+    config::Config,
+};
+
+// Only the `decimals` getter of the SRC-20 standard is needed here.
+abigen!(Contract(name = "Src20", abi = "abi/src20-abi.json"));
+
+/// Symbol, id and decimals of one asset. All conversions between human readable
+/// amounts and base units should go through this.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AssetInfo {
+    pub symbol: String,
+    pub asset_id: AssetId,
+    pub decimals: u32,
+}
+
+impl AssetInfo {
+    pub fn new(symbol: &str, asset_id: AssetId, decimals: u32) -> Self {
+        Self {
+            symbol: symbol.to_ascii_uppercase(),
+            asset_id,
+            decimals,
+        }
+    }
+
+    /// Whole tokens, e.g. `usdc.units(5)` is 5 USDC.
+    pub fn units(&self, units: u64) -> Result<Quantity> {
+        Quantity::from_units(units, self.decimals)
+    }
+
+    /// Parses a human readable amount such as `"0.25"`, rounding down.
+    pub fn parse(&self, value: &str) -> Result<Quantity> {
+        Quantity::from_decimal_str(value, self.decimals, Rounding::Down)
+    }
+
+    pub fn from_f64(&self, value: f64, rounding: Rounding) -> Result<Quantity> {
+        Quantity::from_f64(value, self.decimals, rounding)
+    }
+
+    /// Wraps an amount that is already in base units, e.g. a balance.
+    pub fn quantity(&self, raw: u64) -> Quantity {
+        Quantity::from_raw(raw, self.decimals)
+    }
+
+    /// Lossy conversion of base units for display.
+    pub fn to_f64(&self, raw: u64) -> f64 {
+        self.quantity(raw).to_f64()
+    }
+}
+
+/// Every asset of the active network profile whose decimals are known.
+#[derive(Debug, Clone, Default)]
+pub struct AssetCatalogue {
+    assets: BTreeMap<String, AssetInfo>,
+}
+
+impl AssetCatalogue {
+    /// Builds the catalogue from the decimals set in the config, without touching the chain.
+    pub fn from_config(config: &Config) -> Self {
+        let mut catalogue = Self::default();
+        for (symbol, asset) in &config.assets {
+            if let Some(decimals) = asset.decimals {
+                catalogue.insert(AssetInfo::new(symbol, asset.id, decimals.into()));
+            }
+        }
+        catalogue
+    }
+
+    /// Like `from_config`, then asks the SRC-20 contract of every asset without
+    /// configured decimals for its `decimals()`.
+    pub async fn load(config: &Config, wallet: &WalletUnlocked) -> Result<Self> {
+        let mut catalogue = Self::from_config(config);
+        for (symbol, asset) in &config.assets {
+            if asset.decimals.is_some() {
+                continue;
+            }
+            if let Some(contract) = asset.contract {
+                let decimals = src20_decimals(contract, asset.id, wallet)
+                    .await
+                    .map_err(|e| anyhow!("Failed to read decimals of {}: {}", symbol, e))?;
+                catalogue.insert(AssetInfo::new(symbol, asset.id, decimals.into()));
+            }
+        }
+        Ok(catalogue)
+    }
+
+    pub fn insert(&mut self, info: AssetInfo) {
+        self.assets.insert(info.symbol.clone(), info);
+    }
+
+    /// Looks up an asset by symbol, e.g. `"ETH"`.
+    pub fn get(&self, symbol: &str) -> Result<&AssetInfo> {
+        let symbol = symbol.to_ascii_uppercase();
+        self.assets.get(&symbol).ok_or_else(|| {
+            anyhow!(
+                "Decimals of {} are unknown (set {}_DECIMALS or {}_CONTRACT)",
+                symbol,
+                symbol,
+                symbol
+            )
+        })
+    }
+
+    pub fn by_id(&self, asset_id: &AssetId) -> Option<&AssetInfo> {
+        self.assets.values().find(|info| &info.asset_id == asset_id)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &AssetInfo> {
+        self.assets.values()
+    }
+}
+
+/// Reads `decimals(asset_id)` from an SRC-20 contract.
+pub async fn src20_decimals(
+    contract: ContractId,
+    asset_id: AssetId,
+    wallet: &WalletUnlocked,
+) -> Result<u8> {
+    let src20 = Src20::new(contract, wallet.clone());
+    src20
+        .methods()
+        .decimals(asset_id)
+        .simulate(Execution::StateReadOnly)
+        .await?
+        .value
+        .ok_or_else(|| anyhow!("Contract {} does not know asset {}", contract, asset_id))
+}
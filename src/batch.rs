@@ -0,0 +1,548 @@
+//! Multicalls of any size.
+//!
+//! `BatchSubmitter` collects market calls and splits them into as few
+//! transactions as fit under the chain's gas and size limits, measured with
+//! `estimate_transaction_cost` rather than a guessed number of calls per
+//! transaction. A call that reverts is picked out of its transaction and
+//! reported, and the calls it took down with it are sent again. `simulate`
+//! dry-runs the same transactions instead of sending them.
+
+use std::{collections::VecDeque, fmt::Debug, time::Duration};
+
+use anyhow::{bail, Result};
+use fuels::{
+    accounts::{provider::TransactionCost, wallet::WalletUnlocked},
+    core::traits::{Parameterize, Tokenizable},
+    programs::calls::{CallHandler, ContractCall},
+    tx::Receipt,
+    types::{tx_status::TxStatus, Bytes32, ContractId},
+};
+use futures::future::join_all;
+
+use crate::{
+    error::SparkError,
+    simulate::{Simulation, Simulator},
+    SparkContext,
+};
+
+/// A multicall being put together.
+pub type MultiCall = CallHandler<WalletUnlocked, Vec<ContractCall>, ()>;
+
+/// Gas forwarded to the market's `deposit`.
+pub const DEPOSIT_GAS: u64 = 1_000_000;
+
+/// Adds one pushed call to a multicall. Calls return different types, so they
+/// are kept as the step that adds them rather than as handlers.
+type AddCall = Box<dyn Fn(MultiCall) -> MultiCall>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SubmitMode {
+    /// Wait for each transaction before planning the next one, so later calls
+    /// may depend on earlier ones (a deposit, then orders using it).
+    #[default]
+    Sequential,
+    /// Plan every transaction up front, submit them back to back and wait for
+    /// all of them together. Calls must not depend on each other, and the
+    /// wallet needs a spendable coin per transaction.
+    Pipelined,
+}
+
+/// What to do when a call fails.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OnRevert {
+    /// Report the failing call and send the others again without it.
+    #[default]
+    Drop,
+    /// Report the failing call and send nothing more.
+    Stop,
+}
+
+/// The outcome of one pushed call.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CallStatus {
+    /// Went through in this transaction.
+    Included { tx_id: String },
+    /// Reverted, could not be estimated or was in a transaction that failed
+    /// for another reason.
+    Failed { error: SparkError },
+    /// Never sent because the batch stopped first.
+    NotSent,
+}
+
+/// One transaction of a batch.
+#[derive(Debug, Clone)]
+pub struct ChunkReport {
+    /// Indices of the calls it carried, in the order they were pushed.
+    pub calls: Vec<usize>,
+    pub estimated_gas: u64,
+    pub estimated_size: u64,
+    pub tx_id: Option<String>,
+    pub receipts: Vec<Receipt>,
+    pub error: Option<String>,
+    /// The call found to have made the transaction revert.
+    pub failed_call: Option<usize>,
+}
+
+impl ChunkReport {
+    fn new(calls: Vec<usize>, cost: &TransactionCost) -> Self {
+        Self {
+            calls,
+            estimated_gas: cost.gas_used,
+            estimated_size: cost.metered_bytes_size,
+            tx_id: None,
+            receipts: Vec::new(),
+            error: None,
+            failed_call: None,
+        }
+    }
+
+    pub fn is_success(&self) -> bool {
+        self.tx_id.is_some() && self.error.is_none()
+    }
+}
+
+/// What happened to every call of a batch.
+#[derive(Debug, Clone, Default)]
+pub struct BatchReport {
+    /// Every transaction sent, including reverted ones whose calls were retried.
+    pub chunks: Vec<ChunkReport>,
+    /// One status per pushed call, in push order.
+    pub calls: Vec<CallStatus>,
+}
+
+impl BatchReport {
+    pub fn is_success(&self) -> bool {
+        self.calls
+            .iter()
+            .all(|status| matches!(status, CallStatus::Included { .. }))
+    }
+
+    /// Ids of the transactions that were submitted, successful or not.
+    pub fn tx_ids(&self) -> Vec<String> {
+        self.chunks
+            .iter()
+            .filter_map(|chunk| chunk.tx_id.clone())
+            .collect()
+    }
+
+    /// The transaction that included the call pushed at `index`.
+    pub fn tx_id_of(&self, index: usize) -> Option<&str> {
+        match self.calls.get(index) {
+            Some(CallStatus::Included { tx_id }) => Some(tx_id),
+            _ => None,
+        }
+    }
+
+    /// The last transaction that carried the call pushed at `index`.
+    pub fn chunk_of(&self, index: usize) -> Option<&ChunkReport> {
+        self.chunks
+            .iter()
+            .rev()
+            .find(|chunk| chunk.calls.contains(&index))
+    }
+
+    pub fn included(&self) -> usize {
+        self.calls
+            .iter()
+            .filter(|status| matches!(status, CallStatus::Included { .. }))
+            .count()
+    }
+
+    /// Index and error of every failed call.
+    pub fn failures(&self) -> Vec<(usize, &SparkError)> {
+        self.calls
+            .iter()
+            .enumerate()
+            .filter_map(|(i, status)| match status {
+                CallStatus::Failed { error } => Some((i, error)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Fails with the first failed call, for callers that need every call through.
+    pub fn ensure_success(&self) -> Result<()> {
+        if let Some((index, error)) = self.failures().first() {
+            bail!("Call {} failed: {}", index, error);
+        }
+        let not_sent = self.calls.len() - self.included();
+        if not_sent > 0 {
+            bail!("{} calls were not sent", not_sent);
+        }
+        Ok(())
+    }
+}
+
+/// How the next transaction of a group of calls looks.
+enum Planned {
+    /// The first `len` calls fit.
+    Chunk(usize, TransactionCost),
+    /// The first call cannot be sent at all.
+    Unsendable(SparkError),
+}
+
+/// Collects market calls and submits them in as few transactions as the chain
+/// allows:
+///
+/// ```ignore
+/// let mut batch = BatchSubmitter::new(&ctx);
+/// for id in ids {
+///     batch.push(ctx.market.get_instance().methods().cancel_order(id));
+/// }
+/// let report = batch.submit().await?;
+/// ```
+///
+/// A chunk is grown while its estimated gas and size stay under `headroom` of
+/// the consensus limits, trying all remaining calls first and bisecting when
+/// they do not fit.
+///
+/// When a transaction reverts, the receipts show which call it reverted in:
+/// that call is reported with the decoded reason and, with `OnRevert::Drop`,
+/// the rest are sent again. If the receipts do not tell, the chunk is split in
+/// halves until they do.
+pub struct BatchSubmitter<'a> {
+    ctx: &'a SparkContext,
+    calls: Vec<AddCall>,
+    mode: SubmitMode,
+    on_revert: OnRevert,
+    max_calls: Option<usize>,
+    headroom: f64,
+    timeout: Duration,
+}
+
+impl<'a> BatchSubmitter<'a> {
+    /// Sequential, dropping failed calls, using up to 80% of the limits and
+    /// waiting up to a minute per transaction.
+    pub fn new(ctx: &'a SparkContext) -> Self {
+        Self {
+            ctx,
+            calls: Vec::new(),
+            mode: SubmitMode::default(),
+            on_revert: OnRevert::default(),
+            max_calls: None,
+            headroom: 0.8,
+            timeout: Duration::from_secs(60),
+        }
+    }
+
+    pub fn mode(mut self, mode: SubmitMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    pub fn on_revert(mut self, on_revert: OnRevert) -> Self {
+        self.on_revert = on_revert;
+        self
+    }
+
+    /// Caps the calls per transaction on top of the gas and size limits.
+    pub fn max_calls(mut self, max_calls: usize) -> Self {
+        self.max_calls = Some(max_calls.max(1));
+        self
+    }
+
+    /// Share of the chain limits a transaction may use, leaving room for
+    /// estimates that fall short.
+    pub fn headroom(mut self, headroom: f64) -> Self {
+        self.headroom = headroom.clamp(0.01, 1.0);
+        self
+    }
+
+    /// How long to wait for each transaction to be included.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    pub fn push<T>(&mut self, call: CallHandler<WalletUnlocked, ContractCall, T>)
+    where
+        T: Tokenizable + Parameterize + Debug + 'static,
+    {
+        self.calls
+            .push(Box::new(move |multi| multi.add_call(call.clone())));
+    }
+
+    pub fn len(&self) -> usize {
+        self.calls.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.calls.is_empty()
+    }
+
+    /// Sends every call. Calls that fail, whether while estimating, while
+    /// submitting or on chain, end up in the report rather than as an error.
+    pub async fn submit(self) -> Result<BatchReport> {
+        let (ctx, timeout) = (self.ctx, self.timeout);
+        let mut report = BatchReport {
+            chunks: Vec::new(),
+            calls: vec![CallStatus::NotSent; self.calls.len()],
+        };
+        // Calls still to send, in groups that are never merged into one transaction
+        let mut groups = VecDeque::new();
+        if !self.calls.is_empty() {
+            groups.push_back((0..self.calls.len()).collect::<Vec<_>>());
+        }
+        let mut stopped = false;
+
+        while !groups.is_empty() && !stopped {
+            // Plan one transaction when sequential, all of them when pipelined
+            let mut round = Vec::new();
+            while let Some(mut group) = groups.pop_front() {
+                match self.plan(&group).await? {
+                    Planned::Chunk(len, cost) => {
+                        let rest = group.split_off(len);
+                        if !rest.is_empty() {
+                            groups.push_front(rest);
+                        }
+                        round.push(ChunkReport::new(group, &cost));
+                        if self.mode == SubmitMode::Sequential {
+                            break;
+                        }
+                    }
+                    Planned::Unsendable(error) => {
+                        let index = group.remove(0);
+                        report.calls[index] = CallStatus::Failed { error };
+                        if !group.is_empty() {
+                            groups.push_front(group);
+                        }
+                        if self.on_revert == OnRevert::Stop {
+                            stopped = true;
+                            break;
+                        }
+                    }
+                }
+            }
+
+            // Send back to back, then wait for all of them
+            let mut sent = Vec::with_capacity(round.len());
+            for chunk in &mut round {
+                match self.send(&chunk.calls).await {
+                    Ok(tx_id) => {
+                        chunk.tx_id = Some(format!("0x{}", tx_id));
+                        sent.push(Ok(tx_id));
+                    }
+                    Err(e) => {
+                        let error = SparkError::from(&e);
+                        chunk.error = Some(error.to_string());
+                        sent.push(Err(error));
+                    }
+                }
+            }
+            let statuses = join_all(sent.iter().map(|tx_id| async move {
+                match tx_id {
+                    Ok(tx_id) => ctx
+                        .wait_for_status(*tx_id, timeout)
+                        .await
+                        .map_err(SparkError::from),
+                    Err(error) => Err(error.clone()),
+                }
+            }))
+            .await;
+
+            for (mut chunk, status) in round.into_iter().zip(statuses) {
+                let retry = self.settle(&mut chunk, status, &mut report.calls);
+                if !chunk.is_success() && self.on_revert == OnRevert::Stop {
+                    stopped = true;
+                } else {
+                    for group in retry.into_iter().rev() {
+                        groups.push_front(group);
+                    }
+                }
+                report.chunks.push(chunk);
+            }
+        }
+        Ok(report)
+    }
+
+    /// Dry-runs the calls split into transactions the way `submit` would send
+    /// them and decodes what each would do, sending nothing. Every transaction
+    /// runs against the current state, so calls that depend on an earlier
+    /// transaction of the batch may show as failing.
+    pub async fn simulate(self) -> Result<Vec<Simulation>> {
+        let simulator = Simulator::new(self.ctx);
+        let mut simulations = Vec::new();
+        let mut group: Vec<usize> = (0..self.calls.len()).collect();
+        while !group.is_empty() {
+            let len = match self.plan(&group).await? {
+                Planned::Chunk(len, _) => len,
+                // Run it on its own to see why it fails
+                Planned::Unsendable(_) => 1,
+            };
+            let rest = group.split_off(len);
+            simulations.push(simulator.multi_call(self.multi_call(&group)).await?);
+            group = rest;
+        }
+        Ok(simulations)
+    }
+
+    /// Records the outcome of a sent chunk and returns the calls to send again.
+    fn settle(
+        &self,
+        chunk: &mut ChunkReport,
+        status: Result<TxStatus, SparkError>,
+        calls: &mut [CallStatus],
+    ) -> Vec<Vec<usize>> {
+        let error = match status {
+            Ok(TxStatus::Success { receipts }) => {
+                let tx_id = chunk.tx_id.clone().unwrap_or_default();
+                for index in &chunk.calls {
+                    calls[*index] = CallStatus::Included {
+                        tx_id: tx_id.clone(),
+                    };
+                }
+                chunk.receipts = receipts;
+                return Vec::new();
+            }
+            Ok(TxStatus::Revert {
+                receipts, reason, ..
+            }) => {
+                let reason = self.revert_reason(&receipts).unwrap_or(reason);
+                let error = match SparkError::from_reason(&reason) {
+                    SparkError::Other(detail) => SparkError::Reverted(detail),
+                    known => known,
+                };
+                chunk.error = Some(error.to_string());
+                let culprit = reverted_call(&receipts).filter(|k| *k < chunk.calls.len());
+                chunk.receipts = receipts;
+
+                match culprit {
+                    Some(k) => {
+                        let index = chunk.calls[k];
+                        chunk.failed_call = Some(index);
+                        calls[index] = CallStatus::Failed { error };
+                        let rest: Vec<_> = chunk
+                            .calls
+                            .iter()
+                            .copied()
+                            .filter(|i| *i != index)
+                            .collect();
+                        return if rest.is_empty() { vec![] } else { vec![rest] };
+                    }
+                    // No telling which call it was: try each half on its own
+                    None if chunk.calls.len() > 1 => {
+                        let mut first = chunk.calls.clone();
+                        let second = first.split_off(first.len() / 2);
+                        return vec![first, second];
+                    }
+                    None => error,
+                }
+            }
+            Ok(TxStatus::SqueezedOut { reason }) => SparkError::SqueezedOut(reason),
+            Ok(TxStatus::Submitted) => SparkError::Timeout("still pending".to_string()),
+            Err(error) => error,
+        };
+        if chunk.error.is_none() {
+            chunk.error = Some(error.to_string());
+        }
+        for index in &chunk.calls {
+            calls[*index] = CallStatus::Failed {
+                error: error.clone(),
+            };
+        }
+        Vec::new()
+    }
+
+    /// The last market log of the reverted call, usually the error it
+    /// reverted with, e.g. `InsufficientBalance`.
+    fn revert_reason(&self, receipts: &[Receipt]) -> Option<String> {
+        let end = receipts.iter().position(is_revert)?;
+        let start = receipts[..end]
+            .iter()
+            .rposition(is_top_level_call)
+            .unwrap_or(0);
+        self.ctx
+            .market
+            .get_instance()
+            .log_decoder()
+            .decode_logs(&receipts[start..end])
+            .filter_succeeded()
+            .last()
+            .map(|log| log.to_string())
+    }
+
+    fn multi_call(&self, calls: &[usize]) -> MultiCall {
+        calls.iter().fold(
+            CallHandler::new_multi_call(self.ctx.wallet.clone()),
+            |multi, index| (self.calls[*index])(multi),
+        )
+    }
+
+    async fn send(&self, calls: &[usize]) -> Result<Bytes32> {
+        Ok(self.multi_call(calls).submit().await?.tx_id())
+    }
+
+    async fn estimate(&self, calls: &[usize]) -> Result<TransactionCost> {
+        Ok(self
+            .multi_call(calls)
+            .estimate_transaction_cost(None, None)
+            .await?)
+    }
+
+    fn fits(&self, cost: &TransactionCost) -> bool {
+        let tx_params = self.ctx.provider.consensus_parameters().tx_params();
+        let max_gas = (tx_params.max_gas_per_tx() as f64 * self.headroom) as u64;
+        let max_size = (tx_params.max_size() as f64 * self.headroom) as u64;
+        cost.gas_used <= max_gas && cost.metered_bytes_size <= max_size
+    }
+
+    /// The longest run of calls from the start of `group` that fits in one
+    /// transaction, with its estimated cost.
+    async fn plan(&self, group: &[usize]) -> Result<Planned> {
+        let end = self
+            .max_calls
+            .map_or(group.len(), |max| max.min(group.len()));
+
+        // Usually everything left fits, which costs a single estimate
+        if let Ok(cost) = self.estimate(&group[..end]).await {
+            if self.fits(&cost) {
+                return Ok(Planned::Chunk(end, cost));
+            }
+        }
+
+        let single = match self.estimate(&group[..1]).await {
+            Ok(cost) => cost,
+            Err(e) => return Ok(Planned::Unsendable(SparkError::from(&e))),
+        };
+        if !self.fits(&single) {
+            return Ok(Planned::Unsendable(SparkError::TooLarge(format!(
+                "{} gas, {} bytes",
+                single.gas_used, single.metered_bytes_size
+            ))));
+        }
+
+        // `fitting` calls are known to fit, `too_long` are known not to
+        let (mut fitting, mut cost, mut too_long) = (1, single, end);
+        while too_long - fitting > 1 {
+            let middle = (fitting + too_long) / 2;
+            match self.estimate(&group[..middle]).await {
+                Ok(estimate) if self.fits(&estimate) => {
+                    fitting = middle;
+                    cost = estimate;
+                }
+                _ => too_long = middle,
+            }
+        }
+        Ok(Planned::Chunk(fitting, cost))
+    }
+}
+
+fn is_revert(receipt: &Receipt) -> bool {
+    matches!(receipt, Receipt::Revert { .. } | Receipt::Panic { .. })
+}
+
+/// Calls made by the multicall script itself, one per pushed call, rather than
+/// by the contracts it calls.
+fn is_top_level_call(receipt: &Receipt) -> bool {
+    matches!(receipt, Receipt::Call { id, .. } if *id == ContractId::zeroed())
+}
+
+/// Position within its transaction of the call a revert happened in.
+fn reverted_call(receipts: &[Receipt]) -> Option<usize> {
+    let end = receipts.iter().position(is_revert)?;
+    receipts[..end]
+        .iter()
+        .filter(|receipt| is_top_level_call(receipt))
+        .count()
+        .checked_sub(1)
+}
@@ -1,103 +1,116 @@
-use dotenv::dotenv;
-use std::env;
-
-use fuels::{
-    accounts::provider::Provider, accounts::wallet::WalletUnlocked, types::AssetId,
-    types::ContractId, types::Identity,
-};
-use std::str::FromStr;
-
 use anyhow::Result;
-use spark_market_sdk::{OrderType, SparkMarketContract};
-use tokio::time::{sleep, Duration};
-
-// Add these imports
-use reqwest;
-use serde_json::Value;
-
-pub fn format_value_with_decimals(value: f64, decimals: u32) -> u64 {
-    (value * 10f64.powi(decimals as i32)) as u64
-}
-
-pub fn format_to_readable_value(value: u64, decimals: u32) -> f64 {
-    value as f64 / 10f64.powi(decimals as i32)
+use fuels::prelude::CallParameters;
+use spark_examples::{
+    amount::Rounding,
+    batch::{BatchSubmitter, DEPOSIT_GAS},
+    matcher::{BatchMatcher, PairStatus},
+    utils::{dry_run_requested, hex_str_from_bits256},
+    Oracle, SparkContext,
+};
+use spark_market_sdk::OrderType;
+
+/// Alternately 1000, 2000, ... above and below the current price.
+fn price_adjustment(i: usize) -> f64 {
+    let step = ((i / 2 + 1) as f64) * 1000.0;
+    if i % 2 == 0 {
+        step
+    } else {
+        -step
+    }
 }
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    dotenv().ok();
+    let ctx = SparkContext::load("BTC_USDC").await?;
+    let market = &ctx.market;
+    let main_wallet = &ctx.wallet;
 
-    // Environment variables
-    let mnemonic = env::var("MNEMONIC")?;
-    let contract_id = env::var("BTC_USDC_CONTRACT_ID")?;
+    // Fuel wallet address
+    let wallet_id = ctx.wallet_id();
+    println!("Wallet Address: {:?}", main_wallet.address().to_string());
 
-    // Connect to provider
-    let provider_url = env::var("PROVIDER")?;
-    let provider = Provider::connect(provider_url).await?;
+    // Depositing Assets
+    let btc = &ctx.market_info.base;
+    let btc_amount = btc.units(1)?.raw(); // Deposit 1 BTC
 
-    let main_wallet =
-        WalletUnlocked::new_from_mnemonic_phrase(&mnemonic, Some(provider.clone())).unwrap();
-    let contract_id = ContractId::from_str(&contract_id).unwrap();
-    let market = SparkMarketContract::new(contract_id.clone(), main_wallet.clone()).await;
+    let usdc = &ctx.market_info.quote;
+    let usdc_amount = usdc.units(80_000)?.raw(); // Deposit 80,000 USDC
 
-    // Fuel wallet address
-    let wallet_id: Identity = main_wallet.address().into();
-    println!("Wallet Address: {:?}", main_wallet.address().to_string());
+    // Fetch the current price of Bitcoin, agreed by the default sources
+    let consensus = Oracle::usd("BTC")?.consensus().await?;
+    println!("Current BTC price: {}", consensus);
+    let current_price = consensus.price;
 
-    let btc_id: String = env::var("BTC_ID")?;
-    let usdc_id: String = env::var("USDC_ID")?;
+    let base_price = current_price;
 
-    // Depositing Assets
-    let btc_id = AssetId::from_str(&btc_id).unwrap();
-    let btc_amount = format_value_with_decimals(1.0, 8); // Deposit 1 BTC
+    // Buy/Sell order amounts
+    let buy_amount = btc.parse("0.1")?.raw(); // 0.1 BTC per order
+    let sell_amount = btc.parse("0.1")?.raw(); // 0.1 BTC per order
 
-    let usdc_id = AssetId::from_str(&usdc_id).unwrap();
-    let usdc_amount = format_value_with_decimals(80_000.0, 6); // Deposit 80,000 USDC
+    // Number of orders to create
+    let n_orders = 10;
+
+    // A dry run simulates the deposits and orders; the matches need them on chain
+    if dry_run_requested() {
+        let mut batch = BatchSubmitter::new(&ctx);
+        for (amount, asset_id) in [(btc_amount, btc.asset_id), (usdc_amount, usdc.asset_id)] {
+            batch.push(
+                market
+                    .get_instance()
+                    .methods()
+                    .deposit()
+                    .with_contract_ids(&ctx.contract_ids())
+                    .call_params(CallParameters::new(amount, asset_id, DEPOSIT_GAS))?,
+            );
+        }
+        for (order_type, amount) in [(OrderType::Buy, buy_amount), (OrderType::Sell, sell_amount)] {
+            for i in 0..n_orders {
+                let price = base_price + price_adjustment(i);
+                batch.push(
+                    market
+                        .get_instance()
+                        .methods()
+                        .open_order(
+                            amount,
+                            order_type.clone(),
+                            ctx.market_info
+                                .price_from_f64(price, Rounding::Nearest)?
+                                .raw(),
+                        )
+                        .with_contract_ids(&ctx.contract_ids()),
+                );
+            }
+        }
+        for (tx, simulation) in batch.simulate().await?.iter().enumerate() {
+            println!("Dry run of transaction {}: {}", tx, simulation);
+        }
+        return Ok(());
+    }
 
     println!("Depositing BTC...");
-    market.deposit(btc_amount, btc_id).await?;
+    market.deposit(btc_amount, btc.asset_id).await?;
     println!("BTC Deposit Successful");
 
     println!("Depositing USDC...");
-    market.deposit(usdc_amount, usdc_id).await?;
+    market.deposit(usdc_amount, usdc.asset_id).await?;
     println!("USDC Deposit Successful");
 
-    // Fetch the current price of Bitcoin from CoinGecko API
-    let url = "https://api.coingecko.com/api/v3/simple/price?ids=bitcoin&vs_currencies=usd";
-    let response = reqwest::get(url).await?.json::<Value>().await?;
-    let current_price = response["bitcoin"]["usd"]
-        .as_f64()
-        .ok_or_else(|| anyhow::anyhow!("Failed to parse BTC price from API response"))?;
-    println!("Current BTC price: ${:.2}", current_price);
-
-    let base_price = current_price;
-
-    // Buy/Sell order amounts
-    let buy_amount = format_value_with_decimals(0.1, 8); // 0.1 BTC per order
-    let sell_amount = format_value_with_decimals(0.1, 8); // 0.1 BTC per order
-
     // Vectors to store order IDs
     let mut buy_order_ids = Vec::new();
     let mut sell_order_ids = Vec::new();
 
-    // Number of orders to create
-    let n_orders = 10;
-
     // Creating Buy Orders
     for i in 0..n_orders {
-        // Adjust the price
-        let price_adjustment = if i % 2 == 0 {
-            ((i / 2 + 1) as f64) * 1000.0 // Increase by 1000
-        } else {
-            -((i / 2 + 1) as f64) * 1000.0 // Decrease by 1000
-        };
-        let price = base_price + price_adjustment;
+        let price = base_price + price_adjustment(i);
 
-        let price_formatted = format_value_with_decimals(price, 9); // Price with 9 decimals
+        let price_formatted = ctx
+            .market_info
+            .price_from_f64(price, Rounding::Nearest)?
+            .raw();
 
         println!(
             "Opening Buy Order: {} BTC at ${:.2} per BTC",
-            format_to_readable_value(buy_amount, 8),
+            btc.quantity(buy_amount),
             price
         );
         let order_id = market
@@ -107,21 +120,18 @@ async fn main() -> Result<()> {
         buy_order_ids.push(order_id);
     }
 
-    // Creating Sell Orders
+    // Creating Sell Orders (at the same prices as the buy orders)
     for i in 0..n_orders {
-        // Adjust the price (same as for buy orders)
-        let price_adjustment = if i % 2 == 0 {
-            ((i / 2 + 1) as f64) * 1000.0 // Increase by 1000
-        } else {
-            -((i / 2 + 1) as f64) * 1000.0 // Decrease by 1000
-        };
-        let price = base_price + price_adjustment;
+        let price = base_price + price_adjustment(i);
 
-        let price_formatted = format_value_with_decimals(price, 9); // Price with 9 decimals
+        let price_formatted = ctx
+            .market_info
+            .price_from_f64(price, Rounding::Nearest)?
+            .raw();
 
         println!(
             "Opening Sell Order: {} BTC at ${:.2} per BTC",
-            format_to_readable_value(sell_amount, 8),
+            btc.quantity(sell_amount),
             price
         );
         let order_id = market
@@ -131,21 +141,35 @@ async fn main() -> Result<()> {
         sell_order_ids.push(order_id);
     }
 
-    // Matching Orders
-    for i in 0..n_orders {
-        let buy_order_id = buy_order_ids[i];
-        let sell_order_id = sell_order_ids[i];
-
-        println!(
-            "Matching Orders: Buy {:?} with Sell {:?}",
-            buy_order_id, sell_order_id
+    // Matching all pairs in as few transactions as fit
+    let mut batch = BatchMatcher::new(&ctx);
+    for (buy_order_id, sell_order_id) in buy_order_ids.into_iter().zip(sell_order_ids) {
+        batch.push(buy_order_id, sell_order_id);
+    }
+    println!("Matching {} pairs", batch.len());
+    let report = batch.submit().await?;
+    for pair in &report.pairs {
+        let pair_name = format!(
+            "Buy {} with Sell {}",
+            hex_str_from_bits256(&pair.buy),
+            hex_str_from_bits256(&pair.sell)
         );
-        market.match_order_pair(buy_order_id, sell_order_id).await?;
-        println!("Orders Matched Successfully");
-
-        // Wait 3 seconds before matching the next pair
-        sleep(Duration::from_secs(3)).await;
+        match &pair.status {
+            PairStatus::Matched { tx_id, amount } => {
+                println!("Matched {}: filled {} BTC in {}", pair_name, amount, tx_id)
+            }
+            PairStatus::Skipped { reason } => println!("Skipped {}: {}", pair_name, reason),
+            PairStatus::Failed { error } => println!("Failed {}: {}", pair_name, error),
+            PairStatus::NotSent => println!("Not sent {}", pair_name),
+        }
     }
+    println!(
+        "{} of {} pairs matched in {} transactions, {} BTC filled",
+        report.matched(),
+        report.pairs.len(),
+        report.batch.tx_ids().len(),
+        report.filled(btc.decimals)?
+    );
 
     // Fetch and display the account balance after matching
     let account = market.account(wallet_id).await?.value;
@@ -1,55 +1,28 @@
-use dotenv::dotenv;
-use std::{env, error::Error, str::FromStr};
+use std::error::Error;
 
 use fuels::{
-    accounts::{provider::Provider, wallet::WalletUnlocked},
-    prelude::CallParameters,
-    programs::calls::CallHandler,
-    types::{AssetId, ContractId, Identity},
+    accounts::wallet::WalletUnlocked, prelude::CallParameters, programs::calls::CallHandler,
 };
-
-use spark_market_sdk::{OrderType, SparkMarketContract};
-
+use spark_examples::{simulate::Simulator, utils::dry_run_requested, SparkContext};
+use spark_market_sdk::OrderType;
 use tokio::time::{sleep, Duration};
 
-pub fn format_value_with_decimals(value: u64, decimals: u32) -> u64 {
-    value * 10u64.pow(decimals)
-}
-
-pub fn format_to_readable_value(value: u64, decimals: u32) -> f64 {
-    value as f64 / 10u64.pow(decimals) as f64
-}
-
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn Error>> {
-    dotenv().ok();
-
-    // Environment variables
-    let mnemonic = env::var("MNEMONIC")?;
-    let contract_id = env::var("BTC_USDC_CONTRACT_ID")?;
-
-    // Connect to provider
-    let provider_url = env::var("PROVIDER")?;
-    let provider = Provider::connect(provider_url).await?;
-
-    let main_wallet =
-        WalletUnlocked::new_from_mnemonic_phrase(&mnemonic, Some(provider.clone())).unwrap();
-    let contract_id = ContractId::from_str(&contract_id)?;
-    let market = SparkMarketContract::new(contract_id.clone(), main_wallet.clone()).await;
+    let ctx = SparkContext::load("BTC_USDC").await?;
+    let market = &ctx.market;
+    let main_wallet = &ctx.wallet;
 
     // Fuel wallet address
-    let wallet_id: Identity = main_wallet.address().into();
+    let wallet_id = ctx.wallet_id();
     println!("wallet {:?}", main_wallet.address().to_string());
 
-    let btc_id: String = env::var("BTC_ID")?;
-    let usdc_id: String = env::var("USDC_ID")?;
-
     // Depositing Assets
-    let btc_id = AssetId::from_str(&btc_id)?;
-    let btc_amount = format_value_with_decimals(1, 6);
+    let btc = &ctx.market_info.base;
+    let btc_amount = btc.parse("0.01")?.raw(); // 0.01 BTC
 
-    let usdc_id = AssetId::from_str(&usdc_id)?;
-    let usdc_amount = format_value_with_decimals(3_000, 6);
+    let usdc = &ctx.market_info.quote;
+    let usdc_amount = usdc.units(3_000)?.raw(); // 3,000 USDC
 
     let account = market.account(wallet_id.clone()).await?.value;
     println!(
@@ -64,7 +37,7 @@ async fn main() -> Result<(), Box<dyn Error>> {
     > = CallHandler::new_multi_call(main_wallet.clone());
 
     // Deposit Calls
-    let deposit_btc_call_params = CallParameters::new(btc_amount, btc_id, 20_000_000);
+    let deposit_btc_call_params = CallParameters::new(btc_amount, btc.asset_id, 20_000_000);
     let deposit_btc_call = market
         .get_instance()
         .methods()
@@ -72,7 +45,7 @@ async fn main() -> Result<(), Box<dyn Error>> {
         .call_params(deposit_btc_call_params)
         .unwrap();
 
-    let deposit_usdc_call_params = CallParameters::new(usdc_amount, usdc_id, 20_000_000);
+    let deposit_usdc_call_params = CallParameters::new(usdc_amount, usdc.asset_id, 20_000_000);
     let deposit_usdc_call = market
         .get_instance()
         .methods()
@@ -90,16 +63,22 @@ async fn main() -> Result<(), Box<dyn Error>> {
     let open_order_call_params = CallParameters::default();
 
     let buy_order_type = OrderType::Buy;
-    let buy_order_amount = 100_000; // 0.001 BTC
+    let buy_order_amount = btc.parse("0.001")?.raw(); // 0.001 BTC
     let buy_start_price = 50_000u64; // 50k
-    let sell_order_amount = 100_000; // 0.001 BTC
+    let sell_order_amount = btc.parse("0.001")?.raw(); // 0.001 BTC
     let sell_start_price = 51_000u64; // 51k
     let step = 500;
 
     // Creating Buy / Sell Limit Orders in a single transaction
     for i in 0..8 {
-        let buy_open_price = (buy_start_price + i * step) * 1_000_000_000_u64;
-        let sell_open_price = (sell_start_price + i * step) * 1_000_000_000_u64;
+        let buy_open_price = ctx
+            .market_info
+            .price_from_units(buy_start_price + i * step)?
+            .raw();
+        let sell_open_price = ctx
+            .market_info
+            .price_from_units(sell_start_price + i * step)?
+            .raw();
 
         let buy_open_order_call = market
             .get_instance()
@@ -119,6 +98,12 @@ async fn main() -> Result<(), Box<dyn Error>> {
         multi_call_handler = multi_call_handler.add_call(sell_open_order_call);
     }
 
+    if dry_run_requested() {
+        let simulation = Simulator::new(&ctx).multi_call(multi_call_handler).await?;
+        println!("Dry run, nothing sent: {}", simulation);
+        return Ok(());
+    }
+
     // Execute all the prepared calls in a single transaction (deposit & open orders)
     let multicall_tx_result = multi_call_handler.submit().await?;
 
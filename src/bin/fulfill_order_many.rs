@@ -1,54 +1,30 @@
-use dotenv::dotenv;
-use std::env;
+use std::error::Error;
 
 use fuels::{
-    accounts::provider::Provider, accounts::wallet::WalletUnlocked, prelude::CallParameters,
-    programs::calls::CallHandler, types::AssetId, types::ContractId, types::Identity,
+    accounts::wallet::WalletUnlocked, prelude::CallParameters, programs::calls::CallHandler,
 };
-use std::str::FromStr;
-
-use spark_market_sdk::{LimitType, OrderType, SparkMarketContract};
-use std::error::Error;
-use tokio::time::{timeout, Duration}; // Import the timeout functionality
-
-pub fn format_value_with_decimals(value: u64, decimals: u32) -> u64 {
-    value * 10u64.pow(decimals)
-}
-
-pub fn format_to_readable_value(value: u64, decimals: u32) -> f64 {
-    value as f64 / 10u64.pow(decimals) as f64
-}
+use spark_examples::{
+    simulate::Simulator, utils::dry_run_requested, OrderBook, SparkContext, Swap,
+};
+use spark_market_sdk::{LimitType, OrderType};
+use tokio::time::{timeout, Duration};
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn Error>> {
-    dotenv().ok();
-
-    // Environment variables
-    let mnemonic = env::var("MNEMONIC")?;
-    let contract_id = env::var("BTC_USDC_CONTRACT_ID")?;
-
-    // Connect to provider
-    let provider_url = env::var("PROVIDER")?;
-    let provider = Provider::connect(provider_url).await?;
-
-    let main_wallet =
-        WalletUnlocked::new_from_mnemonic_phrase(&mnemonic, Some(provider.clone())).unwrap();
-    let contract_id = ContractId::from_str(&contract_id)?;
-    let market = SparkMarketContract::new(contract_id.clone(), main_wallet.clone()).await;
+    let ctx = SparkContext::load("BTC_USDC").await?;
+    let market = &ctx.market;
+    let main_wallet = &ctx.wallet;
 
     // Fuel wallet address
-    let wallet_id: Identity = main_wallet.address().into();
+    let wallet_id = ctx.wallet_id();
     println!("wallet {:?}", main_wallet.address().to_string());
 
-    let btc_id: String = env::var("BTC_ID")?;
-    let usdc_id: String = env::var("USDC_ID")?;
-
     // Depositing Assets
-    let btc_id = AssetId::from_str(&btc_id)?;
-    let btc_amount = format_value_with_decimals(1, 6);
+    let btc = &ctx.market_info.base;
+    let btc_amount = btc.parse("0.01")?.raw(); // 0.01 BTC
 
-    let usdc_id = AssetId::from_str(&usdc_id)?;
-    let usdc_amount = format_value_with_decimals(3_000, 6);
+    let usdc = &ctx.market_info.quote;
+    let usdc_amount = usdc.units(3_000)?.raw(); // 3,000 USDC
 
     let mut multi_call_handler: CallHandler<
         WalletUnlocked,
@@ -57,7 +33,7 @@ async fn main() -> Result<(), Box<dyn Error>> {
     > = CallHandler::new_multi_call(main_wallet.clone());
 
     // Deposit Calls
-    let deposit_btc_call_params = CallParameters::new(btc_amount, btc_id, 30_000_000);
+    let deposit_btc_call_params = CallParameters::new(btc_amount, btc.asset_id, 30_000_000);
     let deposit_btc_call = market
         .get_instance()
         .methods()
@@ -65,7 +41,7 @@ async fn main() -> Result<(), Box<dyn Error>> {
         .call_params(deposit_btc_call_params)
         .unwrap();
 
-    let deposit_usdc_call_params = CallParameters::new(usdc_amount, usdc_id, 30_000_000);
+    let deposit_usdc_call_params = CallParameters::new(usdc_amount, usdc.asset_id, 30_000_000);
     let deposit_usdc_call = market
         .get_instance()
         .methods()
@@ -83,12 +59,15 @@ async fn main() -> Result<(), Box<dyn Error>> {
 
     let open_order_call_params = CallParameters::default();
 
-    let sell_order_amount = 100_000; // 0.001 BTC
+    let sell_order_amount = btc.parse("0.001")?.raw(); // 0.001 BTC
     let sell_start_price = 50_500u64;
     let step = 100;
 
     for i in 0..5 {
-        let sell_open_price = (sell_start_price + i * step) * 1_000_000_000_u64;
+        let sell_open_price = ctx
+            .market_info
+            .price_from_units(sell_start_price + i * step)?
+            .raw();
 
         let sell_open_order_call = market
             .get_instance()
@@ -100,6 +79,13 @@ async fn main() -> Result<(), Box<dyn Error>> {
         multi_call_handler = multi_call_handler.add_call(sell_open_order_call);
     }
 
+    // A dry run stops here, the swap takes the orders this would open
+    if dry_run_requested() {
+        let simulation = Simulator::new(&ctx).multi_call(multi_call_handler).await?;
+        println!("Dry run, nothing sent: {}", simulation);
+        return Ok(());
+    }
+
     // Execute all the prepared calls in a single transaction (deposit & open orders)
     let _multicall_tx_result = multi_call_handler.submit().await?;
     // println!("Multicall tx result: {:?}", multicall_tx_result);
@@ -121,21 +107,35 @@ async fn main() -> Result<(), Box<dyn Error>> {
     println!("account before fulfill_order_many: {:?}", account);
 
     // Swap Order Details
-    let buy_order_amount = 500_000; // 0.005 BTC
-    let buy_start_price = 50_500 * 1_000_000_000_u64;
-    let slippage = 10u64; // 10%
-
-    let swap_order = market
-        .fulfill_many(
-            buy_order_amount,
-            OrderType::Buy,
-            LimitType::IOC,
-            buy_start_price,
-            slippage,
-            order_ids,
-        )
+    let buy_order_amount = btc.parse("0.005")?; // 0.005 BTC
+    let max_slippage_bps = 1_000; // up to 10% past the best ask
+
+    // Take the best of our resting sells, all or nothing
+    let book = OrderBook::builder(market, &ctx.market_info)
+        .order_ids(order_ids)
+        .build()
         .await?;
-    println!("result: {:?}", swap_order);
+    let (_, taker_bps) = market.protocol_fee_user(wallet_id).await?.value;
+    let swap = Swap::new(&ctx, book)
+        .taker_bps(taker_bps)
+        .limit_type(LimitType::FOK);
+    let preview = swap.preview(OrderType::Buy, buy_order_amount, max_slippage_bps)?;
+    println!(
+        "Buying {} BTC for {} USDC ({} fees), average {:.2}, impact {:.1} bps, worst price {}",
+        preview.output,
+        preview.input,
+        preview.fees,
+        preview.average_price,
+        preview.price_impact_bps,
+        preview.limit_price
+    );
+    let result = swap.execute(&preview).await?;
+    println!(
+        "Bought {} BTC for {} USDC in {}",
+        result.received,
+        result.spent,
+        result.tx_id.unwrap_or_default()
+    );
 
     let order_ids = market.user_orders(wallet_id).await?.value;
     println!("Number of orders: {:?}", order_ids.len());
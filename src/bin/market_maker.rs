@@ -0,0 +1,88 @@
+//! Quotes a band of buy and sell orders around a reference price on any market:
+//!
+//! ```text
+//! cargo run --bin market_maker -- ETH/USDC
+//! MM_FAIR_VALUE=0.5 cargo run --bin market_maker -- TRMP/KMLA
+//! cargo run --bin market_maker -- ETH/USDC --dry-run
+//! ```
+//!
+//! The reference price is the oracle consensus for the base asset in USD, or
+//! `MM_FAIR_VALUE` / `MM_PRICE_FILE` for assets without a public price.
+//! `MM_LEVELS`, `MM_SPREAD_BPS`, `MM_STEP_BPS` and `MM_ORDER_VALUE` shape the band.
+//! The band leans against the inventory: `MM_TARGET_BASE_RATIO` is the share of
+//! value to keep in base, `MM_SKEW_BPS` / `MM_SIZE_SKEW_BPS` the largest price and
+//! size shifts, and `MM_MIN_BASE_RATIO` / `MM_MAX_BASE_RATIO` where one side stops.
+//! With `--dry-run` (or `SPARK_DRY_RUN=true`) each cycle's cancels, deposits and
+//! opens are simulated and printed instead of sent.
+
+use std::{env, str::FromStr, time::Duration};
+
+use anyhow::{anyhow, Result};
+use spark_examples::{
+    maker::{BandStrategy, InventorySkew, MakerConfig, MarketMaker},
+    oracle::{FilePrice, StaticPrice},
+    utils::{dry_run_requested, first_arg},
+    Oracle, PriceSource, SparkContext,
+};
+
+fn env_or<T: FromStr>(key: &str, default: T) -> Result<T> {
+    match env::var(key) {
+        Ok(value) => value
+            .parse()
+            .map_err(|_| anyhow!("Invalid {}: {}", key, value)),
+        Err(_) => Ok(default),
+    }
+}
+
+fn price_source(symbol: &str) -> Result<Box<dyn PriceSource>> {
+    if let Ok(value) = env::var("MM_FAIR_VALUE") {
+        let price = value
+            .parse()
+            .map_err(|_| anyhow!("Invalid MM_FAIR_VALUE: {}", value))?;
+        return Ok(Box::new(StaticPrice::new("MM_FAIR_VALUE", price)));
+    }
+    if let Ok(path) = env::var("MM_PRICE_FILE") {
+        return Ok(Box::new(FilePrice::new(path)));
+    }
+    Ok(Box::new(Oracle::usd(symbol)?))
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let market = first_arg().unwrap_or_else(|| "ETH/USDC".to_string());
+    let ctx = SparkContext::load(&market).await?;
+    println!(
+        "Making {} as {}",
+        ctx.market_info.name(),
+        ctx.wallet.address()
+    );
+
+    // Five levels from 0.10% to 1.50% each side, $50 per order ($500 in total)
+    let band = BandStrategy::new(
+        env_or("MM_LEVELS", 5)?,
+        env_or("MM_SPREAD_BPS", 10)?,
+        env_or("MM_STEP_BPS", 35)?,
+        env_or("MM_ORDER_VALUE", 50.0)?,
+    );
+    // Up to 0.05% lower prices and 50% larger sells when fully long base, and
+    // the other way round; one side only outside 10%..90% base
+    let strategy = InventorySkew::new(band)
+        .target(env_or("MM_TARGET_BASE_RATIO", 0.5)?)
+        .max_price_shift_bps(env_or("MM_SKEW_BPS", 5)?)
+        .max_size_skew_bps(env_or("MM_SIZE_SKEW_BPS", 5_000)?)
+        .limits(
+            env_or("MM_MIN_BASE_RATIO", 0.1)?,
+            env_or("MM_MAX_BASE_RATIO", 0.9)?,
+        );
+    let config = MakerConfig {
+        interval: Duration::from_secs(env_or("MM_INTERVAL_SECS", 10)?),
+        dry_run: dry_run_requested(),
+        ..MakerConfig::default()
+    };
+
+    let prices = price_source(&ctx.market_info.base.symbol)?;
+    MarketMaker::new(&ctx, prices, strategy)
+        .with_config(config)
+        .run()
+        .await
+}
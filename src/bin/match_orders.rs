@@ -1,47 +1,17 @@
-use dotenv::dotenv;
-use std::env;
-
-use fuels::{
-    accounts::provider::Provider, accounts::wallet::WalletUnlocked, types::AssetId,
-    types::ContractId, types::Identity,
-};
-use std::str::FromStr;
-
 use anyhow::Result;
-use spark_market_sdk::{OrderType, SparkMarketContract};
-
-pub fn format_value_with_decimals(value: u64, decimals: u32) -> u64 {
-    value * 10u64.pow(decimals)
-}
-
-pub fn format_to_readable_value(value: u64, decimals: u32) -> f64 {
-    value as f64 / 10u64.pow(decimals) as f64
-}
+use spark_examples::SparkContext;
+use spark_market_sdk::OrderType;
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    dotenv().ok();
-
-    // Environment variables
-    let mnemonic = env::var("MNEMONIC")?;
-    let contract_id = env::var("BTC_USDC_CONTRACT_ID")?;
-
-    // Connect to provider
-    let provider_url = env::var("PROVIDER")?;
-    let provider = Provider::connect(provider_url).await?;
-
-    let main_wallet =
-        WalletUnlocked::new_from_mnemonic_phrase(&mnemonic, Some(provider.clone())).unwrap();
-    let contract_id = ContractId::from_str(&contract_id).unwrap();
-    let market = SparkMarketContract::new(contract_id.clone(), main_wallet.clone()).await;
+    let ctx = SparkContext::load("BTC_USDC").await?;
+    let market = &ctx.market;
+    let main_wallet = &ctx.wallet;
 
     // Fuel wallet address
-    let wallet_id: Identity = main_wallet.address().into();
+    let wallet_id = ctx.wallet_id();
     println!("wallet {:?}", main_wallet.address().to_string());
 
-    let btc_id: String = env::var("BTC_ID")?;
-    let usdc_id: String = env::var("USDC_ID")?;
-
     // Getting asset balances
     let account = market.account(wallet_id).await?.value;
     let liquid_base = account.liquid.base;
@@ -51,49 +21,47 @@ async fn main() -> Result<()> {
     println!("USDC Balance: {:?}", liquid_quote);
 
     // Depositing Assets
-    let btc_id = AssetId::from_str(&btc_id).unwrap();
-    let btc_amount = format_value_with_decimals(1, 7);
+    let btc = &ctx.market_info.base;
+    let btc_amount = btc.parse("0.1")?; // 0.1 BTC
 
-    let usdc_id = AssetId::from_str(&usdc_id).unwrap();
-    let usdc_amount = format_value_with_decimals(10_000, 5);
+    let usdc = &ctx.market_info.quote;
+    let usdc_amount = usdc.units(10_000)?; // 10,000 USDC
 
     println!("Depositing BTC");
-    market.deposit(btc_amount, btc_id).await?;
+    market.deposit(btc_amount.raw(), btc.asset_id).await?;
     println!("Deposit Success");
 
     println!("Depositing USDC");
-    market.deposit(usdc_amount, usdc_id).await?;
+    market.deposit(usdc_amount.raw(), usdc.asset_id).await?;
     println!("Deposit Success");
 
     // Creating Buy / Sell Limit Orders
 
     // Buying 10_000 USDC worth of BTC
-    let buy_amount = 10_000_000; // 0.1 BTC
+    let buy_amount = btc.parse("0.1")?; // 0.1 BTC
     let order_type: OrderType = OrderType::Buy;
-    let price: u64 = 75_000_000_000_000_u64;
+    let price = ctx.market_info.price_from_units(75_000)?;
 
     println!(
         "Opening Buy Order: {} BTC at {} BTC/USDC",
-        format_to_readable_value(buy_amount, 8),
-        format_to_readable_value(price, 9)
+        buy_amount, price
     );
     let order_id0 = market
-        .open_order(buy_amount, order_type, price)
+        .open_order(buy_amount.raw(), order_type, price.raw())
         .await?
         .value;
 
     // Selling 0.1 BTC for 70k USDC
-    let sell_amount: u64 = btc_amount;
+    let sell_amount = btc_amount;
     let order_type = OrderType::Sell;
-    let price = 75_000_000_000_000_u64;
+    let price = ctx.market_info.price_from_units(75_000)?;
 
     println!(
         "Opening Sell Order: {} BTC at {} BTC/USDC",
-        format_to_readable_value(sell_amount, 8),
-        format_to_readable_value(price, 9)
+        sell_amount, price
     );
     let order_id1 = market
-        .open_order(sell_amount, order_type, price)
+        .open_order(sell_amount.raw(), order_type, price.raw())
         .await?
         .value;
 
@@ -0,0 +1,72 @@
+//! Keeps matching crossing orders on a market and collects the matcher fee:
+//!
+//! ```text
+//! cargo run --bin matcher -- ETH/USDC
+//! MATCHER_GAS_PRICE=2500 cargo run --bin matcher -- TRMP/KMLA
+//! cargo run --bin matcher -- ETH/USDC --dry-run
+//! ```
+//!
+//! Orders are found by following the market's events into `MATCHER_DB`
+//! (`spark-events.db` by default, shared with `spark index`) from
+//! `MATCHER_FROM_HEIGHT`. Gas is priced with the oracle's ETH/USD price, which
+//! suits USD quoted markets; set `MATCHER_GAS_PRICE` to the price of ETH in the
+//! quote asset otherwise. A match is only sent when the matcher fee is at least
+//! `MATCHER_MIN_PROFIT` times its gas cost. With `--dry-run` (or
+//! `SPARK_DRY_RUN=true`) the matches are simulated and printed instead of sent.
+
+use std::{env, str::FromStr, time::Duration};
+
+use anyhow::{anyhow, Result};
+use spark_examples::{
+    indexer::{EventStore, Indexer},
+    matcher::{Matcher, MatcherConfig},
+    oracle::StaticPrice,
+    utils::{dry_run_requested, first_arg},
+    Oracle, PriceSource, SparkContext,
+};
+
+fn env_or<T: FromStr>(key: &str, default: T) -> Result<T> {
+    match env::var(key) {
+        Ok(value) => value
+            .parse()
+            .map_err(|_| anyhow!("Invalid {}: {}", key, value)),
+        Err(_) => Ok(default),
+    }
+}
+
+fn gas_price() -> Result<Box<dyn PriceSource>> {
+    if let Ok(value) = env::var("MATCHER_GAS_PRICE") {
+        let price = value
+            .parse()
+            .map_err(|_| anyhow!("Invalid MATCHER_GAS_PRICE: {}", value))?;
+        return Ok(Box::new(StaticPrice::new("MATCHER_GAS_PRICE", price)));
+    }
+    Ok(Box::new(Oracle::usd("ETH")?))
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let market = first_arg().unwrap_or_else(|| "ETH/USDC".to_string());
+    let ctx = SparkContext::load(&market).await?;
+    println!(
+        "Matching {} as {}",
+        ctx.market_info.name(),
+        ctx.wallet.address()
+    );
+
+    let db = env_or("MATCHER_DB", "spark-events.db".to_string())?;
+    let store = EventStore::open(db, ctx.market_info.contract_id)?;
+    let indexer = Indexer::new(&ctx.market, ctx.provider.clone(), store);
+
+    let config = MatcherConfig {
+        interval: Duration::from_secs(env_or("MATCHER_INTERVAL_SECS", 5)?),
+        from_height: env_or("MATCHER_FROM_HEIGHT", 0)?,
+        min_profit_ratio: env_or("MATCHER_MIN_PROFIT", 1.0)?,
+        dry_run: dry_run_requested(),
+        ..MatcherConfig::default()
+    };
+    Matcher::new(&ctx, indexer, gas_price()?)
+        .with_config(config)
+        .run()
+        .await
+}
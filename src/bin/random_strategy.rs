@@ -1,80 +1,51 @@
-use dotenv::dotenv;
-use rand::Rng;
-use std::env;
-
-use fuels::{
-    accounts::provider::Provider, accounts::wallet::WalletUnlocked, types::AssetId,
-    types::ContractId, types::Identity,
-};
-use std::str::FromStr;
-
-use spark_market_sdk::{OrderType, SparkMarketContract};
 use std::error::Error;
 
-pub fn format_value_with_decimals(value: u64, decimals: u32) -> u64 {
-    value * 10u64.pow(decimals)
-}
-
-pub fn format_to_readble_value(value: u64, decimals: u32) -> f64 {
-    value as f64 / 10u64.pow(decimals) as f64
-}
+use rand::Rng;
+use spark_examples::{amount::Rounding, SparkContext, SparkError};
+use spark_market_sdk::OrderType;
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn Error>> {
-    dotenv().ok();
-
-    // Environment variables
-    let mnemonic = env::var("MNEMONIC")?;
-    let contract_id = env::var("BTC_USDC_CONTRACT_ID")?;
-
-    // Connect to provider
-    let provider_url = env::var("PROVIDER")?;
-    let provider = Provider::connect(provider_url).await?;
-
-    let main_wallet =
-        WalletUnlocked::new_from_mnemonic_phrase(&mnemonic, Some(provider.clone())).unwrap();
-    let contract_id = ContractId::from_str(&contract_id)?;
-    let market = SparkMarketContract::new(contract_id.clone(), main_wallet.clone()).await;
+    let ctx = SparkContext::load("BTC_USDC").await?;
+    let market = &ctx.market;
+    let main_wallet = &ctx.wallet;
 
     // Fuel wallet address
-    let wallet_id: Identity = main_wallet.address().into();
+    let wallet_id = ctx.wallet_id();
     println!("wallet {:?}", main_wallet.address().to_string());
 
-    let btc_id: String = env::var("BTC_ID")?;
-    let usdc_id: String = env::var("USDC_ID")?;
-
     // Getting asset balances
     let account = market.account(wallet_id).await.unwrap().value;
     println!("account balance: {:?}", account);
 
     // Depositing Assets
-    let btc_id = AssetId::from_str(&btc_id)?;
-    let btc_amount = format_value_with_decimals(1, 9);
+    let btc = &ctx.market_info.base;
+    let btc_amount = btc.units(10)?.raw(); // 10 BTC
 
-    let usdc_id = AssetId::from_str(&usdc_id)?;
-    let usdc_amount = format_value_with_decimals(100_000, 6);
+    let usdc = &ctx.market_info.quote;
+    let usdc_amount = usdc.units(100_000)?.raw(); // 100,000 USDC
 
     println!("Depositing BTC");
-    match market.deposit(btc_amount, btc_id).await {
+    match market.deposit(btc_amount, btc.asset_id).await {
         Ok(_) => {
             println!("Deposit Success");
             Ok(())
         }
         Err(e) => {
-            print!("Deposit error: {:?}", e);
+            print!("Deposit error: {}", SparkError::from(&e));
             Err(e)
         }
     }
     .unwrap();
 
     println!("Depositing USDC");
-    match market.deposit(usdc_amount, usdc_id).await {
+    match market.deposit(usdc_amount, usdc.asset_id).await {
         Ok(_) => {
             println!("Deposit Success");
             Ok(())
         }
         Err(e) => {
-            print!("Deposit error: {:?}", e);
+            print!("Deposit error: {}", SparkError::from(&e));
             Err(e)
         }
     }
@@ -84,34 +55,38 @@ async fn main() -> Result<(), Box<dyn Error>> {
     let mut rng = rand::thread_rng();
 
     for price in (46000..=72000).step_by(1000) {
-        let price = format_value_with_decimals(price, 9);
+        let price = ctx.market_info.price_from_units(price)?;
 
-        // Randomize the order amounts between 2 and 10 USD
-        let buy_amount = format_value_with_decimals(rng.gen_range(1..50), 6);
-        let sell_amount = format_value_with_decimals(rng.gen_range(3..30), 6);
+        // Randomize the order amounts between 0.01 and 0.5 BTC
+        let buy_amount = btc.from_f64(rng.gen_range(0.01..0.5), Rounding::Down)?;
+        let sell_amount = btc.from_f64(rng.gen_range(0.03..0.3), Rounding::Down)?;
 
         // Buy Order
         let order_type: OrderType = OrderType::Buy;
         println!(
             "Opening Buy Order: {} BTC at {} BTC/USDC",
-            format_to_readble_value(buy_amount, 8),
-            format_to_readble_value(price, 9)
+            buy_amount, price
         );
-        match market.open_order(buy_amount, order_type, price).await {
+        match market
+            .open_order(buy_amount.raw(), order_type, price.raw())
+            .await
+        {
             Ok(_) => println!("Open Buy Order Success"),
-            Err(e) => println!("Open Buy Order Error: {:?}", e),
+            Err(e) => println!("Open Buy Order Error: {}", SparkError::from(&e)),
         }
 
         // Sell Order
         let order_type: OrderType = OrderType::Sell;
         println!(
             "Opening Sell Order: {} BTC at {} BTC/USDC",
-            format_to_readble_value(sell_amount, 8),
-            format_to_readble_value(price, 9)
+            sell_amount, price
         );
-        match market.open_order(sell_amount, order_type, price).await {
+        match market
+            .open_order(sell_amount.raw(), order_type, price.raw())
+            .await
+        {
             Ok(_) => println!("Open Sell Order Success"),
-            Err(e) => println!("Open Sell Order Error: {:?}", e),
+            Err(e) => println!("Open Sell Order Error: {}", SparkError::from(&e)),
         }
     }
 
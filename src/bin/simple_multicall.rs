@@ -1,49 +1,27 @@
-use dotenv::dotenv;
-use std::env;
+use std::error::Error;
 
 use fuels::{
-    accounts::provider::Provider, accounts::wallet::WalletUnlocked, prelude::CallParameters,
-    programs::calls::CallHandler, types::AssetId, types::ContractId, types::Identity,
+    accounts::wallet::WalletUnlocked, prelude::CallParameters, programs::calls::CallHandler,
 };
-use std::str::FromStr;
-
-use spark_market_sdk::{OrderType, SparkMarketContract};
-use std::error::Error;
-
-pub fn format_value_with_decimals(value: u64, decimals: u32) -> u64 {
-    value * 10u64.pow(decimals)
-}
+use spark_examples::SparkContext;
+use spark_market_sdk::OrderType;
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn Error>> {
-    dotenv().ok();
-
-    // Load environment variables
-    let mnemonic = env::var("MNEMONIC")?;
-    let contract_id = env::var("BTC_USDC_CONTRACT_ID")?;
-
-    // Connect to provider
-    let provider_url = env::var("PROVIDER")?;
-    let provider = Provider::connect(provider_url).await?;
-
-    let main_wallet =
-        WalletUnlocked::new_from_mnemonic_phrase(&mnemonic, Some(provider.clone())).unwrap();
-    let contract_id = ContractId::from_str(&contract_id)?;
-    let market = SparkMarketContract::new(contract_id.clone(), main_wallet.clone()).await;
+    let ctx = SparkContext::load("BTC_USDC").await?;
+    let market = &ctx.market;
+    let main_wallet = &ctx.wallet;
 
     // Get wallet address
-    let wallet_id: Identity = main_wallet.address().into();
+    let wallet_id = ctx.wallet_id();
     println!("Wallet address: {:?}", main_wallet.address().to_string());
 
-    let btc_id: String = env::var("BTC_ID")?;
-    let usdc_id: String = env::var("USDC_ID")?;
-
     // Deposit assets (simplified for BTC and USDC)
-    let btc_id = AssetId::from_str(&btc_id)?;
-    let usdc_id = AssetId::from_str(&usdc_id)?;
+    let btc = &ctx.market_info.base;
+    let usdc = &ctx.market_info.quote;
 
-    let btc_amount = format_value_with_decimals(1, 8); // 1 BTC
-    let usdc_amount = format_value_with_decimals(10_000, 6); // 10,000 USDC
+    let btc_amount = btc.units(1)?.raw(); // 1 BTC
+    let usdc_amount = usdc.units(10_000)?.raw(); // 10,000 USDC
 
     let mut multi_call_handler: CallHandler<
         WalletUnlocked,
@@ -52,7 +30,7 @@ async fn main() -> Result<(), Box<dyn Error>> {
     > = CallHandler::new_multi_call(main_wallet.clone());
 
     // Deposit BTC
-    let deposit_btc_call_params = CallParameters::new(btc_amount, btc_id, 5_000_000);
+    let deposit_btc_call_params = CallParameters::new(btc_amount, btc.asset_id, 5_000_000);
     let deposit_btc_call = market
         .get_instance()
         .methods()
@@ -62,7 +40,7 @@ async fn main() -> Result<(), Box<dyn Error>> {
     multi_call_handler = multi_call_handler.add_call(deposit_btc_call);
 
     // Deposit USDC
-    let deposit_usdc_call_params = CallParameters::new(usdc_amount, usdc_id, 5_000_000);
+    let deposit_usdc_call_params = CallParameters::new(usdc_amount, usdc.asset_id, 5_000_000);
     let deposit_usdc_call = market
         .get_instance()
         .methods()
@@ -73,8 +51,8 @@ async fn main() -> Result<(), Box<dyn Error>> {
 
     // Add Buy Order (simplified)
     let buy_order_type = OrderType::Buy;
-    let buy_order_amount = 1_000_000; // 0.01 BTC
-    let buy_price = 55_000 * 1_000_000_000_u64; // 55,000 USDC per BTC
+    let buy_order_amount = btc.parse("0.01")?.raw(); // 0.01 BTC
+    let buy_price = ctx.market_info.price_from_units(55_000)?.raw(); // 55,000 USDC per BTC
 
     let buy_order_call = market
         .get_instance()
@@ -86,8 +64,8 @@ async fn main() -> Result<(), Box<dyn Error>> {
 
     // Add Sell Order (simplified)
     let sell_order_type = OrderType::Sell;
-    let sell_order_amount = 1_000_000; // 0.01 BTC
-    let sell_price = 65_000 * 1_000_000_000_u64; // 65,000 USDC per BTC
+    let sell_order_amount = btc.parse("0.01")?.raw(); // 0.01 BTC
+    let sell_price = ctx.market_info.price_from_units(65_000)?.raw(); // 65,000 USDC per BTC
 
     let sell_order_call = market
         .get_instance()
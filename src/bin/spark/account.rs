@@ -0,0 +1,166 @@
+use anyhow::{bail, Result};
+use clap::Args;
+use fuels::{
+    prelude::{CallParameters, VariableOutputPolicy},
+    types::Identity,
+};
+use spark_examples::{
+    assets::AssetInfo,
+    batch::DEPOSIT_GAS,
+    simulate::Simulator,
+    utils::parse_identity,
+    views::{BalanceView, TransferView},
+    SparkContext,
+};
+use spark_market_sdk::AssetType;
+
+use crate::output::Output;
+
+#[derive(Args)]
+pub struct AccountArgs {
+    /// Wallet address to show, your own by default.
+    #[arg(long, value_parser = parse_identity)]
+    user: Option<Identity>,
+}
+
+#[derive(Args)]
+pub struct DepositArgs {
+    /// `base`, `quote` or the asset symbol, e.g. USDC.
+    #[arg(long)]
+    asset: String,
+    /// Amount in human units, e.g. 12.5.
+    #[arg(long)]
+    amount: String,
+}
+
+#[derive(Args)]
+pub struct WithdrawArgs {
+    /// `base`, `quote` or the asset symbol. With --all and no asset, both are withdrawn.
+    #[arg(long, required_unless_present = "all")]
+    asset: Option<String>,
+    /// Amount in human units.
+    #[arg(long, required_unless_present = "all", conflicts_with = "all")]
+    amount: Option<String>,
+    /// Withdraw the whole liquid balance.
+    #[arg(long)]
+    all: bool,
+}
+
+pub async fn show(ctx: &SparkContext, out: &Output, args: AccountArgs) -> Result<()> {
+    let user = args.user.unwrap_or_else(|| ctx.wallet_id());
+    let account = ctx.market.account(user.clone()).await?.value;
+
+    let info = &ctx.market_info;
+    out.rows(&[
+        BalanceView::new(&user, &info.base, account.liquid.base, account.locked.base),
+        BalanceView::new(
+            &user,
+            &info.quote,
+            account.liquid.quote,
+            account.locked.quote,
+        ),
+    ])
+}
+
+pub async fn deposit(ctx: &SparkContext, out: &Output, args: DepositArgs) -> Result<()> {
+    let (_, asset) = market_asset(ctx, &args.asset)?;
+    let amount = asset.parse(&args.amount)?;
+    if amount.is_zero() {
+        bail!("Deposit amount must be greater than zero");
+    }
+
+    let call = ctx
+        .market
+        .get_instance()
+        .methods()
+        .deposit()
+        .with_contract_ids(&ctx.contract_ids())
+        .call_params(CallParameters::new(
+            amount.raw(),
+            asset.asset_id,
+            DEPOSIT_GAS,
+        ))?;
+    if out.is_dry_run() {
+        let simulation = Simulator::new(ctx).call(call).await?;
+        return out.simulations(&[simulation]);
+    }
+
+    call.call().await?;
+
+    out.one(&TransferView::new("deposit", asset, amount.raw()))
+}
+
+pub async fn withdraw(ctx: &SparkContext, out: &Output, args: WithdrawArgs) -> Result<()> {
+    let assets = match &args.asset {
+        Some(name) => vec![market_asset(ctx, name)?],
+        None => vec![
+            (AssetType::Base, &ctx.market_info.base),
+            (AssetType::Quote, &ctx.market_info.quote),
+        ],
+    };
+
+    let account = ctx.market.account(ctx.wallet_id()).await?.value;
+
+    let mut withdrawn = Vec::new();
+    let mut simulations = Vec::new();
+    for (asset_type, asset) in assets {
+        let liquid = match asset_type {
+            AssetType::Base => account.liquid.base,
+            AssetType::Quote => account.liquid.quote,
+        };
+        let amount = match &args.amount {
+            Some(amount) => asset.parse(amount)?,
+            None => asset.quantity(liquid),
+        };
+        if amount.is_zero() {
+            continue;
+        }
+        if amount.raw() > liquid {
+            bail!(
+                "Cannot withdraw {} {}, only {} is liquid",
+                amount,
+                asset.symbol,
+                asset.quantity(liquid)
+            );
+        }
+
+        // The withdrawn coins leave through a variable output
+        let call = ctx
+            .market
+            .get_instance()
+            .methods()
+            .withdraw(amount.raw(), asset_type)
+            .with_contract_ids(&ctx.contract_ids())
+            .with_variable_output_policy(VariableOutputPolicy::Exactly(1));
+        if out.is_dry_run() {
+            simulations.push(Simulator::new(ctx).call(call).await?);
+            continue;
+        }
+
+        call.call().await?;
+        withdrawn.push(TransferView::new("withdraw", asset, amount.raw()));
+    }
+    if out.is_dry_run() {
+        return out.simulations(&simulations);
+    }
+    out.rows(&withdrawn)
+}
+
+/// Resolves `base`, `quote` or a symbol to one side of the current market.
+fn market_asset<'a>(ctx: &'a SparkContext, name: &str) -> Result<(AssetType, &'a AssetInfo)> {
+    let info = &ctx.market_info;
+    let name = name.to_ascii_uppercase();
+    if name == "BASE" || name == info.base.symbol {
+        Ok((AssetType::Base, &info.base))
+    } else if name == "QUOTE" || name == info.quote.symbol {
+        Ok((AssetType::Quote, &info.quote))
+    } else {
+        bail!(
+            "{} is not traded on the {} market (use base, quote, {} or {})",
+            name,
+            info.name(),
+            info.base.symbol,
+            info.quote.symbol
+        )
+    }
+}
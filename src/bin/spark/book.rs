@@ -0,0 +1,55 @@
+use std::{path::PathBuf, str::FromStr};
+
+use anyhow::Result;
+use clap::Args;
+use fuels::types::{Bits256, Bytes32, Identity};
+use spark_examples::{
+    utils::{bits256_from_hex_str, parse_identity},
+    views::{BookLevelView, Format},
+    OrderBook, SparkContext,
+};
+
+use crate::output::Output;
+
+#[derive(Args)]
+pub struct BookArgs {
+    /// SQLite database the market's events are indexed into to find its orders.
+    #[arg(long, env = "SPARK_EVENTS_DB", default_value = "spark-events.db")]
+    db: PathBuf,
+    /// Only show the open orders of this wallet. Repeatable; every maker's
+    /// orders when no source is given.
+    #[arg(long = "maker", value_parser = parse_identity)]
+    makers: Vec<Identity>,
+    /// Include this order id. Repeatable.
+    #[arg(long = "order", value_parser = bits256_from_hex_str)]
+    orders: Vec<Bits256>,
+    /// Include the orders opened by this transaction. Repeatable.
+    #[arg(long = "tx", value_parser = Bytes32::from_str)]
+    txs: Vec<Bytes32>,
+    /// Price levels shown per side.
+    #[arg(long, default_value_t = 10)]
+    levels: usize,
+}
+
+pub async fn show(ctx: &SparkContext, out: &Output, args: BookArgs) -> Result<()> {
+    let mut builder = OrderBook::builder(&ctx.market, &ctx.market_info);
+    // The whole book unless only some orders or transactions were asked for
+    if !args.makers.is_empty() || (args.orders.is_empty() && args.txs.is_empty()) {
+        builder = builder
+            .indexed(&ctx.provider, &args.db, &args.makers)
+            .await?;
+    }
+
+    let book = builder
+        .order_ids(args.orders)
+        .transactions(&ctx.provider, &args.txs)
+        .await?
+        .build()
+        .await?;
+
+    if out.format() == Format::Table {
+        println!("{}", book.ladder(args.levels));
+        return Ok(());
+    }
+    out.rows(&BookLevelView::from_book(&book, args.levels))
+}
@@ -0,0 +1,78 @@
+use std::path::PathBuf;
+
+use anyhow::Result;
+use clap::Args;
+use fuels::types::Identity;
+use spark_examples::{
+    indexer::{EventStore, Indexer},
+    utils::parse_identity,
+    views::TradeView,
+    SparkContext,
+};
+
+use tokio::time::{sleep, Duration};
+
+use crate::output::Output;
+
+#[derive(Args)]
+pub struct IndexArgs {
+    /// SQLite database the events are written to.
+    #[arg(long, env = "SPARK_EVENTS_DB", default_value = "spark-events.db")]
+    db: PathBuf,
+    /// Block height to start from on the first run; later runs resume after the last indexed block.
+    #[arg(long, default_value_t = 0)]
+    from: u32,
+    /// Keep following the chain, polling every this many seconds.
+    #[arg(long)]
+    follow: Option<u64>,
+}
+
+#[derive(Args)]
+pub struct TradesArgs {
+    /// SQLite database filled by `spark index`.
+    #[arg(long, env = "SPARK_EVENTS_DB", default_value = "spark-events.db")]
+    db: PathBuf,
+    /// Only fills where this wallet bought or sold, your own by default.
+    #[arg(long, value_parser = parse_identity, conflicts_with = "everyone")]
+    user: Option<Identity>,
+    /// Fills of every user.
+    #[arg(long)]
+    everyone: bool,
+    /// Only fills at or after this block height.
+    #[arg(long, default_value_t = 0)]
+    since: u32,
+}
+
+pub async fn index(ctx: &SparkContext, out: &Output, args: IndexArgs) -> Result<()> {
+    let store = EventStore::open(&args.db, ctx.market_info.contract_id)?;
+    let mut indexer = Indexer::new(&ctx.market, ctx.provider.clone(), store);
+
+    loop {
+        let stats = indexer.sync(args.from).await?;
+        if let Some(height) = stats.height {
+            out.status(&format!(
+                "Indexed {} blocks up to {}: {} events in {} transactions",
+                stats.blocks, height, stats.events, stats.transactions
+            ));
+        }
+        match args.follow {
+            Some(secs) => sleep(Duration::from_secs(secs)).await,
+            None => return Ok(()),
+        }
+    }
+}
+
+pub fn trades(ctx: &SparkContext, out: &Output, args: TradesArgs) -> Result<()> {
+    let store = EventStore::open(&args.db, ctx.market_info.contract_id)?;
+    let user = match (args.everyone, args.user) {
+        (true, _) => None,
+        (false, user) => Some(user.unwrap_or_else(|| ctx.wallet_id())),
+    };
+
+    let rows: Vec<_> = store
+        .trades(user.as_ref(), args.since)?
+        .iter()
+        .map(|trade| TradeView::new(&ctx.market_info, trade))
+        .collect();
+    out.rows(&rows)
+}
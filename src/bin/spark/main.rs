@@ -0,0 +1,100 @@
+//! `spark`: everyday market operations from one command line, e.g.
+//!
+//! ```text
+//! spark --market ETH/USDC orders list
+//! spark orders open --side buy --amount 0.01 --price 3200
+//! spark --format csv account
+//! spark --dry-run orders cancel --all
+//! ```
+
+mod account;
+mod book;
+mod history;
+mod markets;
+mod orders;
+mod output;
+mod route;
+mod swap;
+
+use anyhow::Result;
+use clap::{Parser, Subcommand};
+use spark_examples::{views::Format, SparkContext, SparkError};
+
+use output::Output;
+
+#[derive(Parser)]
+#[command(name = "spark", version, about = "Command line for Spark markets")]
+struct Cli {
+    /// Market as BASE/QUOTE or a contract id. Markets missing from the config
+    /// are resolved through the registry.
+    #[arg(long, global = true, env = "SPARK_MARKET", default_value = "ETH/USDC")]
+    market: String,
+
+    /// Output format: table, json or csv.
+    #[arg(long, global = true, default_value_t = Format::Table)]
+    format: Format,
+
+    /// Shorthand for --format json.
+    #[arg(long, global = true)]
+    json: bool,
+
+    /// Simulate state-changing commands and show what they would do instead
+    /// of sending anything.
+    #[arg(long, global = true, env = "SPARK_DRY_RUN")]
+    dry_run: bool,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// List, show, open, cancel and match orders.
+    Orders {
+        #[command(subcommand)]
+        command: orders::OrdersCommand,
+    },
+    /// Show the balances held in the market.
+    Account(account::AccountArgs),
+    /// Deposit into the market.
+    Deposit(account::DepositArgs),
+    /// Withdraw from the market.
+    Withdraw(account::WithdrawArgs),
+    /// Show the order book built from known makers, orders or transactions.
+    Book(book::BookArgs),
+    /// Index the market's events into a local SQLite database.
+    Index(history::IndexArgs),
+    /// Show indexed fills.
+    Trades(history::TradesArgs),
+    /// Show the fee tiers and your current one, or the fees of an order.
+    Fees(markets::FeesArgs),
+    /// Describe the market, or list every registered market with --all.
+    Markets(markets::MarketsArgs),
+    /// Plan, and with --execute trade, a purchase across every registered market.
+    Route(route::RouteArgs),
+    /// Preview, and with --execute send, a swap against the market's book.
+    Swap(swap::SwapArgs),
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let cli = Cli::parse();
+    let out = Output::new(if cli.json { Format::Json } else { cli.format }).dry_run(cli.dry_run);
+    let ctx = SparkContext::load(&cli.market).await?;
+
+    let result = match cli.command {
+        Command::Orders { command } => orders::run(&ctx, &out, command).await,
+        Command::Account(args) => account::show(&ctx, &out, args).await,
+        Command::Deposit(args) => account::deposit(&ctx, &out, args).await,
+        Command::Withdraw(args) => account::withdraw(&ctx, &out, args).await,
+        Command::Book(args) => book::show(&ctx, &out, args).await,
+        Command::Index(args) => history::index(&ctx, &out, args).await,
+        Command::Trades(args) => history::trades(&ctx, &out, args),
+        Command::Fees(args) => markets::fees(&ctx, &out, args).await,
+        Command::Markets(args) => markets::run(&ctx, &out, args).await,
+        Command::Route(args) => route::run(&ctx, &out, args).await,
+        Command::Swap(args) => swap::run(&ctx, &out, args).await,
+    };
+    // Reverts read as what went wrong rather than as raw receipts
+    result.map_err(|e| SparkError::from(&e).into())
+}
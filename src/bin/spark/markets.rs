@@ -0,0 +1,107 @@
+use std::path::PathBuf;
+
+use anyhow::Result;
+use clap::Args;
+use fuels::types::Identity;
+use spark_examples::{
+    indexer::EventStore,
+    maker::DesiredOrder,
+    market,
+    utils::parse_identity,
+    validate::Liquid,
+    views::{FeeTierView, MarketView, OrderFeesView},
+    FeeSchedule, SparkContext,
+};
+
+use crate::{orders::Side, output::Output};
+
+#[derive(Args)]
+pub struct MarketsArgs {
+    /// List every registered market between the configured assets.
+    #[arg(long)]
+    all: bool,
+}
+
+#[derive(Args)]
+pub struct FeesArgs {
+    /// Wallet whose tier to look up, your own by default.
+    #[arg(long, value_parser = parse_identity)]
+    user: Option<Identity>,
+    /// Quote volume traded, e.g. 25000, to show how far the next tier is.
+    /// Otherwise summed from the fills in --db.
+    #[arg(long)]
+    volume: Option<String>,
+    /// SQLite database filled by `spark index`.
+    #[arg(long, env = "SPARK_EVENTS_DB", default_value = "spark-events.db")]
+    db: PathBuf,
+    /// Show the fees of an order on this side instead of the tiers.
+    #[arg(long, value_enum, requires_all = ["amount", "price"])]
+    side: Option<Side>,
+    /// Amount of the base asset of that order.
+    #[arg(long, requires = "side")]
+    amount: Option<String>,
+    /// Price of that order.
+    #[arg(long, requires = "side")]
+    price: Option<String>,
+}
+
+/// Protocol fees are in basis points, the matcher fee and volumes in quote units.
+/// The tier is the one the market charges the user; the traded volume only
+/// tells how far the next one is.
+pub async fn fees(ctx: &SparkContext, out: &Output, args: FeesArgs) -> Result<()> {
+    let info = &ctx.market_info;
+    let schedule = FeeSchedule::from_market(info);
+    let user = args.user.unwrap_or_else(|| ctx.wallet_id());
+    let (maker_bps, taker_bps) = ctx.market.protocol_fee_user(user.clone()).await?.value;
+    let tier = schedule.user_tier(maker_bps, taker_bps);
+
+    let volume = match &args.volume {
+        Some(volume) => Some(info.quote.parse(volume)?),
+        None if args.db.exists() => {
+            let store = EventStore::open(&args.db, info.contract_id)?;
+            Some(FeeSchedule::traded_volume(
+                info,
+                &store.trades(Some(&user), 0)?,
+            )?)
+        }
+        None => None,
+    };
+    if let Some(volume) = volume {
+        let mut status = format!("Traded volume {} {}", volume, info.quote.symbol);
+        if let Some((next, missing)) = schedule.next_tier(volume) {
+            status.push_str(&format!(
+                ", {} more for {}/{} bps",
+                missing, next.maker_bps, next.taker_bps
+            ));
+        }
+        out.status(&status);
+    }
+
+    let (Some(side), Some(amount), Some(price)) = (args.side, &args.amount, &args.price) else {
+        return out.rows(&FeeTierView::from_schedule(&schedule, Some(tier)));
+    };
+    let order = DesiredOrder {
+        side: side.order_type(),
+        amount: info.base.parse(amount)?,
+        price: info.parse_price(price)?,
+    };
+    let fees = schedule.order_fees_in(info, &order, tier)?;
+    let account = ctx.market.account(user).await?.value;
+    let liquid = Liquid {
+        base: info.base.quantity(account.liquid.base),
+        quote: info.quote.quantity(account.liquid.quote),
+    };
+    out.one(&OrderFeesView::new(info, &order, volume, &fees, &liquid))
+}
+
+pub async fn run(ctx: &SparkContext, out: &Output, args: MarketsArgs) -> Result<()> {
+    if !args.all {
+        return out.one(&MarketView::from(&ctx.market_info));
+    }
+
+    let mut rows = Vec::new();
+    for contract_id in market::registered_markets(&ctx.registry, &ctx.assets).await? {
+        rows.push(MarketView::from(&ctx.market_info(contract_id).await?));
+    }
+    out.rows(&rows)
+}
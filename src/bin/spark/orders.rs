@@ -0,0 +1,256 @@
+use anyhow::{anyhow, Result};
+use clap::{Subcommand, ValueEnum};
+use fuels::types::{Bits256, Identity};
+use spark_examples::{
+    batch::BatchSubmitter,
+    maker::DesiredOrder,
+    simulate::Simulator,
+    utils::{bits256_from_hex_str, hex_str_from_bits256, parse_identity},
+    validate::{Liquid, OrderValidator},
+    views::{OrderTxView, OrderView},
+    SparkContext,
+};
+use spark_market_sdk::OrderType;
+
+use crate::output::Output;
+
+#[derive(Subcommand)]
+pub enum OrdersCommand {
+    /// List the open orders of a wallet, your own by default.
+    List {
+        /// Wallet address to list orders for.
+        #[arg(long, value_parser = parse_identity)]
+        user: Option<Identity>,
+    },
+    /// Show one order.
+    Show {
+        #[arg(value_parser = bits256_from_hex_str)]
+        id: Bits256,
+    },
+    /// Open a limit order.
+    Open {
+        #[arg(long, value_enum)]
+        side: Side,
+        /// Amount of the base asset, e.g. 0.01.
+        #[arg(long)]
+        amount: String,
+        /// Price in quote per base, e.g. 3200.5.
+        #[arg(long)]
+        price: String,
+    },
+    /// Cancel orders by id, or all of your orders with --all.
+    Cancel {
+        #[arg(value_parser = bits256_from_hex_str, required_unless_present = "all")]
+        ids: Vec<Bits256>,
+        #[arg(long, conflicts_with = "ids")]
+        all: bool,
+    },
+    /// Match a buy order with a sell order, or several orders at once.
+    Match {
+        #[arg(value_parser = bits256_from_hex_str, num_args = 2..)]
+        ids: Vec<Bits256>,
+    },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum Side {
+    Buy,
+    Sell,
+}
+
+impl Side {
+    pub fn order_type(self) -> OrderType {
+        match self {
+            Side::Buy => OrderType::Buy,
+            Side::Sell => OrderType::Sell,
+        }
+    }
+}
+
+pub async fn run(ctx: &SparkContext, out: &Output, command: OrdersCommand) -> Result<()> {
+    match command {
+        OrdersCommand::List { user } => {
+            list(ctx, out, user.unwrap_or_else(|| ctx.wallet_id())).await
+        }
+        OrdersCommand::Show { id } => show(ctx, out, id).await,
+        OrdersCommand::Open {
+            side,
+            amount,
+            price,
+        } => open(ctx, out, side, &amount, &price).await,
+        OrdersCommand::Cancel { ids, all } => {
+            let ids = if all {
+                ctx.market.user_orders(ctx.wallet_id()).await?.value
+            } else {
+                ids
+            };
+            cancel(ctx, out, ids).await
+        }
+        OrdersCommand::Match { ids } => match_orders(ctx, out, ids).await,
+    }
+}
+
+async fn fetch_order(ctx: &SparkContext, id: Bits256) -> Result<Option<OrderView>> {
+    let order = ctx.market.order(id).await?.value;
+    Ok(order.map(|order| {
+        OrderView::new(
+            &ctx.market_info,
+            &id,
+            &order.order_type,
+            order.amount,
+            order.price,
+            &order.owner,
+        )
+    }))
+}
+
+async fn list(ctx: &SparkContext, out: &Output, user: Identity) -> Result<()> {
+    let ids = ctx.market.user_orders(user).await?.value;
+
+    let mut rows = Vec::with_capacity(ids.len());
+    for id in ids {
+        if let Some(row) = fetch_order(ctx, id).await? {
+            rows.push(row);
+        }
+    }
+    out.rows(&rows)
+}
+
+async fn show(ctx: &SparkContext, out: &Output, id: Bits256) -> Result<()> {
+    let row = fetch_order(ctx, id)
+        .await?
+        .ok_or_else(|| anyhow!("Order {} not found", hex_str_from_bits256(&id)))?;
+    out.one(&row)
+}
+
+async fn open(
+    ctx: &SparkContext,
+    out: &Output,
+    side: Side,
+    amount: &str,
+    price: &str,
+) -> Result<()> {
+    let order = DesiredOrder {
+        side: side.order_type(),
+        amount: ctx.market_info.base.parse(amount)?,
+        price: ctx.market_info.parse_price(price)?,
+    };
+
+    // Refuse here what the market would revert on, before paying for gas
+    let liquid = Liquid::load(ctx).await?;
+    OrderValidator::for_wallet(ctx)
+        .await?
+        .check(&order, Some(&liquid))?;
+
+    let call = ctx
+        .market
+        .get_instance()
+        .methods()
+        .open_order(order.amount.raw(), order.side.clone(), order.price.raw())
+        .with_contract_ids(&ctx.contract_ids());
+    if out.is_dry_run() {
+        let simulation = Simulator::new(ctx).call(call).await?;
+        return out.simulations(&[simulation]);
+    }
+
+    let id = call.call().await?.value;
+
+    out.one(&OrderView::new(
+        &ctx.market_info,
+        &id,
+        &order.side,
+        order.amount.raw(),
+        order.price.raw(),
+        &ctx.wallet_id(),
+    ))
+}
+
+async fn cancel(ctx: &SparkContext, out: &Output, ids: Vec<Bits256>) -> Result<()> {
+    let mut batch = BatchSubmitter::new(ctx);
+    for id in &ids {
+        batch.push(
+            ctx.market
+                .get_instance()
+                .methods()
+                .cancel_order(*id)
+                .with_contract_ids(&ctx.contract_ids()),
+        );
+    }
+
+    if out.is_dry_run() {
+        return out.simulations(&batch.simulate().await?);
+    }
+
+    out.status(&format!("Cancelling {} orders", ids.len()));
+    let report = batch.submit().await?;
+    for chunk in report.chunks.iter().filter(|chunk| chunk.is_success()) {
+        out.status(&format!(
+            "{} orders in {}",
+            chunk.calls.len(),
+            chunk.tx_id.as_deref().unwrap_or_default()
+        ));
+    }
+
+    for (i, error) in report.failures() {
+        out.status(&format!(
+            "Could not cancel {}: {}",
+            hex_str_from_bits256(&ids[i]),
+            error
+        ));
+    }
+
+    // Orders that could not be cancelled are listed without a tx id
+    let rows: Vec<_> = ids
+        .iter()
+        .enumerate()
+        .map(|(i, id)| OrderTxView::new("cancel", id, report.tx_id_of(i).map(String::from)))
+        .collect();
+    out.rows(&rows)?;
+    report.ensure_success()
+}
+
+async fn match_orders(ctx: &SparkContext, out: &Output, ids: Vec<Bits256>) -> Result<()> {
+    let methods = ctx.market.get_instance().methods();
+    if out.is_dry_run() {
+        let simulator = Simulator::new(ctx);
+        let simulation = if let [buy, sell] = ids.as_slice() {
+            simulator
+                .call(
+                    methods
+                        .match_order_pair(*buy, *sell)
+                        .with_contract_ids(&ctx.contract_ids()),
+                )
+                .await?
+        } else {
+            simulator
+                .call(
+                    methods
+                        .match_order_many(ids)
+                        .with_contract_ids(&ctx.contract_ids()),
+                )
+                .await?
+        };
+        return out.simulations(&[simulation]);
+    }
+
+    let response = if let [buy, sell] = ids.as_slice() {
+        methods
+            .match_order_pair(*buy, *sell)
+            .with_contract_ids(&ctx.contract_ids())
+            .call()
+            .await?
+    } else {
+        methods
+            .match_order_many(ids.clone())
+            .with_contract_ids(&ctx.contract_ids())
+            .call()
+            .await?
+    };
+
+    let tx_id = response.tx_id.map(|id| format!("0x{}", id));
+    let rows: Vec<_> = ids
+        .iter()
+        .map(|id| OrderTxView::new("match", id, tx_id.clone()))
+        .collect();
+    out.rows(&rows)
+}
@@ -0,0 +1,78 @@
+use anyhow::Result;
+use serde::Serialize;
+use spark_examples::{
+    simulate::Simulation,
+    views::{render, render_one, EffectView, Format},
+};
+
+/// Prints command results as JSON, CSV or a table.
+pub struct Output {
+    format: Format,
+    dry_run: bool,
+}
+
+impl Output {
+    pub fn new(format: Format) -> Self {
+        Self {
+            format,
+            dry_run: false,
+        }
+    }
+
+    /// State-changing commands simulate their transactions and print the
+    /// effects instead of sending them.
+    pub fn dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    pub fn format(&self) -> Format {
+        self.format
+    }
+
+    pub fn is_dry_run(&self) -> bool {
+        self.dry_run
+    }
+
+    pub fn rows<T: Serialize + Default>(&self, rows: &[T]) -> Result<()> {
+        println!("{}", render(rows, self.format)?);
+        Ok(())
+    }
+
+    pub fn one<T: Serialize>(&self, row: &T) -> Result<()> {
+        println!("{}", render_one(row, self.format)?);
+        Ok(())
+    }
+
+    /// Prints one row per expected effect of dry-run transactions. Fails after
+    /// printing when any of them would revert.
+    pub fn simulations(&self, simulations: &[Simulation]) -> Result<()> {
+        self.status(&format!(
+            "Dry run of {} transactions, nothing sent",
+            simulations.len()
+        ));
+        let rows: Vec<_> = simulations
+            .iter()
+            .enumerate()
+            .flat_map(|(tx, simulation)| EffectView::from_simulation(tx, simulation))
+            .collect();
+        self.rows(&rows)?;
+        match simulations
+            .iter()
+            .find_map(|simulation| simulation.error.clone())
+        {
+            Some(error) => Err(error.into()),
+            None => Ok(()),
+        }
+    }
+
+    /// Progress messages go to stderr unless the output is a table, so that
+    /// JSON and CSV on stdout stay parseable.
+    pub fn status(&self, message: &str) {
+        if self.format == Format::Table {
+            println!("{}", message);
+        } else {
+            eprintln!("{}", message);
+        }
+    }
+}
@@ -0,0 +1,90 @@
+use std::path::PathBuf;
+
+use anyhow::Result;
+use clap::Args;
+use fuels::types::Identity;
+use spark_examples::{utils::parse_identity, views::RouteLegView, Router, SparkContext};
+
+use crate::output::Output;
+
+#[derive(Args)]
+pub struct RouteArgs {
+    /// Asset to receive, e.g. ETH.
+    #[arg(long)]
+    buy: String,
+    /// Amount to receive in human units.
+    #[arg(long)]
+    amount: String,
+    /// Asset to pay with, e.g. TRMP.
+    #[arg(long)]
+    pay: String,
+    /// SQLite database the markets' events are indexed into to find their orders.
+    #[arg(long, env = "SPARK_EVENTS_DB", default_value = "spark-events.db")]
+    db: PathBuf,
+    /// Only route through the open orders of this wallet. Repeatable.
+    #[arg(long = "maker", value_parser = parse_identity)]
+    makers: Vec<Identity>,
+    /// Slices the amount is split into across paths.
+    #[arg(long, default_value_t = spark_examples::router::DEFAULT_SLICES)]
+    slices: usize,
+    /// Trade the plan instead of only showing it. With --dry-run the trades are
+    /// simulated either way.
+    #[arg(long)]
+    execute: bool,
+    /// How far past each leg's limit price a fill may go.
+    #[arg(long, default_value_t = 50)]
+    slippage_bps: u64,
+}
+
+pub async fn run(ctx: &SparkContext, out: &Output, args: RouteArgs) -> Result<()> {
+    let pay = ctx.assets.get(&args.pay)?;
+    let receive = ctx.assets.get(&args.buy)?;
+    let amount = receive.parse(&args.amount)?;
+
+    let router = Router::discover(ctx, &args.db, &args.makers)
+        .await?
+        .slices(args.slices);
+    let plan = router.plan(pay, receive, amount)?;
+    out.status(&format!(
+        "{} {} for {} {} over {} routes, {:.6} {} per {}",
+        plan.output()?,
+        receive.symbol,
+        plan.input()?,
+        pay.symbol,
+        plan.routes.len(),
+        plan.average_price()?,
+        pay.symbol,
+        receive.symbol
+    ));
+    if out.is_dry_run() {
+        return out.simulations(&[plan.simulate(ctx, args.slippage_bps).await?]);
+    }
+    if !args.execute {
+        let rows: Vec<_> = plan
+            .routes
+            .iter()
+            .enumerate()
+            .flat_map(|(i, route)| {
+                route
+                    .legs
+                    .iter()
+                    .map(move |leg| RouteLegView::planned(i, leg))
+            })
+            .collect();
+        return out.rows(&rows);
+    }
+
+    let report = plan.execute(ctx, args.slippage_bps).await?;
+    out.status(&format!(
+        "Received {} {} for {} {}, {:.6} {} per {}",
+        report.received,
+        receive.symbol,
+        report.spent,
+        pay.symbol,
+        report.average_price(),
+        pay.symbol,
+        receive.symbol
+    ));
+    let rows: Vec<_> = report.legs.iter().map(RouteLegView::executed).collect();
+    out.rows(&rows)
+}
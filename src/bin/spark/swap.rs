@@ -0,0 +1,65 @@
+use std::path::PathBuf;
+
+use anyhow::Result;
+use clap::Args;
+use fuels::types::Identity;
+use spark_examples::{utils::parse_identity, views::SwapView, SparkContext, Swap};
+use spark_market_sdk::LimitType;
+
+use crate::{orders::Side, output::Output};
+
+#[derive(Args)]
+pub struct SwapArgs {
+    #[arg(long, value_enum)]
+    side: Side,
+    /// Base amount in human units.
+    #[arg(long)]
+    amount: String,
+    /// How far past the best price the swap may fill.
+    #[arg(long, default_value_t = 50)]
+    max_slippage_bps: u64,
+    /// Fill the whole amount or nothing, instead of as much as possible.
+    #[arg(long)]
+    fok: bool,
+    /// SQLite database the market's events are indexed into to find its orders.
+    #[arg(long, env = "SPARK_EVENTS_DB", default_value = "spark-events.db")]
+    db: PathBuf,
+    /// Only take the open orders of this wallet. Repeatable.
+    #[arg(long = "maker", value_parser = parse_identity)]
+    makers: Vec<Identity>,
+    /// Send the swap instead of only previewing it. With --dry-run the swap is
+    /// simulated either way.
+    #[arg(long)]
+    execute: bool,
+}
+
+pub async fn run(ctx: &SparkContext, out: &Output, args: SwapArgs) -> Result<()> {
+    let info = &ctx.market_info;
+    let limit_type = if args.fok {
+        LimitType::FOK
+    } else {
+        LimitType::IOC
+    };
+
+    let swap = Swap::load(ctx, &args.db, &args.makers)
+        .await?
+        .limit_type(limit_type);
+    let preview = swap.preview(
+        args.side.order_type(),
+        info.base.parse(&args.amount)?,
+        args.max_slippage_bps,
+    )?;
+    if out.is_dry_run() {
+        out.status(&format!(
+            "Expecting to fill {} {}, {:.1} bps price impact",
+            preview.fill, info.base.symbol, preview.price_impact_bps
+        ));
+        return out.simulations(&[swap.simulate(&preview).await?]);
+    }
+    if !args.execute {
+        return out.one(&SwapView::preview(info, &preview));
+    }
+
+    let result = swap.execute(&preview).await?;
+    out.one(&SwapView::executed(info, &result))
+}
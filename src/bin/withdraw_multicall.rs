@@ -1,43 +1,21 @@
-use dotenv::dotenv;
-use std::{env, error::Error, str::FromStr};
-
-use fuels::{
-    accounts::{provider::Provider, wallet::WalletUnlocked},
-    prelude::VariableOutputPolicy,
-    programs::calls::CallHandler,
-    types::{ContractId, Identity},
-};
-
-use spark_market_sdk::{AssetType, SparkMarketContract};
-
-pub fn format_value_with_decimals(value: u64, decimals: u32) -> u64 {
-    value * 10u64.pow(decimals)
-}
-
-// 1) Run withdraw.rs on BTC & ETH markets
+// 1) Run `spark withdraw --all` on the BTC & ETH markets
 // 2) Run batch_open_orders.rs for BTC market
 // 3) Run this script:
 
-#[tokio::main]
-async fn main() -> Result<(), Box<dyn Error>> {
-    dotenv().ok();
-
-    // Environment variables
-    let mnemonic = env::var("MNEMONIC")?;
-    let btc_usdc_contract_id = env::var("BTC_USDC_CONTRACT_ID")?;
-
-    // Connect to provider
-    let provider_url = env::var("PROVIDER")?;
-    let provider = Provider::connect(provider_url).await?;
+use std::error::Error;
 
-    let main_wallet =
-        WalletUnlocked::new_from_mnemonic_phrase(&mnemonic, Some(provider.clone())).unwrap();
+use fuels::{prelude::VariableOutputPolicy, programs::calls::CallHandler};
+use spark_examples::SparkContext;
+use spark_market_sdk::AssetType;
 
-    let btc_contract_id = ContractId::from_str(&btc_usdc_contract_id)?;
-    let btc_market = SparkMarketContract::new(btc_contract_id.clone(), main_wallet.clone()).await;
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn Error>> {
+    let ctx = SparkContext::load("BTC_USDC").await?;
+    let btc_market = &ctx.market;
+    let main_wallet = &ctx.wallet;
 
     // Fuel wallet address
-    let wallet_id: Identity = main_wallet.address().into();
+    let wallet_id = ctx.wallet_id();
     println!("Wallet Address: {:?}", main_wallet.address().to_string());
 
     // Retrieve account balances in BTC/USDC market
@@ -1,56 +1,28 @@
-use dotenv::dotenv;
-use std::env;
-
-use fuels::{
-    accounts::provider::Provider,
-    accounts::wallet::WalletUnlocked,
-    prelude::CallHandler,
-    types::{AssetId, ContractId, Identity},
-};
-use std::str::FromStr;
+// This example shows how to withdraw assets from one market and deposit them to another market.
+// A Multicall doesn't allow to do that due to Fuel restrictions.
 
-use spark_market_sdk::{AssetType, SparkMarketContract};
 use std::error::Error;
 
-pub fn format_value_with_decimals(value: u64, decimals: u32) -> u64 {
-    value * 10u64.pow(decimals)
-}
-
-pub fn format_to_readble_value(value: u64, decimals: u32) -> f64 {
-    value as f64 / 10u64.pow(decimals) as f64
-}
-
-// This example shows how to withdraw assets from one market and deposit them to another market.
-// A Multicall doesn't allow to do that due to Fuel restrictions.
+use fuels::programs::calls::CallHandler;
+use spark_examples::{SparkContext, SparkError};
+use spark_market_sdk::AssetType;
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn Error>> {
-    dotenv().ok();
-
-    // Environment variables
-    let mnemonic = env::var("MNEMONIC")?;
-    let contract_id = env::var("BTC_USDC_CONTRACT_ID")?;
-    let contract_id_2 = env::var("ETH_USDC_CONTRACT_ID")?;
-    let usdc_id: String = env::var("USDC_ID")?;
-
-    // Connect to provider
-    let provider_url = env::var("PROVIDER")?;
-    let provider = Provider::connect(provider_url).await?;
+    let ctx = SparkContext::load("BTC_USDC").await?;
+    let market = &ctx.market;
+    let main_wallet = &ctx.wallet;
 
-    let main_wallet =
-        WalletUnlocked::new_from_mnemonic_phrase(&mnemonic, Some(provider.clone())).unwrap();
-    let contract_id = ContractId::from_str(&contract_id)?;
-    let market = SparkMarketContract::new(contract_id.clone(), main_wallet.clone()).await;
-    let contract_id_2 = ContractId::from_str(&contract_id_2)?;
+    let contract_id_2 = ctx.config.market("ETH_USDC")?.contract_id;
 
     // Fuel wallet address
-    let wallet_id: Identity = main_wallet.address().into();
+    let wallet_id = ctx.wallet_id();
     println!("wallet {:?}", main_wallet.address().to_string());
 
     println!("Depositing USDC");
-    let usdc_id = AssetId::from_str(&usdc_id).unwrap();
-    let usdc_amount = 10000;
-    market.deposit(usdc_amount, usdc_id).await?;
+    let usdc = &ctx.market_info.quote;
+    let usdc_amount = usdc.parse("0.01")?.raw(); // 0.01 USDC
+    market.deposit(usdc_amount, usdc.asset_id).await?;
 
     // Getting asset balances
     let account = market.account(wallet_id).await.unwrap().value;
@@ -96,7 +68,7 @@ async fn main() -> Result<(), Box<dyn Error>> {
                 Ok(())
             }
             Err(e) => {
-                print!("Withdraw base Error: {:?}", e);
+                print!("Withdraw base Error: {}", SparkError::from(&e));
                 Err(e)
             }
         }
@@ -118,7 +90,7 @@ async fn main() -> Result<(), Box<dyn Error>> {
                 Ok(())
             }
             Err(e) => {
-                print!("Withdraw quote Error: {:?}", e);
+                print!("Withdraw quote Error: {}", SparkError::from(&e));
                 Err(e)
             }
         }
@@ -0,0 +1,406 @@
+use std::{collections::BTreeMap, env, fmt, fs, path::Path, str::FromStr};
+
+use anyhow::{anyhow, Result};
+use dotenv::dotenv;
+use fuels::types::{AssetId, ContractId};
+use serde::Deserialize;
+
+/// Default location of the config file, overridable with `SPARK_CONFIG`.
+pub const DEFAULT_CONFIG_PATH: &str = "spark.toml";
+
+// Names the examples have always read from `.env`. They are picked up from the
+// environment even when the config file does not declare them.
+const LEGACY_ASSETS: [&str; 5] = ["BTC", "ETH", "USDC", "TRMP", "KMLA"];
+const LEGACY_MARKETS: [&str; 3] = ["BTC_USDC", "ETH_USDC", "TRMP_KMLA"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Network {
+    Testnet,
+    Mainnet,
+    Local,
+}
+
+impl Network {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Network::Testnet => "testnet",
+            Network::Mainnet => "mainnet",
+            Network::Local => "local",
+        }
+    }
+
+    fn default_provider(&self) -> &'static str {
+        match self {
+            Network::Testnet => "testnet.fuel.network",
+            Network::Mainnet => "mainnet.fuel.network",
+            Network::Local => "127.0.0.1:4000",
+        }
+    }
+}
+
+impl FromStr for Network {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "testnet" => Ok(Network::Testnet),
+            "mainnet" => Ok(Network::Mainnet),
+            "local" => Ok(Network::Local),
+            other => Err(anyhow!(
+                "Unknown network {}, expected testnet, mainnet or local",
+                other
+            )),
+        }
+    }
+}
+
+impl fmt::Display for Network {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct AssetConfig {
+    pub id: AssetId,
+    /// Decimals from `<SYMBOL>_DECIMALS` or the file. When unset they are read on chain.
+    pub decimals: Option<u8>,
+    /// SRC-20 contract that minted the asset, asked for `decimals()` when they are not configured.
+    pub contract: Option<ContractId>,
+}
+
+#[derive(Debug, Clone)]
+pub struct MarketConfig {
+    pub contract_id: ContractId,
+    /// Proxy implementation, needed for raw `get_instance()` calls on upgradeable markets.
+    pub implementation: Option<ContractId>,
+}
+
+/// Validated configuration for one network profile.
+#[derive(Debug, Clone)]
+pub struct Config {
+    pub network: Network,
+    pub provider: String,
+    pub mnemonic: String,
+    pub registry: ContractId,
+    pub assets: BTreeMap<String, AssetConfig>,
+    pub markets: BTreeMap<String, MarketConfig>,
+}
+
+/// Every missing or malformed key found while loading the config.
+#[derive(Debug)]
+pub struct ConfigError {
+    pub problems: Vec<String>,
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "Invalid configuration ({} problems):",
+            self.problems.len()
+        )?;
+        for problem in &self.problems {
+            writeln!(f, "  - {}", problem)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+#[derive(Debug, Default, Deserialize)]
+struct ConfigFile {
+    network: Option<String>,
+    #[serde(default)]
+    networks: BTreeMap<String, ProfileFile>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct ProfileFile {
+    provider: Option<String>,
+    mnemonic: Option<String>,
+    registry: Option<String>,
+    #[serde(default)]
+    assets: BTreeMap<String, AssetFile>,
+    #[serde(default)]
+    markets: BTreeMap<String, MarketFile>,
+}
+
+/// An asset is either just its id or a table with `id`, `decimals` and `contract`.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum AssetFile {
+    Id(String),
+    Table {
+        id: Option<String>,
+        decimals: Option<u8>,
+        contract: Option<String>,
+    },
+}
+
+impl AssetFile {
+    fn id(&self) -> Option<String> {
+        match self {
+            AssetFile::Id(id) => Some(id.clone()),
+            AssetFile::Table { id, .. } => id.clone(),
+        }
+    }
+
+    fn decimals(&self) -> Option<u8> {
+        match self {
+            AssetFile::Id(_) => None,
+            AssetFile::Table { decimals, .. } => *decimals,
+        }
+    }
+
+    fn contract(&self) -> Option<String> {
+        match self {
+            AssetFile::Id(_) => None,
+            AssetFile::Table { contract, .. } => contract.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct MarketFile {
+    contract_id: Option<String>,
+    implementation: Option<String>,
+}
+
+impl Config {
+    /// Loads `.env`, the TOML file at `SPARK_CONFIG` (default `spark.toml`, optional)
+    /// and applies environment overrides on top of the selected network profile.
+    ///
+    /// The profile is picked by `SPARK_NETWORK`, then the file's `network` key,
+    /// then defaults to testnet.
+    pub fn load() -> Result<Self> {
+        dotenv().ok();
+
+        let path = env::var("SPARK_CONFIG").unwrap_or_else(|_| DEFAULT_CONFIG_PATH.to_string());
+        Self::load_from(path)
+    }
+
+    pub fn load_from(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let file = if path.exists() {
+            let contents = fs::read_to_string(path)?;
+            toml::from_str::<ConfigFile>(&contents)
+                .map_err(|e| anyhow!("Failed to parse {}: {}", path.display(), e))?
+        } else {
+            ConfigFile::default()
+        };
+
+        let network = match env_value("SPARK_NETWORK").or(file.network) {
+            Some(network) => network.parse::<Network>()?,
+            None => Network::Testnet,
+        };
+
+        let mut networks = file.networks;
+        let profile = networks.remove(network.as_str()).unwrap_or_default();
+
+        Self::resolve(network, profile)
+    }
+
+    fn resolve(network: Network, profile: ProfileFile) -> Result<Self> {
+        let mut problems = Vec::new();
+
+        let provider = env_value("PROVIDER")
+            .or(profile.provider)
+            .unwrap_or_else(|| network.default_provider().to_string());
+
+        let mnemonic = env_value("MNEMONIC").or(profile.mnemonic);
+        if mnemonic.is_none() {
+            problems.push("MNEMONIC is not set".to_string());
+        }
+
+        let registry = required_id::<ContractId>(
+            "MARKET_REGISTRY",
+            env_value("MARKET_REGISTRY").or(profile.registry),
+            &mut problems,
+        );
+
+        let file_assets: BTreeMap<String, AssetFile> = profile
+            .assets
+            .into_iter()
+            .map(|(symbol, id)| (symbol.to_ascii_uppercase(), id))
+            .collect();
+
+        let mut asset_names: Vec<String> = file_assets.keys().cloned().collect();
+        asset_names.extend(LEGACY_ASSETS.iter().map(|s| s.to_string()));
+        asset_names.sort();
+        asset_names.dedup();
+
+        let mut assets = BTreeMap::new();
+        for name in asset_names {
+            let file_asset = file_assets.get(&name);
+
+            let key = format!("{}_ID", name);
+            let value = env_value(&key).or_else(|| file_asset.and_then(AssetFile::id));
+            let id = if file_asset.is_some() {
+                required_id::<AssetId>(&key, value, &mut problems)
+            } else {
+                optional_id::<AssetId>(&key, value, &mut problems)
+            };
+
+            let decimals_key = format!("{}_DECIMALS", name);
+            let decimals = match env_value(&decimals_key) {
+                Some(value) => match value.trim().parse::<u8>() {
+                    Ok(decimals) => Some(decimals),
+                    Err(_) => {
+                        problems.push(format!(
+                            "{} is not a valid number of decimals: {:?}",
+                            decimals_key, value
+                        ));
+                        None
+                    }
+                },
+                None => file_asset.and_then(AssetFile::decimals),
+            };
+
+            let contract_key = format!("{}_CONTRACT", name);
+            let contract_value =
+                env_value(&contract_key).or_else(|| file_asset.and_then(AssetFile::contract));
+            let contract = optional_id::<ContractId>(&contract_key, contract_value, &mut problems);
+
+            if let Some(id) = id {
+                assets.insert(
+                    name,
+                    AssetConfig {
+                        id,
+                        decimals,
+                        contract,
+                    },
+                );
+            }
+        }
+
+        let mut market_names: Vec<String> = profile.markets.keys().map(|k| market_key(k)).collect();
+        market_names.extend(LEGACY_MARKETS.iter().map(|s| s.to_string()));
+        market_names.sort();
+        market_names.dedup();
+
+        let mut markets = BTreeMap::new();
+        for name in market_names {
+            let file_market = profile
+                .markets
+                .iter()
+                .find(|(k, _)| market_key(k) == name)
+                .map(|(_, v)| v);
+
+            let contract_key = format!("{}_CONTRACT_ID", name);
+            let contract_value = env_value(&contract_key)
+                .or_else(|| file_market.and_then(|m| m.contract_id.clone()));
+            let implementation_key = format!("{}_IMPLEMENTATION", name);
+            let implementation_value = env_value(&implementation_key)
+                .or_else(|| file_market.and_then(|m| m.implementation.clone()));
+
+            // A market declared in the file must have a contract id; legacy names are optional.
+            let contract_id = if file_market.is_some() {
+                required_id::<ContractId>(&contract_key, contract_value, &mut problems)
+            } else {
+                optional_id::<ContractId>(&contract_key, contract_value, &mut problems)
+            };
+            let implementation =
+                optional_id::<ContractId>(&implementation_key, implementation_value, &mut problems);
+
+            if let Some(contract_id) = contract_id {
+                markets.insert(
+                    name,
+                    MarketConfig {
+                        contract_id,
+                        implementation,
+                    },
+                );
+            }
+        }
+
+        if !problems.is_empty() {
+            return Err(ConfigError { problems }.into());
+        }
+
+        Ok(Self {
+            network,
+            provider,
+            mnemonic: mnemonic.unwrap_or_default(),
+            registry: registry.unwrap_or_default(),
+            assets,
+            markets,
+        })
+    }
+
+    /// Looks up an asset id by symbol, e.g. `"USDC"`.
+    pub fn asset(&self, symbol: &str) -> Result<AssetId> {
+        self.assets
+            .get(&symbol.to_ascii_uppercase())
+            .map(|asset| asset.id)
+            .ok_or_else(|| {
+                anyhow!(
+                    "Asset {} is not configured for {} (set {}_ID)",
+                    symbol,
+                    self.network,
+                    symbol.to_ascii_uppercase()
+                )
+            })
+    }
+
+    /// Proxy implementation configured for a market contract, if any.
+    pub fn implementation(&self, contract_id: &ContractId) -> Option<ContractId> {
+        self.markets
+            .values()
+            .find(|market| &market.contract_id == contract_id)
+            .and_then(|market| market.implementation)
+    }
+
+    /// Looks up a market by name, accepting both `"ETH/USDC"` and `"ETH_USDC"`.
+    pub fn market(&self, name: &str) -> Result<&MarketConfig> {
+        let key = market_key(name);
+        self.markets.get(&key).ok_or_else(|| {
+            anyhow!(
+                "Market {} is not configured for {} (set {}_CONTRACT_ID)",
+                name,
+                self.network,
+                key
+            )
+        })
+    }
+}
+
+/// Normalises `eth/usdc`, `ETH-USDC` and `ETH_USDC` to `ETH_USDC`.
+pub fn market_key(name: &str) -> String {
+    name.to_ascii_uppercase().replace(['/', '-'], "_")
+}
+
+/// Reads an environment variable, treating an empty value as unset.
+fn env_value(key: &str) -> Option<String> {
+    env::var(key).ok().filter(|value| !value.trim().is_empty())
+}
+
+fn required_id<T: FromStr>(
+    key: &str,
+    value: Option<String>,
+    problems: &mut Vec<String>,
+) -> Option<T> {
+    if value.is_none() {
+        problems.push(format!("{} is not set", key));
+    }
+    optional_id(key, value, problems)
+}
+
+fn optional_id<T: FromStr>(
+    key: &str,
+    value: Option<String>,
+    problems: &mut Vec<String>,
+) -> Option<T> {
+    let value = value?;
+    match T::from_str(value.trim()) {
+        Ok(id) => Some(id),
+        Err(_) => {
+            problems.push(format!(
+                "{} is not a valid 32-byte hex id: {:?}",
+                key, value
+            ));
+            None
+        }
+    }
+}
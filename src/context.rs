@@ -0,0 +1,202 @@
+use std::time::{Duration, Instant};
+
+use anyhow::{anyhow, bail, Result};
+use fuels::{
+    accounts::{provider::Provider, wallet::WalletUnlocked},
+    tx::Receipt,
+    types::{bech32::Bech32ContractId, tx_status::TxStatus, Bytes32, ContractId, Identity},
+};
+use spark_market_sdk::SparkMarketContract;
+use spark_registry_sdk::SparkRegistryContract;
+
+use crate::{
+    assets::AssetCatalogue,
+    config::{market_key, Config},
+    market::{self, MarketInfo},
+    utils::parse_contract_id,
+};
+
+/// Decimals of the gas token when it is not in the asset catalogue.
+const GAS_TOKEN_DECIMALS: u32 = 9;
+
+/// Everything a Spark script needs to talk to one market: a connected provider,
+/// the unlocked wallet, the market and the market registry.
+pub struct SparkContext {
+    pub config: Config,
+    pub provider: Provider,
+    pub wallet: WalletUnlocked,
+    pub market: SparkMarketContract,
+    pub registry: SparkRegistryContract,
+    pub assets: AssetCatalogue,
+    /// Assets, decimals and fees of `market` as reported by the contract.
+    pub market_info: MarketInfo,
+    /// Proxy implementation contract, required by calls built through `get_instance()`
+    /// on upgradeable markets.
+    pub implementation: Option<ContractId>,
+}
+
+impl SparkContext {
+    /// Loads `Config` (see `Config::load`) and connects to the named market,
+    /// e.g. `"ETH_USDC"` or `"ETH/USDC"`.
+    ///
+    /// Markets missing from the config are looked up in the registry by their
+    /// symbols; a `0x` contract id is also accepted.
+    pub async fn load(market: &str) -> Result<Self> {
+        let config = Config::load()?;
+        Self::new(config, market).await
+    }
+
+    pub async fn new(config: Config, market: &str) -> Result<Self> {
+        let provider = Provider::connect(&config.provider).await?;
+        let wallet =
+            WalletUnlocked::new_from_mnemonic_phrase(&config.mnemonic, Some(provider.clone()))?;
+        Self::with_wallet(config, wallet, market).await
+    }
+
+    /// Like `new`, but signs with an already unlocked wallet instead of the
+    /// configured mnemonic, e.g. one funded by a local test node.
+    pub async fn with_wallet(config: Config, wallet: WalletUnlocked, market: &str) -> Result<Self> {
+        let provider = wallet
+            .provider()
+            .ok_or_else(|| anyhow!("Wallet {} has no provider", wallet.address()))?
+            .clone();
+
+        let registry = SparkRegistryContract::new(config.registry, wallet.clone()).await;
+        let mut assets = AssetCatalogue::load(&config, &wallet).await?;
+
+        let contract_id = match config.market(market) {
+            Ok(market_config) => market_config.contract_id,
+            Err(_) => resolve_market(&registry, &assets, market).await?,
+        };
+        let implementation = config.implementation(&contract_id);
+
+        let market = SparkMarketContract::new(contract_id, wallet.clone()).await;
+        let market_info =
+            MarketInfo::load(&market, contract_id, &registry, &assets, implementation).await?;
+        for info in [&market_info.base, &market_info.quote] {
+            if assets.by_id(&info.asset_id).is_none() {
+                assets.insert(info.clone());
+            }
+        }
+
+        Ok(Self {
+            config,
+            provider,
+            wallet,
+            market,
+            registry,
+            assets,
+            market_info,
+            implementation,
+        })
+    }
+
+    /// The wallet as an `Identity`, as expected by `account` and `user_orders`.
+    pub fn wallet_id(&self) -> Identity {
+        self.wallet.address().into()
+    }
+
+    /// Opens another market with the same wallet.
+    pub async fn connect_market(&self, contract_id: ContractId) -> SparkMarketContract {
+        SparkMarketContract::new(contract_id, self.wallet.clone()).await
+    }
+
+    /// Describes any market from just its contract id. The implementation id is
+    /// taken from the config when the market is configured.
+    pub async fn market_info(&self, contract_id: ContractId) -> Result<MarketInfo> {
+        let market = self.connect_market(contract_id).await;
+        MarketInfo::load(
+            &market,
+            contract_id,
+            &self.registry,
+            &self.assets,
+            self.config.implementation(&contract_id),
+        )
+        .await
+    }
+
+    /// Finds the registered market for two configured symbols, e.g. `("ETH", "USDC")`.
+    pub async fn find_market(&self, base: &str, quote: &str) -> Result<MarketInfo> {
+        let contract_id = market::find_market(
+            &self.registry,
+            self.assets.get(base)?,
+            self.assets.get(quote)?,
+        )
+        .await?;
+        self.market_info(contract_id).await
+    }
+
+    /// Contract ids to attach to raw `get_instance()` calls so that proxied
+    /// markets can reach their implementation.
+    pub fn contract_ids(&self) -> Vec<Bech32ContractId> {
+        self.implementation.iter().map(|id| (*id).into()).collect()
+    }
+
+    /// Like `contract_ids`, for calls to another configured market.
+    pub fn contract_ids_of(&self, market: ContractId) -> Vec<Bech32ContractId> {
+        self.config
+            .implementation(&market)
+            .iter()
+            .map(|id| (*id).into())
+            .collect()
+    }
+
+    /// Decimals of the token gas is paid in, 9 when it is not in the catalogue.
+    pub fn gas_token_decimals(&self) -> u32 {
+        self.assets
+            .by_id(self.provider.base_asset_id())
+            .map_or(GAS_TOKEN_DECIMALS, |asset| asset.decimals)
+    }
+
+    /// Waits for a transaction sent with `submit()` to be included, instead of
+    /// sleeping a fixed time. Reverts and squeezed out transactions are errors.
+    pub async fn wait_for_tx(&self, tx_id: Bytes32, timeout: Duration) -> Result<Vec<Receipt>> {
+        match self.wait_for_status(tx_id, timeout).await? {
+            TxStatus::Success { receipts } => Ok(receipts),
+            TxStatus::Revert { reason, .. } => {
+                bail!("Transaction 0x{} reverted: {}", tx_id, reason)
+            }
+            TxStatus::SqueezedOut { reason } => {
+                bail!("Transaction 0x{} was squeezed out: {}", tx_id, reason)
+            }
+            TxStatus::Submitted => unreachable!("pending statuses are waited on"),
+        }
+    }
+
+    /// Like `wait_for_tx`, but hands back the final status, including the
+    /// receipts of a reverted transaction. Only a timeout is an error.
+    pub async fn wait_for_status(&self, tx_id: Bytes32, timeout: Duration) -> Result<TxStatus> {
+        let started = Instant::now();
+        loop {
+            match self.provider.tx_status(&tx_id).await {
+                // Not known to the node yet, or still pending
+                Ok(TxStatus::Submitted) | Err(_) => {}
+                Ok(status) => return Ok(status),
+            }
+            if started.elapsed() > timeout {
+                bail!("Transaction 0x{} not included after {:?}", tx_id, timeout);
+            }
+            tokio::time::sleep(Duration::from_millis(500)).await;
+        }
+    }
+}
+
+/// Resolves a market that is not in the config: either a literal contract id
+/// or a `BASE/QUOTE` pair looked up in the registry.
+async fn resolve_market(
+    registry: &SparkRegistryContract,
+    assets: &AssetCatalogue,
+    name: &str,
+) -> Result<ContractId> {
+    if name.starts_with("0x") {
+        return parse_contract_id(name);
+    }
+    let key = market_key(name);
+    let (base, quote) = key.split_once('_').ok_or_else(|| {
+        anyhow!(
+            "Expected a market like ETH/USDC or a contract id, got {}",
+            name
+        )
+    })?;
+    market::find_market(registry, assets.get(base)?, assets.get(quote)?).await
+}
@@ -0,0 +1,209 @@
+//! Market errors people can act on.
+//!
+//! The market contract reverts with a logged error such as
+//! `InsufficientBalance((500, 1000, true))` and the SDK passes it on wrapped in
+//! a `fuels` error, next to node, wallet and network failures. `SparkError`
+//! sorts all of these into the handful of cases a script or bot handles
+//! differently, with a readable message and whether trying again can help.
+
+use std::fmt;
+
+use fuels::types::errors::{transaction::Reason, Error as FuelsError};
+
+use crate::validate::Rejected;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SparkError {
+    /// The market account's liquid balance does not cover the order or withdrawal.
+    InsufficientBalance(String),
+    /// The wallet lacks the coins to deposit or to pay for gas.
+    InsufficientFunds(String),
+    OrderNotFound(String),
+    PriceTooLow(String),
+    OrderTooSmall(String),
+    /// The asset is neither the base nor the quote of the market.
+    InvalidAsset(String),
+    /// Any other argument the contract refused, e.g. an empty list or a bad fee.
+    InvalidValue(String),
+    /// Orders on the same side or whose prices do not cross.
+    NotMatchable(String),
+    Paused,
+    /// Owner-only call, or acting on someone else's order.
+    Unauthorized(String),
+    OutOfGas(String),
+    /// More calls than fit in one transaction.
+    TooLarge(String),
+    /// Dropped from the node's pool, e.g. for a spent coin.
+    SqueezedOut(String),
+    /// Not included in time; it may still go through.
+    Timeout(String),
+    /// The node could not be reached or answered with garbage.
+    Network(String),
+    /// Reverted for a reason not listed above.
+    Reverted(String),
+    Other(String),
+}
+
+impl SparkError {
+    /// Whether sending the same thing again may succeed without changing anything.
+    pub fn is_retryable(&self) -> bool {
+        matches!(
+            self,
+            SparkError::OutOfGas(_)
+                | SparkError::SqueezedOut(_)
+                | SparkError::Timeout(_)
+                | SparkError::Network(_)
+        )
+    }
+
+    /// Sorts a revert reason, decoded log or error message by the market
+    /// error or node message it contains.
+    pub fn from_reason(reason: &str) -> Self {
+        let detail = reason.trim().to_string();
+        let has = |needles: &[&str]| needles.iter().any(|needle| reason.contains(needle));
+
+        if has(&["InsufficientBalance"]) {
+            SparkError::InsufficientBalance(detail)
+        } else if has(&["OrderNotFound"]) {
+            SparkError::OrderNotFound(detail)
+        } else if has(&["PriceTooSmall", "PriceTooLow", "ZeroPrice"]) {
+            SparkError::PriceTooLow(detail)
+        } else if has(&["ZeroOrderAmount", "OrderSizeTooSmall", "OrderTooSmall"]) {
+            SparkError::OrderTooSmall(detail)
+        } else if has(&["InvalidAsset"]) {
+            SparkError::InvalidAsset(detail)
+        } else if has(&["CantMatch", "CantFulfill"]) {
+            SparkError::NotMatchable(detail)
+        } else if has(&["Paused"]) {
+            SparkError::Paused
+        } else if has(&[
+            "NotOwner",
+            "Unauthorized",
+            "InvalidUser",
+            "InvalidMarketSender",
+        ]) {
+            SparkError::Unauthorized(detail)
+        } else if has(&[
+            "InvalidAmount",
+            "InvalidValue",
+            "InvalidArrayLength",
+            "InvalidFee",
+            "InvalidSlippage",
+        ]) {
+            SparkError::InvalidValue(detail)
+        } else if has(&["OutOfGas"]) {
+            SparkError::OutOfGas(detail)
+        } else if has(&[
+            "not enough coins",
+            "enough coins could not be found",
+            "InsufficientFeeAmount",
+        ]) {
+            SparkError::InsufficientFunds(detail)
+        } else if has(&["squeezed out", "SqueezedOut"]) {
+            SparkError::SqueezedOut(detail)
+        } else if has(&["not included after", "timed out", "Timeout"]) {
+            SparkError::Timeout(detail)
+        } else if has(&[
+            "error sending request",
+            "Connection refused",
+            "connection closed",
+            "dns error",
+        ]) {
+            SparkError::Network(detail)
+        } else if has(&["Revert", "revert", "Panic"]) {
+            SparkError::Reverted(detail)
+        } else {
+            SparkError::Other(detail)
+        }
+    }
+}
+
+impl fmt::Display for SparkError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (message, detail) = match self {
+            SparkError::InsufficientBalance(d) => (
+                "Not enough liquid balance in the market account; deposit more or cancel orders",
+                d,
+            ),
+            SparkError::InsufficientFunds(d) => {
+                ("The wallet does not hold enough coins for this", d)
+            }
+            SparkError::OrderNotFound(d) => {
+                ("Order not found; it may have been filled or cancelled", d)
+            }
+            SparkError::PriceTooLow(d) => ("Price is below what the market accepts", d),
+            SparkError::OrderTooSmall(d) => ("Order amount is too small", d),
+            SparkError::InvalidAsset(d) => ("Asset is not traded on this market", d),
+            SparkError::InvalidValue(d) => ("The market rejected an argument", d),
+            SparkError::NotMatchable(d) => ("These orders cannot be matched", d),
+            SparkError::Paused => return write!(f, "The market is paused"),
+            SparkError::Unauthorized(d) => ("Not allowed for this wallet", d),
+            SparkError::OutOfGas(d) => ("Ran out of gas", d),
+            SparkError::TooLarge(d) => ("Too large for a single transaction", d),
+            SparkError::SqueezedOut(d) => ("The node dropped the transaction", d),
+            SparkError::Timeout(d) => ("Timed out waiting for the transaction", d),
+            SparkError::Network(d) => ("Could not reach the node", d),
+            SparkError::Reverted(d) => ("Transaction reverted", d),
+            SparkError::Other(d) => return write!(f, "{}", d),
+        };
+        if detail.is_empty() {
+            write!(f, "{}", message)
+        } else {
+            write!(f, "{} ({})", message, detail)
+        }
+    }
+}
+
+impl std::error::Error for SparkError {}
+
+impl From<&FuelsError> for SparkError {
+    fn from(error: &FuelsError) -> Self {
+        match error {
+            FuelsError::Transaction(Reason::Reverted { reason, .. }) => {
+                match SparkError::from_reason(reason) {
+                    SparkError::Other(detail) => SparkError::Reverted(detail),
+                    known => known,
+                }
+            }
+            FuelsError::Transaction(Reason::SqueezedOut(reason)) => {
+                SparkError::SqueezedOut(reason.clone())
+            }
+            FuelsError::IO(message) => match SparkError::from_reason(message) {
+                SparkError::Other(detail) => SparkError::Network(detail),
+                known => known,
+            },
+            other => SparkError::from_reason(&other.to_string()),
+        }
+    }
+}
+
+impl From<FuelsError> for SparkError {
+    fn from(error: FuelsError) -> Self {
+        SparkError::from(&error)
+    }
+}
+
+impl From<&anyhow::Error> for SparkError {
+    /// Uses a `SparkError`, `fuels` error or rejected order anywhere in the
+    /// chain, falling back to the full message.
+    fn from(error: &anyhow::Error) -> Self {
+        for cause in error.chain() {
+            if let Some(spark) = cause.downcast_ref::<SparkError>() {
+                return spark.clone();
+            }
+            if let Some(fuels) = cause.downcast_ref::<FuelsError>() {
+                return SparkError::from(fuels);
+            }
+            if let Some(rejected) = cause.downcast_ref::<Rejected>() {
+                return SparkError::from(rejected);
+            }
+        }
+        SparkError::from_reason(&format!("{:#}", error))
+    }
+}
+
+impl From<anyhow::Error> for SparkError {
+    fn from(error: anyhow::Error) -> Self {
+        SparkError::from(&error)
+    }
+}
@@ -0,0 +1,217 @@
+//! Protocol and matcher fees.
+//!
+//! A market charges makers and takers a rate in basis points of the quote value
+//! traded, lower the more quote volume the user has traded, plus a flat matcher
+//! fee per order. `FeeSchedule` holds the tiers `protocol_fee()` reports and
+//! works out what an order pays and what `open_order` locks for it.
+
+use anyhow::Result;
+use spark_market_sdk::{OrderType, ProtocolFee};
+
+use crate::{
+    amount::{Quantity, Rounding},
+    assets::AssetInfo,
+    indexer::TradeRecord,
+    maker::DesiredOrder,
+    market::MarketInfo,
+};
+
+/// Protocol fee rates are in basis points of the quote value.
+pub const FEE_SCALE: u64 = 10_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FeeTier {
+    /// Quote volume from which the tier applies.
+    pub volume_threshold: Quantity,
+    pub maker_bps: u64,
+    pub taker_bps: u64,
+}
+
+impl FeeTier {
+    /// The rate `open_order` reserves, since nobody knows yet which side of a
+    /// trade the order ends up on.
+    pub fn reserve_bps(&self) -> u64 {
+        self.maker_bps.max(self.taker_bps)
+    }
+}
+
+/// What one order pays, all in quote.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OrderFees {
+    pub tier: FeeTier,
+    /// Quote value of the order, rounded up.
+    pub value: Quantity,
+    /// Protocol fee if the order rests and is filled as a maker.
+    pub maker_fee: Quantity,
+    /// Protocol fee if the order crosses and is filled as a taker.
+    pub taker_fee: Quantity,
+    pub matcher_fee: Quantity,
+    /// Fees `open_order` locks from the liquid quote of a buy.
+    pub reserve: Quantity,
+    /// Everything a buy locks: its value plus the reserve. Sells lock only their base.
+    pub locked_quote: Quantity,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FeeSchedule {
+    /// Lowest threshold first.
+    tiers: Vec<FeeTier>,
+    matcher_fee: Quantity,
+}
+
+impl FeeSchedule {
+    /// Tiers as `protocol_fee()` returns them, with the matcher fee in quote
+    /// base units.
+    pub fn new(protocol_fee: &[ProtocolFee], matcher_fee: u64, quote: &AssetInfo) -> Self {
+        let mut tiers: Vec<_> = protocol_fee
+            .iter()
+            .map(|tier| FeeTier {
+                volume_threshold: quote.quantity(tier.volume_threshold),
+                maker_bps: tier.maker_fee,
+                taker_bps: tier.taker_fee,
+            })
+            .collect();
+        tiers.sort_by_key(|tier| tier.volume_threshold);
+        Self {
+            tiers,
+            matcher_fee: quote.quantity(matcher_fee),
+        }
+    }
+
+    pub fn from_market(market: &MarketInfo) -> Self {
+        Self::new(&market.protocol_fee, market.matcher_fee, &market.quote)
+    }
+
+    pub fn tiers(&self) -> &[FeeTier] {
+        &self.tiers
+    }
+
+    pub fn matcher_fee(&self) -> Quantity {
+        self.matcher_fee
+    }
+
+    /// Index of the tier a user with `volume` traded is in: the highest one
+    /// whose threshold it reached, or the first one below every threshold.
+    pub fn tier_index(&self, volume: Quantity) -> Option<usize> {
+        if self.tiers.is_empty() {
+            return None;
+        }
+        let reached = self
+            .tiers
+            .iter()
+            .take_while(|tier| tier.volume_threshold.raw() <= volume.raw())
+            .count();
+        Some(reached.saturating_sub(1))
+    }
+
+    /// The tier for `volume`; a market without tiers charges nothing.
+    pub fn tier(&self, volume: Quantity) -> FeeTier {
+        match self.tier_index(volume) {
+            Some(index) => self.tiers[index],
+            None => FeeTier {
+                volume_threshold: Quantity::zero(self.matcher_fee.decimals()),
+                maker_bps: 0,
+                taker_bps: 0,
+            },
+        }
+    }
+
+    /// The tier the market charges a user, from the rates `protocol_fee_user`
+    /// reports. Rates no tier lists are returned with a zero threshold.
+    pub fn user_tier(&self, maker_bps: u64, taker_bps: u64) -> FeeTier {
+        self.tiers
+            .iter()
+            .rev()
+            .find(|tier| tier.maker_bps == maker_bps && tier.taker_bps == taker_bps)
+            .copied()
+            .unwrap_or(FeeTier {
+                volume_threshold: Quantity::zero(self.matcher_fee.decimals()),
+                maker_bps,
+                taker_bps,
+            })
+    }
+
+    /// The next cheaper tier and the volume still missing to reach it.
+    pub fn next_tier(&self, volume: Quantity) -> Option<(FeeTier, Quantity)> {
+        let tier = self
+            .tiers
+            .iter()
+            .find(|tier| tier.volume_threshold.raw() > volume.raw())?;
+        let missing = tier.volume_threshold.raw() - volume.raw();
+        Some((*tier, Quantity::from_raw(missing, volume.decimals())))
+    }
+
+    /// The highest rate any tier reserves, for when the user's tier is unknown.
+    pub fn max_reserve_bps(&self) -> u64 {
+        self.tiers
+            .iter()
+            .map(FeeTier::reserve_bps)
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// The highest taker fee of any tier, for when the taker's tier is unknown.
+    pub fn max_taker_bps(&self) -> u64 {
+        self.tiers
+            .iter()
+            .map(|tier| tier.taker_bps)
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Fees `open_order` locks on top of `value` at `rate_bps`: the protocol
+    /// fee, rounded up, plus the matcher fee.
+    pub fn reserve(&self, value: Quantity, rate_bps: u64) -> Result<Quantity> {
+        value
+            .mul_div(rate_bps, FEE_SCALE, Rounding::Up)?
+            .checked_add(self.matcher_fee)
+    }
+
+    /// Fees of `order` for a user who has traded `volume` of quote.
+    pub fn order_fees(
+        &self,
+        market: &MarketInfo,
+        order: &DesiredOrder,
+        volume: Quantity,
+    ) -> Result<OrderFees> {
+        self.order_fees_in(market, order, self.tier(volume))
+    }
+
+    /// Fees of `order` for a user in `tier`, e.g. from `user_tier`.
+    pub fn order_fees_in(
+        &self,
+        market: &MarketInfo,
+        order: &DesiredOrder,
+        tier: FeeTier,
+    ) -> Result<OrderFees> {
+        let value = order.quote_value(market)?;
+        let reserve = self.reserve(value, tier.reserve_bps())?;
+        let locked_quote = match order.side {
+            OrderType::Buy => value.checked_add(reserve)?,
+            OrderType::Sell => Quantity::zero(value.decimals()),
+        };
+        Ok(OrderFees {
+            tier,
+            value,
+            maker_fee: value.mul_div(tier.maker_bps, FEE_SCALE, Rounding::Up)?,
+            taker_fee: value.mul_div(tier.taker_bps, FEE_SCALE, Rounding::Up)?,
+            matcher_fee: self.matcher_fee,
+            reserve,
+            locked_quote,
+        })
+    }
+
+    /// Quote volume of indexed fills, e.g. a user's trades from `EventStore::trades`.
+    pub fn traded_volume(market: &MarketInfo, trades: &[TradeRecord]) -> Result<Quantity> {
+        trades
+            .iter()
+            .try_fold(Quantity::zero(market.quote.decimals), |total, trade| {
+                let value = market.quote_for(
+                    market.base.quantity(trade.size),
+                    market.price(trade.price),
+                    Rounding::Down,
+                )?;
+                total.checked_add(value)
+            })
+    }
+}
@@ -0,0 +1,723 @@
+//! Decodes a market's logged events from the chain into a local SQLite database.
+//!
+//! `Indexer::sync` pages through blocks from a start height, fetches the receipts
+//! of every transaction and decodes the `LogData` the market emitted with the
+//! SDK's ABI decoder. Each block is written in one SQLite transaction together
+//! with the indexed height, so an interrupted sync resumes where it stopped and
+//! re-indexing a block is a no-op.
+//!
+//! Receipts already at hand, e.g. from a `CallHandler::submit()` the bot just
+//! made, can be stored right away with `Indexer::index_receipts`.
+
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use fuels::{
+    accounts::provider::Provider,
+    client::{PageDirection, PaginationRequest},
+    core::codec::LogDecoder,
+    tx::Receipt,
+    types::{tx_status::TxStatus, AssetId, Bytes32, ContractId, Identity},
+};
+use rusqlite::{params, Connection, OptionalExtension, Transaction};
+use serde::Serialize;
+use spark_market_sdk::{
+    Account, CancelOrderEvent, DepositEvent, OpenOrderEvent, SparkMarketContract, TradeOrderEvent,
+    WithdrawEvent,
+};
+
+use crate::{
+    utils::{hex_str_from_bits256, identity_str},
+    views::side_str,
+};
+
+/// Blocks requested per page while syncing.
+pub const DEFAULT_PAGE_SIZE: i32 = 100;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS cursors (
+    market TEXT PRIMARY KEY,
+    height INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS orders_opened (
+    market TEXT NOT NULL,
+    block_height INTEGER NOT NULL,
+    tx_id TEXT NOT NULL,
+    tx_index INTEGER NOT NULL,
+    log_index INTEGER NOT NULL,
+    order_id TEXT NOT NULL,
+    user TEXT NOT NULL,
+    side TEXT NOT NULL,
+    amount INTEGER NOT NULL,
+    price INTEGER NOT NULL,
+    PRIMARY KEY (tx_id, log_index)
+);
+CREATE TABLE IF NOT EXISTS orders_cancelled (
+    market TEXT NOT NULL,
+    block_height INTEGER NOT NULL,
+    tx_id TEXT NOT NULL,
+    tx_index INTEGER NOT NULL,
+    log_index INTEGER NOT NULL,
+    order_id TEXT NOT NULL,
+    user TEXT NOT NULL,
+    PRIMARY KEY (tx_id, log_index)
+);
+CREATE TABLE IF NOT EXISTS trades (
+    market TEXT NOT NULL,
+    block_height INTEGER NOT NULL,
+    tx_id TEXT NOT NULL,
+    tx_index INTEGER NOT NULL,
+    log_index INTEGER NOT NULL,
+    sell_order_id TEXT NOT NULL,
+    buy_order_id TEXT NOT NULL,
+    seller TEXT NOT NULL,
+    buyer TEXT NOT NULL,
+    matcher TEXT NOT NULL,
+    size INTEGER NOT NULL,
+    price INTEGER NOT NULL,
+    PRIMARY KEY (tx_id, log_index)
+);
+CREATE TABLE IF NOT EXISTS transfers (
+    market TEXT NOT NULL,
+    block_height INTEGER NOT NULL,
+    tx_id TEXT NOT NULL,
+    tx_index INTEGER NOT NULL,
+    log_index INTEGER NOT NULL,
+    kind TEXT NOT NULL,
+    user TEXT NOT NULL,
+    asset_id TEXT NOT NULL,
+    amount INTEGER NOT NULL,
+    PRIMARY KEY (tx_id, log_index)
+);
+CREATE TABLE IF NOT EXISTS balances (
+    market TEXT NOT NULL,
+    block_height INTEGER NOT NULL,
+    tx_id TEXT NOT NULL,
+    tx_index INTEGER NOT NULL,
+    log_index INTEGER NOT NULL,
+    user TEXT NOT NULL,
+    liquid_base INTEGER NOT NULL,
+    liquid_quote INTEGER NOT NULL,
+    locked_base INTEGER NOT NULL,
+    locked_quote INTEGER NOT NULL,
+    PRIMARY KEY (tx_id, log_index, user)
+);
+CREATE INDEX IF NOT EXISTS trades_seller ON trades (market, seller, block_height);
+CREATE INDEX IF NOT EXISTS trades_buyer ON trades (market, buyer, block_height);
+CREATE INDEX IF NOT EXISTS balances_user ON balances (market, user, block_height);
+";
+
+/// A decoded market log.
+#[derive(Debug, Clone)]
+pub enum MarketEvent {
+    Open(OpenOrderEvent),
+    Cancel(CancelOrderEvent),
+    Trade(TradeOrderEvent),
+    Deposit(DepositEvent),
+    Withdraw(WithdrawEvent),
+}
+
+impl MarketEvent {
+    /// The market accounts the event logged, as they were right after it.
+    pub fn balances(&self) -> Vec<(&Identity, &Account)> {
+        match self {
+            MarketEvent::Open(e) => vec![(&e.user, &e.balance)],
+            MarketEvent::Cancel(e) => vec![(&e.user, &e.balance)],
+            MarketEvent::Trade(e) => vec![
+                (&e.order_seller, &e.s_account_balance),
+                (&e.order_buyer, &e.b_account_balance),
+            ],
+            MarketEvent::Deposit(e) => vec![(&e.user, &e.account)],
+            MarketEvent::Withdraw(e) => vec![(&e.user, &e.account)],
+        }
+    }
+}
+
+/// Where an event was logged.
+#[derive(Debug, Clone)]
+pub struct EventMeta {
+    pub block_height: u32,
+    pub tx_id: String,
+    /// Position of the transaction within its block.
+    pub tx_index: usize,
+    /// Position of the log among the transaction's receipts.
+    pub log_index: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct OpenRecord {
+    pub block_height: u32,
+    pub tx_id: String,
+    pub order_id: String,
+    pub user: String,
+    pub side: String,
+    pub amount: u64,
+    pub price: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CancelRecord {
+    pub block_height: u32,
+    pub tx_id: String,
+    pub order_id: String,
+    pub user: String,
+}
+
+/// A fill. `size` is in base units, `price` in the market's price decimals.
+#[derive(Debug, Clone, Serialize)]
+pub struct TradeRecord {
+    pub block_height: u32,
+    pub tx_id: String,
+    pub sell_order_id: String,
+    pub buy_order_id: String,
+    pub seller: String,
+    pub buyer: String,
+    pub matcher: String,
+    pub size: u64,
+    pub price: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TransferRecord {
+    pub block_height: u32,
+    pub tx_id: String,
+    /// `deposit` or `withdraw`.
+    pub kind: String,
+    pub user: String,
+    pub asset_id: String,
+    pub amount: u64,
+}
+
+/// A user's market balance right after an event that changed it.
+#[derive(Debug, Clone, Serialize)]
+pub struct BalanceRecord {
+    pub block_height: u32,
+    pub tx_id: String,
+    pub user: String,
+    pub liquid_base: u64,
+    pub liquid_quote: u64,
+    pub locked_base: u64,
+    pub locked_quote: u64,
+}
+
+/// Indexed events of one market.
+pub struct EventStore {
+    conn: Connection,
+    market: String,
+}
+
+impl EventStore {
+    pub fn open(path: impl AsRef<Path>, market: ContractId) -> Result<Self> {
+        let path = path.as_ref();
+        let conn = Connection::open(path)
+            .with_context(|| format!("Cannot open event database {}", path.display()))?;
+        Self::with_connection(conn, market)
+    }
+
+    pub fn in_memory(market: ContractId) -> Result<Self> {
+        Self::with_connection(Connection::open_in_memory()?, market)
+    }
+
+    fn with_connection(conn: Connection, market: ContractId) -> Result<Self> {
+        conn.execute_batch(SCHEMA)?;
+        Ok(Self {
+            conn,
+            market: format!("0x{}", market),
+        })
+    }
+
+    /// Last block fully indexed for this market.
+    pub fn cursor(&self) -> Result<Option<u32>> {
+        Ok(self
+            .conn
+            .query_row(
+                "SELECT height FROM cursors WHERE market = ?1",
+                params![self.market],
+                |row| row.get(0),
+            )
+            .optional()?)
+    }
+
+    /// Stores the events of one block and moves the cursor past it, atomically.
+    pub fn insert_block(
+        &mut self,
+        block_height: u32,
+        events: &[(EventMeta, MarketEvent)],
+    ) -> Result<()> {
+        let tx = self.conn.transaction()?;
+        for (meta, event) in events {
+            insert_event(&tx, &self.market, meta, event)?;
+        }
+        tx.execute(
+            "INSERT INTO cursors (market, height) VALUES (?1, ?2)
+             ON CONFLICT (market) DO UPDATE SET height = MAX(height, excluded.height)",
+            params![self.market, block_height],
+        )?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Stores events without touching the cursor, e.g. from a transaction just submitted.
+    pub fn insert_events(&mut self, events: &[(EventMeta, MarketEvent)]) -> Result<()> {
+        let tx = self.conn.transaction()?;
+        for (meta, event) in events {
+            insert_event(&tx, &self.market, meta, event)?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Orders opened by `user`, or by everyone, oldest first.
+    pub fn opened(&self, user: Option<&Identity>, since_height: u32) -> Result<Vec<OpenRecord>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT block_height, tx_id, order_id, user, side, amount, price FROM orders_opened
+             WHERE market = ?1 AND block_height >= ?2 AND (?3 IS NULL OR user = ?3)
+             ORDER BY block_height, tx_index, log_index",
+        )?;
+        let rows = stmt.query_map(
+            params![self.market, since_height, user.map(identity_str)],
+            |row| {
+                Ok(OpenRecord {
+                    block_height: row.get(0)?,
+                    tx_id: row.get(1)?,
+                    order_id: row.get(2)?,
+                    user: row.get(3)?,
+                    side: row.get(4)?,
+                    amount: row.get(5)?,
+                    price: row.get(6)?,
+                })
+            },
+        )?;
+        Ok(rows.collect::<rusqlite::Result<_>>()?)
+    }
+
+    /// Cancels by `user`, or by everyone, oldest first.
+    pub fn cancels(&self, user: Option<&Identity>, since_height: u32) -> Result<Vec<CancelRecord>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT block_height, tx_id, order_id, user FROM orders_cancelled
+             WHERE market = ?1 AND block_height >= ?2 AND (?3 IS NULL OR user = ?3)
+             ORDER BY block_height, tx_index, log_index",
+        )?;
+        let rows = stmt.query_map(
+            params![self.market, since_height, user.map(identity_str)],
+            |row| {
+                Ok(CancelRecord {
+                    block_height: row.get(0)?,
+                    tx_id: row.get(1)?,
+                    order_id: row.get(2)?,
+                    user: row.get(3)?,
+                })
+            },
+        )?;
+        Ok(rows.collect::<rusqlite::Result<_>>()?)
+    }
+
+    /// Fills where `user` bought or sold, or every fill, oldest first.
+    pub fn trades(&self, user: Option<&Identity>, since_height: u32) -> Result<Vec<TradeRecord>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT block_height, tx_id, sell_order_id, buy_order_id, seller, buyer, matcher,
+                    size, price
+             FROM trades
+             WHERE market = ?1 AND block_height >= ?2
+               AND (?3 IS NULL OR seller = ?3 OR buyer = ?3)
+             ORDER BY block_height, tx_index, log_index",
+        )?;
+        let rows = stmt.query_map(
+            params![self.market, since_height, user.map(identity_str)],
+            |row| {
+                Ok(TradeRecord {
+                    block_height: row.get(0)?,
+                    tx_id: row.get(1)?,
+                    sell_order_id: row.get(2)?,
+                    buy_order_id: row.get(3)?,
+                    seller: row.get(4)?,
+                    buyer: row.get(5)?,
+                    matcher: row.get(6)?,
+                    size: row.get(7)?,
+                    price: row.get(8)?,
+                })
+            },
+        )?;
+        Ok(rows.collect::<rusqlite::Result<_>>()?)
+    }
+
+    /// Deposits and withdrawals of `user`, or of everyone, oldest first.
+    pub fn transfers(
+        &self,
+        user: Option<&Identity>,
+        since_height: u32,
+    ) -> Result<Vec<TransferRecord>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT block_height, tx_id, kind, user, asset_id, amount FROM transfers
+             WHERE market = ?1 AND block_height >= ?2 AND (?3 IS NULL OR user = ?3)
+             ORDER BY block_height, tx_index, log_index",
+        )?;
+        let rows = stmt.query_map(
+            params![self.market, since_height, user.map(identity_str)],
+            |row| {
+                Ok(TransferRecord {
+                    block_height: row.get(0)?,
+                    tx_id: row.get(1)?,
+                    kind: row.get(2)?,
+                    user: row.get(3)?,
+                    asset_id: row.get(4)?,
+                    amount: row.get(5)?,
+                })
+            },
+        )?;
+        Ok(rows.collect::<rusqlite::Result<_>>()?)
+    }
+
+    /// Balance history of `user`, oldest first.
+    pub fn balances(&self, user: &Identity, since_height: u32) -> Result<Vec<BalanceRecord>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT block_height, tx_id, user, liquid_base, liquid_quote, locked_base, locked_quote
+             FROM balances
+             WHERE market = ?1 AND block_height >= ?2 AND user = ?3
+             ORDER BY block_height, tx_index, log_index",
+        )?;
+        let rows = stmt.query_map(
+            params![self.market, since_height, identity_str(user)],
+            |row| {
+                Ok(BalanceRecord {
+                    block_height: row.get(0)?,
+                    tx_id: row.get(1)?,
+                    user: row.get(2)?,
+                    liquid_base: row.get(3)?,
+                    liquid_quote: row.get(4)?,
+                    locked_base: row.get(5)?,
+                    locked_quote: row.get(6)?,
+                })
+            },
+        )?;
+        Ok(rows.collect::<rusqlite::Result<_>>()?)
+    }
+
+    /// Latest known balance of `user`, without asking the chain.
+    pub fn latest_balance(&self, user: &Identity) -> Result<Option<BalanceRecord>> {
+        Ok(self.balances(user, 0)?.pop())
+    }
+}
+
+fn insert_event(
+    tx: &Transaction,
+    market: &str,
+    meta: &EventMeta,
+    event: &MarketEvent,
+) -> Result<()> {
+    match event {
+        MarketEvent::Open(e) => {
+            tx.execute(
+                "INSERT OR IGNORE INTO orders_opened
+                 (market, block_height, tx_id, tx_index, log_index, order_id, user, side, amount,
+                  price)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+                params![
+                    market,
+                    meta.block_height,
+                    meta.tx_id,
+                    meta.tx_index,
+                    meta.log_index,
+                    hex_str_from_bits256(&e.order_id),
+                    identity_str(&e.user),
+                    side_str(&e.order_type),
+                    e.amount,
+                    e.price
+                ],
+            )?;
+            insert_balance(tx, market, meta, &e.user, &e.balance)?;
+        }
+        MarketEvent::Cancel(e) => {
+            tx.execute(
+                "INSERT OR IGNORE INTO orders_cancelled
+                 (market, block_height, tx_id, tx_index, log_index, order_id, user)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                params![
+                    market,
+                    meta.block_height,
+                    meta.tx_id,
+                    meta.tx_index,
+                    meta.log_index,
+                    hex_str_from_bits256(&e.order_id),
+                    identity_str(&e.user)
+                ],
+            )?;
+            insert_balance(tx, market, meta, &e.user, &e.balance)?;
+        }
+        MarketEvent::Trade(e) => {
+            tx.execute(
+                "INSERT OR IGNORE INTO trades
+                 (market, block_height, tx_id, tx_index, log_index, sell_order_id,
+                  buy_order_id, seller, buyer, matcher, size, price)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
+                params![
+                    market,
+                    meta.block_height,
+                    meta.tx_id,
+                    meta.tx_index,
+                    meta.log_index,
+                    hex_str_from_bits256(&e.base_sell_order_id),
+                    hex_str_from_bits256(&e.base_buy_order_id),
+                    identity_str(&e.order_seller),
+                    identity_str(&e.order_buyer),
+                    identity_str(&e.order_matcher),
+                    e.trade_size,
+                    e.trade_price
+                ],
+            )?;
+            insert_balance(tx, market, meta, &e.order_seller, &e.s_account_balance)?;
+            insert_balance(tx, market, meta, &e.order_buyer, &e.b_account_balance)?;
+        }
+        MarketEvent::Deposit(e) => {
+            insert_transfer(tx, market, meta, "deposit", &e.user, e.asset, e.amount)?;
+            insert_balance(tx, market, meta, &e.user, &e.account)?;
+        }
+        MarketEvent::Withdraw(e) => {
+            insert_transfer(tx, market, meta, "withdraw", &e.user, e.asset, e.amount)?;
+            insert_balance(tx, market, meta, &e.user, &e.account)?;
+        }
+    }
+    Ok(())
+}
+
+fn insert_transfer(
+    tx: &Transaction,
+    market: &str,
+    meta: &EventMeta,
+    kind: &str,
+    user: &Identity,
+    asset_id: AssetId,
+    amount: u64,
+) -> Result<()> {
+    tx.execute(
+        "INSERT OR IGNORE INTO transfers
+         (market, block_height, tx_id, tx_index, log_index, kind, user, asset_id, amount)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+        params![
+            market,
+            meta.block_height,
+            meta.tx_id,
+            meta.tx_index,
+            meta.log_index,
+            kind,
+            identity_str(user),
+            format!("0x{}", asset_id),
+            amount
+        ],
+    )?;
+    Ok(())
+}
+
+fn insert_balance(
+    tx: &Transaction,
+    market: &str,
+    meta: &EventMeta,
+    user: &Identity,
+    account: &Account,
+) -> Result<()> {
+    tx.execute(
+        "INSERT OR IGNORE INTO balances
+         (market, block_height, tx_id, tx_index, log_index, user,
+          liquid_base, liquid_quote, locked_base, locked_quote)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+        params![
+            market,
+            meta.block_height,
+            meta.tx_id,
+            meta.tx_index,
+            meta.log_index,
+            identity_str(user),
+            account.liquid.base,
+            account.liquid.quote,
+            account.locked.base,
+            account.locked.quote
+        ],
+    )?;
+    Ok(())
+}
+
+/// Decodes the market's events from a transaction's receipts, in log order.
+/// Logs of other contracts are skipped.
+pub fn decode_events(
+    decoder: &LogDecoder,
+    market: ContractId,
+    receipts: &[Receipt],
+) -> Result<Vec<(usize, MarketEvent)>> {
+    let mut events = Vec::new();
+    for (index, receipt) in receipts.iter().enumerate() {
+        if !matches!(receipt, Receipt::LogData { id, .. } if *id == market) {
+            continue;
+        }
+        if let Some(event) = decode_event(decoder, receipt)? {
+            events.push((index, event));
+        }
+    }
+    Ok(events)
+}
+
+fn decode_event(decoder: &LogDecoder, receipt: &Receipt) -> Result<Option<MarketEvent>> {
+    let receipt = std::slice::from_ref(receipt);
+    if let Some(e) = decoder
+        .decode_logs_with_type::<OpenOrderEvent>(receipt)?
+        .pop()
+    {
+        return Ok(Some(MarketEvent::Open(e)));
+    }
+    if let Some(e) = decoder
+        .decode_logs_with_type::<CancelOrderEvent>(receipt)?
+        .pop()
+    {
+        return Ok(Some(MarketEvent::Cancel(e)));
+    }
+    if let Some(e) = decoder
+        .decode_logs_with_type::<TradeOrderEvent>(receipt)?
+        .pop()
+    {
+        return Ok(Some(MarketEvent::Trade(e)));
+    }
+    if let Some(e) = decoder
+        .decode_logs_with_type::<DepositEvent>(receipt)?
+        .pop()
+    {
+        return Ok(Some(MarketEvent::Deposit(e)));
+    }
+    if let Some(e) = decoder
+        .decode_logs_with_type::<WithdrawEvent>(receipt)?
+        .pop()
+    {
+        return Ok(Some(MarketEvent::Withdraw(e)));
+    }
+    Ok(None)
+}
+
+/// What one `sync` call covered.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SyncStats {
+    pub blocks: usize,
+    pub transactions: usize,
+    pub events: usize,
+    /// Last block indexed, if any.
+    pub height: Option<u32>,
+}
+
+/// Follows one market's logs into an `EventStore`.
+pub struct Indexer {
+    provider: Provider,
+    market_id: ContractId,
+    decoder: LogDecoder,
+    store: EventStore,
+    page_size: i32,
+}
+
+impl Indexer {
+    pub fn new(market: &SparkMarketContract, provider: Provider, store: EventStore) -> Self {
+        let instance = market.get_instance();
+        Self {
+            provider,
+            market_id: instance.contract_id().into(),
+            decoder: instance.log_decoder(),
+            store,
+            page_size: DEFAULT_PAGE_SIZE,
+        }
+    }
+
+    pub fn with_page_size(mut self, page_size: i32) -> Self {
+        self.page_size = page_size.max(1);
+        self
+    }
+
+    pub fn store(&self) -> &EventStore {
+        &self.store
+    }
+
+    /// Indexes every block after the stored cursor, or from `start_height` on the
+    /// first run, up to the current chain head.
+    pub async fn sync(&mut self, start_height: u32) -> Result<SyncStats> {
+        let from = match self.store.cursor()? {
+            Some(height) => height.max(start_height.saturating_sub(1)) + 1,
+            None => start_height,
+        };
+        let head = self.provider.latest_block_height().await?;
+
+        let mut stats = SyncStats::default();
+        if from > head {
+            return Ok(stats);
+        }
+
+        // Block cursors are heights; pages start after the cursor
+        let mut cursor = from.checked_sub(1).map(|height| height.to_string());
+        loop {
+            let page = self
+                .provider
+                .get_blocks(PaginationRequest {
+                    cursor: cursor.clone(),
+                    results: self.page_size,
+                    direction: PageDirection::Forward,
+                })
+                .await?;
+
+            for block in &page.results {
+                let height = block.header.height;
+                if height < from {
+                    continue;
+                }
+                let mut events = Vec::new();
+                for (tx_index, tx_id) in block.transactions.iter().enumerate() {
+                    let receipts = match self.provider.tx_status(tx_id).await? {
+                        TxStatus::Success { receipts } => receipts,
+                        _ => continue,
+                    };
+                    stats.transactions += 1;
+                    events.extend(self.decode(height, tx_index, tx_id, &receipts)?);
+                }
+                stats.events += events.len();
+                self.store.insert_block(height, &events)?;
+                stats.blocks += 1;
+                stats.height = Some(height);
+            }
+
+            if !page.has_next_page || page.results.is_empty() {
+                break;
+            }
+            cursor = page.cursor;
+        }
+        Ok(stats)
+    }
+
+    /// Stores the events of a transaction whose receipts are already known,
+    /// e.g. from a call response. `tx_index` is its position within the block.
+    /// Returns how many were found.
+    pub fn index_receipts(
+        &mut self,
+        block_height: u32,
+        tx_index: usize,
+        tx_id: &Bytes32,
+        receipts: &[Receipt],
+    ) -> Result<usize> {
+        let events = self.decode(block_height, tx_index, tx_id, receipts)?;
+        self.store.insert_events(&events)?;
+        Ok(events.len())
+    }
+
+    fn decode(
+        &self,
+        block_height: u32,
+        tx_index: usize,
+        tx_id: &Bytes32,
+        receipts: &[Receipt],
+    ) -> Result<Vec<(EventMeta, MarketEvent)>> {
+        let tx_id = format!("0x{}", tx_id);
+        Ok(decode_events(&self.decoder, self.market_id, receipts)?
+            .into_iter()
+            .map(|(log_index, event)| {
+                let meta = EventMeta {
+                    block_height,
+                    tx_id: tx_id.clone(),
+                    tx_index,
+                    log_index,
+                };
+                (meta, event)
+            })
+            .collect())
+    }
+}
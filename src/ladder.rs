@@ -0,0 +1,284 @@
+//! Ladders of orders spread around a mid price.
+//!
+//! `LadderBuilder` places levels every `step` (or `levels` evenly) out to
+//! `range` on each side of the mid price and splits a base budget over the asks
+//! and a quote budget over the bids according to a `Distribution`. Everything
+//! here is pure: no calls, just the orders to open.
+
+use anyhow::{bail, Result};
+use spark_market_sdk::OrderType;
+
+use crate::{
+    amount::{Price, Quantity, Rounding},
+    maker::DesiredOrder,
+    market::MarketInfo,
+};
+
+/// Weights are turned into shares of a budget with this precision.
+const SHARE_SCALE: u64 = 1_000_000_000;
+
+/// How liquidity is spread over the levels of one side of a ladder.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Distribution {
+    /// A bell curve centred on the mid price; the range spans `sigmas`
+    /// standard deviations.
+    Normal { sigmas: f64 },
+    /// Falls in a straight line from 1 at the mid price to `edge` at the end
+    /// of the range.
+    Linear { edge: f64 },
+    /// Decays as `e^(-rate x)` where `x` goes from 0 at the mid price to 1 at
+    /// the end of the range.
+    Exponential { rate: f64 },
+    /// The same amount on every level.
+    Flat,
+    /// One weight per level, innermost first.
+    Custom(Vec<f64>),
+}
+
+impl Distribution {
+    /// Three standard deviations over the range, as the load tests use.
+    pub fn normal() -> Self {
+        Distribution::Normal { sigmas: 3.0 }
+    }
+
+    /// Relative weights of levels at `positions`, each the distance from the
+    /// mid price as a fraction of the range. Weights are not normalised.
+    pub fn weights(&self, positions: &[f64]) -> Result<Vec<f64>> {
+        let weights: Vec<f64> = match self {
+            Distribution::Normal { sigmas } => {
+                if *sigmas <= 0.0 {
+                    bail!(
+                        "A normal distribution needs a positive width, got {}",
+                        sigmas
+                    );
+                }
+                positions
+                    .iter()
+                    .map(|x| (-(x * sigmas).powi(2) / 2.0).exp())
+                    .collect()
+            }
+            Distribution::Linear { edge } => {
+                positions.iter().map(|x| 1.0 - (1.0 - edge) * x).collect()
+            }
+            Distribution::Exponential { rate } => {
+                positions.iter().map(|x| (-rate * x).exp()).collect()
+            }
+            Distribution::Flat => vec![1.0; positions.len()],
+            Distribution::Custom(weights) => {
+                if weights.len() != positions.len() {
+                    bail!(
+                        "{} custom weights given for {} levels",
+                        weights.len(),
+                        positions.len()
+                    );
+                }
+                weights.clone()
+            }
+        };
+        if let Some(bad) = weights.iter().find(|w| !w.is_finite() || **w < 0.0) {
+            bail!("Level weights must be finite and not negative, got {}", bad);
+        }
+        if !positions.is_empty() && weights.iter().sum::<f64>() <= 0.0 {
+            bail!("Level weights add up to zero");
+        }
+        Ok(weights)
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Spacing {
+    Step(Price),
+    Levels(usize),
+}
+
+/// Builds a ladder of bids below and asks above a mid price.
+///
+/// ```ignore
+/// let orders = LadderBuilder::new(&market, mid)
+///     .range(market.parse_price("500")?)
+///     .step(market.parse_price("10")?)
+///     .distribution(Distribution::normal())
+///     .base_budget(market.base.parse("1")?)
+///     .quote_budget(market.quote.parse("3000")?)
+///     .min_size(market.base.parse("0.001")?)
+///     .build()?;
+/// ```
+///
+/// A side without a budget is not quoted. Amounts are rounded down, so a side
+/// never uses more than its budget, and levels below `min_size` are dropped
+/// rather than redistributed.
+#[derive(Debug, Clone)]
+pub struct LadderBuilder<'a> {
+    market: &'a MarketInfo,
+    mid: Price,
+    range: Option<Price>,
+    spacing: Option<Spacing>,
+    distribution: Distribution,
+    base_budget: Option<Quantity>,
+    quote_budget: Option<Quantity>,
+    min_size: Option<Quantity>,
+}
+
+impl<'a> LadderBuilder<'a> {
+    pub fn new(market: &'a MarketInfo, mid: Price) -> Self {
+        Self {
+            market,
+            mid,
+            range: None,
+            spacing: None,
+            distribution: Distribution::Flat,
+            base_budget: None,
+            quote_budget: None,
+            min_size: None,
+        }
+    }
+
+    /// Distance from the mid price to the outermost level on each side.
+    pub fn range(mut self, range: Price) -> Self {
+        self.range = Some(range);
+        self
+    }
+
+    /// Distance between levels; the first level is one step from the mid price.
+    pub fn step(mut self, step: Price) -> Self {
+        self.spacing = Some(Spacing::Step(step));
+        self
+    }
+
+    /// Number of levels per side, evenly spread over the range.
+    pub fn levels(mut self, levels: usize) -> Self {
+        self.spacing = Some(Spacing::Levels(levels));
+        self
+    }
+
+    pub fn distribution(mut self, distribution: Distribution) -> Self {
+        self.distribution = distribution;
+        self
+    }
+
+    /// Total base to offer on the asks.
+    pub fn base_budget(mut self, budget: Quantity) -> Self {
+        self.base_budget = Some(budget);
+        self
+    }
+
+    /// Total quote to bid with.
+    pub fn quote_budget(mut self, budget: Quantity) -> Self {
+        self.quote_budget = Some(budget);
+        self
+    }
+
+    /// Smallest base amount worth opening.
+    pub fn min_size(mut self, min_size: Quantity) -> Self {
+        self.min_size = Some(min_size);
+        self
+    }
+
+    /// The raw range and the raw distance of each level from the mid price,
+    /// innermost first.
+    fn distances(&self) -> Result<(u64, Vec<u64>)> {
+        let Some(range) = self.range else {
+            bail!("A ladder needs a range");
+        };
+        let range = range.raw();
+        let (step, levels) = match self.spacing {
+            Some(Spacing::Step(step)) if step.raw() == 0 => bail!("Ladder step must not be zero"),
+            Some(Spacing::Step(step)) => (step.raw(), range / step.raw()),
+            Some(Spacing::Levels(0)) => bail!("A ladder needs at least one level"),
+            Some(Spacing::Levels(levels)) => (range / levels as u64, levels as u64),
+            None => bail!("A ladder needs a step or a number of levels"),
+        };
+        if step == 0 || levels == 0 {
+            bail!("Range {} is too narrow for the requested levels", range);
+        }
+        Ok((range, (1..=levels).map(|level| level * step).collect()))
+    }
+
+    fn check_decimals(&self) -> Result<()> {
+        let market = self.market;
+        let prices = [Some(self.mid), self.range, self.spacing_step()];
+        for price in prices.into_iter().flatten() {
+            if price.decimals() != market.price_decimals {
+                bail!(
+                    "Ladder price {} has {} decimals, the market uses {}",
+                    price,
+                    price.decimals(),
+                    market.price_decimals
+                );
+            }
+        }
+        let amounts = [
+            (self.base_budget, &market.base),
+            (self.min_size, &market.base),
+            (self.quote_budget, &market.quote),
+        ];
+        for (amount, asset) in amounts {
+            if let Some(amount) = amount.filter(|a| a.decimals() != asset.decimals) {
+                bail!(
+                    "Ladder amount {} has {} decimals, {} uses {}",
+                    amount,
+                    amount.decimals(),
+                    asset.symbol,
+                    asset.decimals
+                );
+            }
+        }
+        Ok(())
+    }
+
+    fn spacing_step(&self) -> Option<Price> {
+        match self.spacing {
+            Some(Spacing::Step(step)) => Some(step),
+            _ => None,
+        }
+    }
+
+    pub fn build(&self) -> Result<Vec<DesiredOrder>> {
+        self.check_decimals()?;
+        let market = self.market;
+        let (range, distances) = self.distances()?;
+        let positions: Vec<f64> = distances.iter().map(|d| *d as f64 / range as f64).collect();
+        let weights = self.distribution.weights(&positions)?;
+        let total: f64 = weights.iter().sum();
+
+        let mut orders = Vec::with_capacity(distances.len() * 2);
+        for (distance, weight) in distances.iter().zip(&weights) {
+            let share = ((weight / total) * SHARE_SCALE as f64).floor() as u64;
+
+            if let Some(budget) = self.quote_budget {
+                if let Some(raw) = self.mid.raw().checked_sub(*distance).filter(|p| *p > 0) {
+                    let price = market.price(raw);
+                    let quote = budget.mul_div(share, SHARE_SCALE, Rounding::Down)?;
+                    let amount = market.base_for(quote, price, Rounding::Down)?;
+                    self.push(&mut orders, OrderType::Buy, amount, price);
+                }
+            }
+            if let Some(budget) = self.base_budget {
+                let Some(raw) = self.mid.raw().checked_add(*distance) else {
+                    bail!("Ask {} away from {} overflows", distance, self.mid);
+                };
+                let amount = budget.mul_div(share, SHARE_SCALE, Rounding::Down)?;
+                self.push(&mut orders, OrderType::Sell, amount, market.price(raw));
+            }
+        }
+        Ok(orders)
+    }
+
+    fn push(
+        &self,
+        orders: &mut Vec<DesiredOrder>,
+        side: OrderType,
+        amount: Quantity,
+        price: Price,
+    ) {
+        let too_small = self.min_size.is_some_and(|min| amount.raw() < min.raw());
+        if amount.is_zero() || too_small {
+            return;
+        }
+        orders.push(DesiredOrder {
+            side,
+            amount,
+            price,
+        });
+    }
+}
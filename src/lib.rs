@@ -0,0 +1,37 @@
+//! Shared building blocks for the Spark examples and bots.
+//!
+//! Every binary in `src/bin` used to carry its own copy of the provider/wallet
+//! setup and the amount formatting helpers. They now live here.
+
+pub mod amount;
+pub mod assets;
+pub mod batch;
+pub mod config;
+pub mod context;
+pub mod error;
+pub mod fees;
+pub mod indexer;
+pub mod ladder;
+pub mod maker;
+pub mod market;
+pub mod matcher;
+pub mod oracle;
+pub mod orderbook;
+pub mod router;
+pub mod simulate;
+pub mod swap;
+pub mod utils;
+pub mod validate;
+pub mod views;
+
+pub use assets::{AssetCatalogue, AssetInfo};
+pub use config::Config;
+pub use context::SparkContext;
+pub use error::SparkError;
+pub use fees::FeeSchedule;
+pub use ladder::{Distribution, LadderBuilder};
+pub use market::MarketInfo;
+pub use oracle::{Oracle, PriceSource};
+pub use orderbook::OrderBook;
+pub use router::Router;
+pub use swap::Swap;
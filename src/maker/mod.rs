@@ -0,0 +1,373 @@
+//! A market-making loop that works on any market.
+//!
+//! Each cycle `MarketMaker` reads a fair value from a `PriceSource`, asks its
+//! `QuoteStrategy` which orders should rest on the book, lets the `Reconciler`
+//! compare them with the orders already there, cancels the ones no longer
+//! wanted, deposits whatever the new orders are short of and opens them in
+//! multicalls. Orders within the tolerance of a quote are left alone. With
+//! `MakerConfig::dry_run` the same calls are simulated and reported instead.
+
+mod reconcile;
+mod skew;
+mod strategy;
+
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+use fuels::{prelude::CallParameters, types::Bits256};
+use tokio::time::sleep;
+
+pub use reconcile::{Plan, Reconciler, Tolerance};
+pub use skew::{InventorySkew, SkewCurve};
+pub use strategy::{BandStrategy, DesiredOrder, Inventory, QuoteInput, QuoteStrategy};
+
+use crate::{
+    amount::{Quantity, Rounding},
+    batch::{BatchReport, BatchSubmitter, CallStatus, DEPOSIT_GAS},
+    error::SparkError,
+    oracle::PriceSource,
+    orderbook::OrderBook,
+    simulate::Simulation,
+    utils::hex_str_from_bits256,
+    validate::{OnViolation, OrderValidator},
+    SparkContext,
+};
+
+#[derive(Debug, Clone)]
+pub struct MakerConfig {
+    /// Pause between cycles.
+    pub interval: Duration,
+    /// Most calls per multicall transaction; transactions are also kept under
+    /// the chain's gas and size limits.
+    pub max_calls_per_tx: usize,
+    /// Deposit from the wallet when the market balance cannot cover the new orders.
+    pub top_up: bool,
+    /// How long to wait for each transaction to be included.
+    pub tx_timeout: Duration,
+    /// How far resting orders may drift from the quotes before being replaced.
+    pub tolerance: Tolerance,
+    /// Simulate each cycle's cancels, deposits and opens instead of sending them.
+    pub dry_run: bool,
+}
+
+impl Default for MakerConfig {
+    fn default() -> Self {
+        Self {
+            interval: Duration::from_secs(10),
+            max_calls_per_tx: 10,
+            top_up: true,
+            tx_timeout: Duration::from_secs(60),
+            tolerance: Tolerance::default(),
+            dry_run: false,
+        }
+    }
+}
+
+/// What one cycle did.
+#[derive(Debug, Clone, Default)]
+pub struct CycleReport {
+    pub fair_value: f64,
+    pub kept: usize,
+    pub cancelled: Vec<Bits256>,
+    pub opened: Vec<DesiredOrder>,
+    /// Raw amounts deposited to cover the new orders.
+    pub deposited_base: u64,
+    pub deposited_quote: u64,
+    pub tx_ids: Vec<String>,
+    /// Cancels and opens that failed and were left out, with the reason. Cancels
+    /// of orders that were filled in the meantime are not listed.
+    pub failed: Vec<String>,
+    /// In a dry run, what each transaction would do. The cancels, deposits and
+    /// opens above are then the ones planned rather than sent.
+    pub simulations: Vec<Simulation>,
+}
+
+pub struct MarketMaker<'a, S> {
+    ctx: &'a SparkContext,
+    prices: Box<dyn PriceSource>,
+    strategy: S,
+    config: MakerConfig,
+}
+
+impl<'a, S: QuoteStrategy> MarketMaker<'a, S> {
+    pub fn new(ctx: &'a SparkContext, prices: impl PriceSource + 'static, strategy: S) -> Self {
+        Self {
+            ctx,
+            prices: Box::new(prices),
+            strategy,
+            config: MakerConfig::default(),
+        }
+    }
+
+    pub fn with_config(mut self, config: MakerConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    pub fn strategy(&self) -> &S {
+        &self.strategy
+    }
+
+    /// Runs cycles forever. A failed cycle is reported and retried after the
+    /// usual interval rather than ending the loop.
+    pub async fn run(&self) -> Result<()> {
+        loop {
+            match self.cycle().await {
+                Ok(report) => {
+                    println!(
+                        "Fair value {:.6}: kept {}, cancelled {}, opened {}",
+                        report.fair_value,
+                        report.kept,
+                        report.cancelled.len(),
+                        report.opened.len()
+                    );
+                    let info = &self.ctx.market_info;
+                    for (asset, amount) in [
+                        (&info.base, report.deposited_base),
+                        (&info.quote, report.deposited_quote),
+                    ] {
+                        if amount > 0 {
+                            println!("  Deposited {} {}", asset.quantity(amount), asset.symbol);
+                        }
+                    }
+                    for id in &report.cancelled {
+                        println!("  Cancelled {}", hex_str_from_bits256(id));
+                    }
+                    for order in &report.opened {
+                        println!(
+                            "  Opened {:?} {} at {}",
+                            order.side, order.amount, order.price
+                        );
+                    }
+                    for failure in &report.failed {
+                        println!("  {}", failure);
+                    }
+                    for (tx, simulation) in report.simulations.iter().enumerate() {
+                        println!("  Dry run of transaction {}: {}", tx, simulation);
+                    }
+                }
+                Err(e) => println!("Market maker cycle failed: {}", SparkError::from(&e)),
+            }
+            sleep(self.config.interval).await;
+        }
+    }
+
+    /// One full pass: price, quote, cancel, top up, open.
+    pub async fn cycle(&self) -> Result<CycleReport> {
+        let ctx = self.ctx;
+        let info = &ctx.market_info;
+
+        let point = self.prices.price().await?;
+        let fair_value = info.price_from_f64(point.price, Rounding::Nearest)?;
+
+        let account = ctx.market.account(ctx.wallet_id()).await?.value;
+        let held = |liquid: u64, locked: u64, symbol: &str| {
+            liquid
+                .checked_add(locked)
+                .ok_or_else(|| anyhow!("Market balance of {} overflows u64", symbol))
+        };
+        let inventory = Inventory {
+            base: info.base.quantity(held(
+                account.liquid.base,
+                account.locked.base,
+                &info.base.symbol,
+            )?),
+            quote: info.quote.quantity(held(
+                account.liquid.quote,
+                account.locked.quote,
+                &info.quote.symbol,
+            )?),
+        };
+        let desired = self.strategy.quotes(&QuoteInput {
+            market: info,
+            fair_value,
+            inventory,
+        })?;
+
+        // Quotes off the market's tick, lot or minimums are fixed or left out here
+        let mut rejected = Vec::new();
+        let desired: Vec<_> = self
+            .validator()
+            .check_all(&desired, None)
+            .into_iter()
+            .filter_map(|checked| match checked {
+                Ok(valid) => Some(valid.order),
+                Err(rejection) => {
+                    rejected.push(rejection.to_string());
+                    None
+                }
+            })
+            .collect();
+
+        let live = OrderBook::builder(&ctx.market, info)
+            .maker(ctx.wallet_id())
+            .build()
+            .await?
+            .orders;
+        let plan = Reconciler::new(self.config.tolerance).plan(&desired, &live);
+
+        let mut report = CycleReport {
+            fair_value: point.price,
+            kept: plan.keep.len(),
+            failed: rejected,
+            ..Default::default()
+        };
+
+        if self.config.dry_run {
+            return self.simulate(&plan, report).await;
+        }
+
+        // A cancel fails when the order was filled in the meantime: nothing to undo
+        let cancels = self.cancel(&plan.cancel).await?;
+        report.tx_ids.extend(cancels.tx_ids());
+        for (i, id) in plan.cancel.iter().enumerate() {
+            match &cancels.calls[i] {
+                CallStatus::Included { .. } => report.cancelled.push(*id),
+                CallStatus::Failed {
+                    error: SparkError::OrderNotFound(_),
+                } => {}
+                CallStatus::Failed { error } => report.failed.push(format!(
+                    "Cancel {} failed: {}",
+                    hex_str_from_bits256(id),
+                    error
+                )),
+                CallStatus::NotSent => {}
+            }
+        }
+
+        if self.config.top_up {
+            let (base, quote) = self.shortfall(&plan.open).await?;
+            let mut batch = self.batch();
+            self.push_deposits(&mut batch, base, quote)?;
+            let deposits = batch.submit().await?;
+            report.tx_ids.extend(deposits.tx_ids());
+            deposits.ensure_success()?;
+            report.deposited_base = base;
+            report.deposited_quote = quote;
+        }
+
+        let opens = self.open(&plan.open).await?;
+        report.tx_ids.extend(opens.tx_ids());
+        for (order, status) in plan.open.iter().zip(&opens.calls) {
+            if let CallStatus::Included { .. } = status {
+                report.opened.push(order.clone());
+            }
+        }
+        for (i, error) in opens.failures() {
+            let order = &plan.open[i];
+            report.failed.push(format!(
+                "Open {:?} {} at {} failed: {}",
+                order.side, order.amount, order.price, error
+            ));
+        }
+        Ok(report)
+    }
+
+    /// Runs the cycle's cancels, deposits and opens as one batch of dry runs.
+    async fn simulate(&self, plan: &Plan, mut report: CycleReport) -> Result<CycleReport> {
+        let mut batch = self.batch();
+        self.push_cancels(&mut batch, &plan.cancel);
+        if self.config.top_up {
+            // Nothing is cancelled yet, so this may deposit more than a real cycle
+            let (base, quote) = self.shortfall(&plan.open).await?;
+            self.push_deposits(&mut batch, base, quote)?;
+            report.deposited_base = base;
+            report.deposited_quote = quote;
+        }
+        self.push_opens(&mut batch, &plan.open);
+
+        report.cancelled = plan.cancel.clone();
+        report.opened = plan.open.clone();
+        report.simulations = batch.simulate().await?;
+        Ok(report)
+    }
+
+    fn validator(&self) -> OrderValidator<'a> {
+        OrderValidator::new(&self.ctx.market_info).on_violation(OnViolation::Round)
+    }
+
+    fn batch(&self) -> BatchSubmitter<'a> {
+        BatchSubmitter::new(self.ctx)
+            .max_calls(self.config.max_calls_per_tx)
+            .timeout(self.config.tx_timeout)
+    }
+
+    async fn cancel(&self, ids: &[Bits256]) -> Result<BatchReport> {
+        let mut batch = self.batch();
+        self.push_cancels(&mut batch, ids);
+        batch.submit().await
+    }
+
+    async fn open(&self, orders: &[DesiredOrder]) -> Result<BatchReport> {
+        let mut batch = self.batch();
+        self.push_opens(&mut batch, orders);
+        batch.submit().await
+    }
+
+    fn push_cancels(&self, batch: &mut BatchSubmitter<'a>, ids: &[Bits256]) {
+        let ctx = self.ctx;
+        for id in ids {
+            batch.push(
+                ctx.market
+                    .get_instance()
+                    .methods()
+                    .cancel_order(*id)
+                    .with_contract_ids(&ctx.contract_ids()),
+            );
+        }
+    }
+
+    fn push_opens(&self, batch: &mut BatchSubmitter<'a>, orders: &[DesiredOrder]) {
+        let ctx = self.ctx;
+        for order in orders {
+            batch.push(
+                ctx.market
+                    .get_instance()
+                    .methods()
+                    .open_order(order.amount.raw(), order.side.clone(), order.price.raw())
+                    .with_contract_ids(&ctx.contract_ids()),
+            );
+        }
+    }
+
+    /// The raw base the new sells and quote and fees the new buys are missing
+    /// from the liquid balance.
+    async fn shortfall(&self, orders: &[DesiredOrder]) -> Result<(u64, u64)> {
+        let ctx = self.ctx;
+        let info = &ctx.market_info;
+
+        let validator = self.validator();
+        let mut base = Quantity::zero(info.base.decimals);
+        let mut quote = Quantity::zero(info.quote.decimals);
+        for order in orders {
+            let (locked_base, locked_quote, _) = validator.locks(order)?;
+            base = base.checked_add(locked_base)?;
+            quote = quote.checked_add(locked_quote)?;
+        }
+
+        let account = ctx.market.account(ctx.wallet_id()).await?.value;
+        Ok((
+            base.raw().saturating_sub(account.liquid.base),
+            quote.raw().saturating_sub(account.liquid.quote),
+        ))
+    }
+
+    fn push_deposits(&self, batch: &mut BatchSubmitter<'a>, base: u64, quote: u64) -> Result<()> {
+        let ctx = self.ctx;
+        let info = &ctx.market_info;
+        for (amount, asset_id) in [(base, info.base.asset_id), (quote, info.quote.asset_id)] {
+            if amount == 0 {
+                continue;
+            }
+            batch.push(
+                ctx.market
+                    .get_instance()
+                    .methods()
+                    .deposit()
+                    .with_contract_ids(&ctx.contract_ids())
+                    .call_params(CallParameters::new(amount, asset_id, DEPOSIT_GAS))?,
+            );
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,131 @@
+use fuels::types::Bits256;
+
+use crate::orderbook::BookOrder;
+
+use super::DesiredOrder;
+
+/// How far a live order may drift from a desired one and still stand in for it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Tolerance {
+    /// Price distance relative to the desired price, in basis points.
+    pub price_bps: u64,
+    /// Size difference relative to the desired amount, in basis points. Covers
+    /// partial fills and small changes in the order value.
+    pub amount_bps: u64,
+}
+
+impl Tolerance {
+    /// Only identical orders stand in for each other.
+    pub const EXACT: Tolerance = Tolerance {
+        price_bps: 0,
+        amount_bps: 0,
+    };
+}
+
+impl Default for Tolerance {
+    /// 0.05% in price, 5% in size.
+    fn default() -> Self {
+        Self {
+            price_bps: 5,
+            amount_bps: 500,
+        }
+    }
+}
+
+/// The calls that turn the live orders into the desired ones.
+#[derive(Debug, Clone, Default)]
+pub struct Plan {
+    /// Live orders left in place, with the desired order each one covers.
+    pub keep: Vec<(Bits256, DesiredOrder)>,
+    pub cancel: Vec<Bits256>,
+    pub open: Vec<DesiredOrder>,
+}
+
+impl Plan {
+    /// Whether the book already looks like the quotes.
+    pub fn is_empty(&self) -> bool {
+        self.cancel.is_empty() && self.open.is_empty()
+    }
+}
+
+/// Works out the fewest cancels and opens between the quotes a strategy wants
+/// and the orders the maker has on the book.
+///
+/// Orders are paired per side, closest prices first, as long as both price and
+/// size are within the tolerance. Each live order covers at most one desired
+/// order, so a second order resting on the same level is cancelled.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Reconciler {
+    pub tolerance: Tolerance,
+}
+
+impl Reconciler {
+    pub fn new(tolerance: Tolerance) -> Self {
+        Self { tolerance }
+    }
+
+    pub fn exact() -> Self {
+        Self::new(Tolerance::EXACT)
+    }
+
+    /// Whether `live` is close enough to stand in for `desired`.
+    pub fn covers(&self, live: &BookOrder, desired: &DesiredOrder) -> bool {
+        live.side == desired.side
+            && within_bps(
+                live.price.raw(),
+                desired.price.raw(),
+                self.tolerance.price_bps,
+            )
+            && within_bps(
+                live.amount.raw(),
+                desired.amount.raw(),
+                self.tolerance.amount_bps,
+            )
+    }
+
+    pub fn plan(&self, desired: &[DesiredOrder], live: &[BookOrder]) -> Plan {
+        // Every acceptable pairing, nearest price first, amount as tie-break
+        let mut pairs = Vec::new();
+        for (d, wanted) in desired.iter().enumerate() {
+            for (l, order) in live.iter().enumerate() {
+                if self.covers(order, wanted) {
+                    let distance = (
+                        order.price.raw().abs_diff(wanted.price.raw()),
+                        order.amount.raw().abs_diff(wanted.amount.raw()),
+                    );
+                    pairs.push((distance, d, l));
+                }
+            }
+        }
+        pairs.sort();
+
+        let mut covered_by = vec![None; desired.len()];
+        let mut used = vec![false; live.len()];
+        for (_, d, l) in pairs {
+            if covered_by[d].is_none() && !used[l] {
+                covered_by[d] = Some(l);
+                used[l] = true;
+            }
+        }
+
+        let mut plan = Plan::default();
+        for (wanted, covered) in desired.iter().zip(covered_by) {
+            match covered {
+                Some(l) => plan.keep.push((live[l].id, wanted.clone())),
+                None => plan.open.push(wanted.clone()),
+            }
+        }
+        plan.cancel = live
+            .iter()
+            .zip(used)
+            .filter(|(_, used)| !used)
+            .map(|(order, _)| order.id)
+            .collect();
+        plan
+    }
+}
+
+/// `|actual - target| <= target * bps / 10_000`
+fn within_bps(actual: u64, target: u64, bps: u64) -> bool {
+    u128::from(actual.abs_diff(target)) * 10_000 <= u128::from(target) * u128::from(bps)
+}
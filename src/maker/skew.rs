@@ -0,0 +1,176 @@
+use anyhow::{bail, Result};
+use spark_market_sdk::OrderType;
+
+use crate::amount::Rounding;
+
+use super::{DesiredOrder, QuoteInput, QuoteStrategy};
+
+/// How strongly the skew reacts to a given drift from the target.
+/// Every curve maps 0 to 0 and 1 (inventory entirely on one side) to 1.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SkewCurve {
+    /// Proportional to the drift.
+    Linear,
+    /// Gentle near the target, steep near the edges.
+    Quadratic,
+    /// `(e^(k x) - 1) / (e^k - 1)`; larger `k` waits longer before reacting.
+    Exponential(f64),
+}
+
+impl SkewCurve {
+    pub fn apply(&self, drift: f64) -> f64 {
+        let x = drift.clamp(0.0, 1.0);
+        match *self {
+            SkewCurve::Linear => x,
+            SkewCurve::Quadratic => x * x,
+            SkewCurve::Exponential(k) if k.abs() < f64::EPSILON => x,
+            SkewCurve::Exponential(k) => (k * x).exp_m1() / k.exp_m1(),
+        }
+    }
+}
+
+/// Wraps another strategy and leans its quotes against the inventory.
+///
+/// Inventory is measured as the share of its value held in base at the fair
+/// value. When that share is above `target_base_ratio` every price moves down
+/// by up to `max_price_shift_bps` and sells grow while buys shrink by up to
+/// `max_size_skew_bps`, so fills bring the inventory back; below the target it
+/// is the other way round. Past `max_base_ratio` buys stop altogether, below
+/// `min_base_ratio` sells do.
+///
+/// Keep `max_price_shift_bps` below the inner strategy's spread, or the
+/// innermost quote on the heavy side ends up on the wrong side of fair value.
+#[derive(Debug, Clone)]
+pub struct InventorySkew<S> {
+    inner: S,
+    pub target_base_ratio: f64,
+    pub curve: SkewCurve,
+    pub max_price_shift_bps: u64,
+    pub max_size_skew_bps: u64,
+    pub min_base_ratio: f64,
+    pub max_base_ratio: f64,
+}
+
+impl<S: QuoteStrategy> InventorySkew<S> {
+    /// Aims for half the value in each asset, shifting prices by up to 10 bps
+    /// and sizes by up to 50%, and quotes one side only past 10% / 90%.
+    pub fn new(inner: S) -> Self {
+        Self {
+            inner,
+            target_base_ratio: 0.5,
+            curve: SkewCurve::Linear,
+            max_price_shift_bps: 10,
+            max_size_skew_bps: 5_000,
+            min_base_ratio: 0.1,
+            max_base_ratio: 0.9,
+        }
+    }
+
+    pub fn target(mut self, base_ratio: f64) -> Self {
+        self.target_base_ratio = base_ratio;
+        self
+    }
+
+    pub fn curve(mut self, curve: SkewCurve) -> Self {
+        self.curve = curve;
+        self
+    }
+
+    pub fn max_price_shift_bps(mut self, bps: u64) -> Self {
+        self.max_price_shift_bps = bps;
+        self
+    }
+
+    pub fn max_size_skew_bps(mut self, bps: u64) -> Self {
+        self.max_size_skew_bps = bps;
+        self
+    }
+
+    /// Base ratios outside `min..=max` quote one side only.
+    pub fn limits(mut self, min_base_ratio: f64, max_base_ratio: f64) -> Self {
+        self.min_base_ratio = min_base_ratio;
+        self.max_base_ratio = max_base_ratio;
+        self
+    }
+
+    pub fn inner(&self) -> &S {
+        &self.inner
+    }
+
+    /// Share of the inventory value held in base, or `None` for an empty inventory.
+    pub fn base_ratio(&self, input: &QuoteInput) -> Result<Option<f64>> {
+        let base = input
+            .market
+            .quote_for(input.inventory.base, input.fair_value, Rounding::Nearest)?
+            .to_f64();
+        let total = base + input.inventory.quote.to_f64();
+        if total <= 0.0 {
+            return Ok(None);
+        }
+        Ok(Some(base / total))
+    }
+
+    /// Signed skew in `-1.0..=1.0`: positive when long base, negative when long quote.
+    pub fn skew(&self, base_ratio: f64) -> f64 {
+        let target = self.target_base_ratio;
+        let drift = if base_ratio >= target {
+            (base_ratio - target) / (1.0 - target)
+        } else {
+            (base_ratio - target) / target
+        };
+        drift.signum() * self.curve.apply(drift.abs())
+    }
+
+    fn validate(&self) -> Result<()> {
+        let target = self.target_base_ratio;
+        if !(target > 0.0 && target < 1.0) {
+            bail!("Target base ratio must be between 0 and 1, got {}", target);
+        }
+        if !(self.min_base_ratio <= target && target <= self.max_base_ratio) {
+            bail!(
+                "Target base ratio {} is outside the limits {}..={}",
+                target,
+                self.min_base_ratio,
+                self.max_base_ratio
+            );
+        }
+        Ok(())
+    }
+}
+
+impl<S: QuoteStrategy> QuoteStrategy for InventorySkew<S> {
+    fn quotes(&self, input: &QuoteInput) -> Result<Vec<DesiredOrder>> {
+        self.validate()?;
+        let orders = self.inner.quotes(input)?;
+        let Some(ratio) = self.base_ratio(input)? else {
+            // Nothing deposited yet, so nothing to lean against
+            return Ok(orders);
+        };
+
+        let skew = self.skew(ratio);
+        let shift = -(skew * self.max_price_shift_bps as f64).round() as i64;
+        let size_skew = skew * self.max_size_skew_bps as f64 / 10_000.0;
+
+        let mut skewed = Vec::with_capacity(orders.len());
+        for order in orders {
+            let (rounding, factor) = match order.side {
+                OrderType::Buy if ratio >= self.max_base_ratio => continue,
+                OrderType::Sell if ratio <= self.min_base_ratio => continue,
+                OrderType::Buy => (Rounding::Down, 1.0 - size_skew),
+                OrderType::Sell => (Rounding::Up, 1.0 + size_skew),
+            };
+            let price = order.price.offset_bps(shift, rounding)?;
+            let factor_bps = (factor * 10_000.0).round().max(0.0) as u64;
+            let amount = order.amount.mul_div(factor_bps, 10_000, Rounding::Down)?;
+            if amount.is_zero() {
+                continue;
+            }
+            skewed.push(DesiredOrder {
+                side: order.side,
+                amount,
+                price,
+            });
+        }
+        Ok(skewed)
+    }
+}
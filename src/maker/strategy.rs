@@ -0,0 +1,104 @@
+use anyhow::{bail, Result};
+use spark_market_sdk::OrderType;
+
+use crate::{
+    amount::{Price, Quantity, Rounding},
+    market::MarketInfo,
+};
+
+/// What the maker holds in the market, liquid plus locked in its orders.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Inventory {
+    pub base: Quantity,
+    pub quote: Quantity,
+}
+
+/// Everything a strategy gets to decide its quotes.
+pub struct QuoteInput<'a> {
+    pub market: &'a MarketInfo,
+    pub fair_value: Price,
+    pub inventory: Inventory,
+}
+
+/// An order the strategy wants resting on the book.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DesiredOrder {
+    pub side: OrderType,
+    pub amount: Quantity,
+    pub price: Price,
+}
+
+impl DesiredOrder {
+    /// Quote this order locks (buys) or would receive (sells), rounded up.
+    pub fn quote_value(&self, market: &MarketInfo) -> Result<Quantity> {
+        market.quote_for(self.amount, self.price, Rounding::Up)
+    }
+}
+
+/// Decides which orders a `MarketMaker` should keep on the book. Strategies are
+/// pure: the engine fetches prices and balances and does all the calls.
+pub trait QuoteStrategy: Send + Sync {
+    fn quotes(&self, input: &QuoteInput) -> Result<Vec<DesiredOrder>>;
+}
+
+/// Symmetric levels around the fair value: the innermost bid and ask sit
+/// `spread_bps` away from it, each further level another `step_bps` out, and
+/// every order is worth `order_value` of the quote asset.
+#[derive(Debug, Clone)]
+pub struct BandStrategy {
+    pub levels: usize,
+    pub spread_bps: u64,
+    pub step_bps: u64,
+    /// Quote value of each order, in human units, e.g. `50.0` USDC.
+    pub order_value: f64,
+}
+
+impl BandStrategy {
+    pub fn new(levels: usize, spread_bps: u64, step_bps: u64, order_value: f64) -> Self {
+        Self {
+            levels,
+            spread_bps,
+            step_bps,
+            order_value,
+        }
+    }
+
+    /// How far the outermost level sits from the fair value.
+    pub fn band_bps(&self) -> u64 {
+        self.spread_bps + self.step_bps * self.levels.saturating_sub(1) as u64
+    }
+}
+
+impl QuoteStrategy for BandStrategy {
+    fn quotes(&self, input: &QuoteInput) -> Result<Vec<DesiredOrder>> {
+        if self.band_bps() >= 10_000 {
+            bail!(
+                "A band of {} bps would quote bids at or below zero",
+                self.band_bps()
+            );
+        }
+        let market = input.market;
+        let value = market.quote.from_f64(self.order_value, Rounding::Down)?;
+
+        let mut orders = Vec::with_capacity(self.levels * 2);
+        for level in 0..self.levels as u64 {
+            let offset = (self.spread_bps + level * self.step_bps) as i64;
+
+            // Round away from the fair value so the spread is never narrower than asked
+            let bid = input.fair_value.offset_bps(-offset, Rounding::Down)?;
+            let ask = input.fair_value.offset_bps(offset, Rounding::Up)?;
+
+            for (side, price) in [(OrderType::Buy, bid), (OrderType::Sell, ask)] {
+                let amount = market.base_for(value, price, Rounding::Down)?;
+                if !amount.is_zero() {
+                    orders.push(DesiredOrder {
+                        side,
+                        amount,
+                        price,
+                    });
+                }
+            }
+        }
+        Ok(orders)
+    }
+}
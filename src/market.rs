@@ -0,0 +1,175 @@
+use anyhow::{anyhow, bail, Result};
+use fuels::types::{AssetId, ContractId};
+use spark_market_sdk::{ProtocolFee, SparkMarketContract};
+use spark_registry_sdk::SparkRegistryContract;
+
+use crate::{
+    amount::{Price, Quantity, Rounding},
+    assets::{AssetCatalogue, AssetInfo},
+};
+
+/// A fully described market, read from the market contract itself rather than
+/// from whichever `*_ID` variables happen to be set next to its contract id.
+#[derive(Debug, Clone)]
+pub struct MarketInfo {
+    pub contract_id: ContractId,
+    pub base: AssetInfo,
+    pub quote: AssetInfo,
+    pub price_decimals: u32,
+    pub version: u32,
+    /// Proxy implementation, needed for raw `get_instance()` calls on upgradeable markets.
+    pub implementation: Option<ContractId>,
+    pub protocol_fee: Vec<ProtocolFee>,
+    pub matcher_fee: u64,
+    /// Smallest base amount an order may have, in base units.
+    pub min_order_size: u64,
+    /// Lowest price an order may have, in price units.
+    pub min_order_price: u64,
+    /// Whether the registry lists this contract for its asset pair.
+    pub registered: bool,
+}
+
+impl MarketInfo {
+    /// Reads assets, decimals, fees and order limits from the market's getters and checks the
+    /// registry. Assets found in `assets` keep their symbol; configured decimals
+    /// that disagree with the market are an error.
+    pub async fn load(
+        market: &SparkMarketContract,
+        contract_id: ContractId,
+        registry: &SparkRegistryContract,
+        assets: &AssetCatalogue,
+        implementation: Option<ContractId>,
+    ) -> Result<Self> {
+        let (
+            base_asset,
+            base_decimals,
+            quote_asset,
+            quote_decimals,
+            _owner,
+            price_decimals,
+            version,
+        ) = market.config().await?.value;
+
+        let base = describe_asset(assets, base_asset, base_decimals, contract_id)?;
+        let quote = describe_asset(assets, quote_asset, quote_decimals, contract_id)?;
+
+        let protocol_fee = market.protocol_fee().await?.value;
+        let matcher_fee = market.matcher_fee().await?.value;
+        let min_order_size = market.min_order_size().await?.value;
+        let min_order_price = market.min_order_price().await?.value;
+
+        let registered = registry
+            .markets(vec![(base_asset, quote_asset)])
+            .await?
+            .value
+            .first()
+            .and_then(|(_, _, id)| *id)
+            == Some(contract_id);
+
+        Ok(Self {
+            contract_id,
+            base,
+            quote,
+            price_decimals,
+            version,
+            implementation,
+            protocol_fee,
+            matcher_fee,
+            min_order_size,
+            min_order_price,
+            registered,
+        })
+    }
+
+    /// Name in config form, e.g. `ETH_USDC`.
+    pub fn name(&self) -> String {
+        format!("{}_{}", self.base.symbol, self.quote.symbol)
+    }
+
+    /// Wraps a price that is already scaled by the market's price decimals.
+    pub fn price(&self, raw: u64) -> Price {
+        Price::from_raw(raw, self.price_decimals)
+    }
+
+    /// A whole number price, e.g. `price_from_units(3_200)`.
+    pub fn price_from_units(&self, units: u64) -> Result<Price> {
+        Price::from_units(units, self.price_decimals)
+    }
+
+    /// Parses a human readable price such as `"3201.5"`, rounding down.
+    pub fn parse_price(&self, value: &str) -> Result<Price> {
+        Price::from_decimal_str(value, self.price_decimals, Rounding::Down)
+    }
+
+    pub fn price_from_f64(&self, value: f64, rounding: Rounding) -> Result<Price> {
+        Price::from_f64(value, self.price_decimals, rounding)
+    }
+
+    /// Quote amount for `base` at `price`, as the contract computes it.
+    pub fn quote_for(&self, base: Quantity, price: Price, rounding: Rounding) -> Result<Quantity> {
+        price.quote_for(base, self.quote.decimals, rounding)
+    }
+
+    /// Base amount that `quote` buys at `price`.
+    pub fn base_for(&self, quote: Quantity, price: Price, rounding: Rounding) -> Result<Quantity> {
+        price.base_for(quote, self.base.decimals, rounding)
+    }
+}
+
+fn describe_asset(
+    assets: &AssetCatalogue,
+    asset_id: AssetId,
+    decimals: u32,
+    contract_id: ContractId,
+) -> Result<AssetInfo> {
+    match assets.by_id(&asset_id) {
+        Some(info) if info.decimals != decimals => bail!(
+            "{} is configured with {} decimals but market {} uses {}",
+            info.symbol,
+            info.decimals,
+            contract_id,
+            decimals
+        ),
+        Some(info) => Ok(info.clone()),
+        None => Ok(AssetInfo::new(&asset_id.to_string(), asset_id, decimals)),
+    }
+}
+
+/// Every market the registry lists between two of `assets`, in either direction.
+pub async fn registered_markets(
+    registry: &SparkRegistryContract,
+    assets: &AssetCatalogue,
+) -> Result<Vec<ContractId>> {
+    // Ask the registry about every ordered pair at once
+    let assets: Vec<_> = assets.iter().collect();
+    let mut pairs = Vec::new();
+    for base in &assets {
+        for quote in &assets {
+            if base.asset_id != quote.asset_id {
+                pairs.push((base.asset_id, quote.asset_id));
+            }
+        }
+    }
+    Ok(registry
+        .markets(pairs)
+        .await?
+        .value
+        .into_iter()
+        .filter_map(|(_, _, id)| id)
+        .collect())
+}
+
+/// Looks up the market registered for a base/quote pair.
+pub async fn find_market(
+    registry: &SparkRegistryContract,
+    base: &AssetInfo,
+    quote: &AssetInfo,
+) -> Result<ContractId> {
+    registry
+        .markets(vec![(base.asset_id, quote.asset_id)])
+        .await?
+        .value
+        .first()
+        .and_then(|(_, _, id)| *id)
+        .ok_or_else(|| anyhow!("No {}/{} market in the registry", base.symbol, quote.symbol))
+}
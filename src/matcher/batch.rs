@@ -0,0 +1,297 @@
+use std::collections::HashMap;
+
+use anyhow::Result;
+use fuels::types::Bits256;
+use spark_market_sdk::OrderType;
+
+use crate::{
+    amount::Quantity,
+    batch::{BatchReport, BatchSubmitter, CallStatus, SubmitMode},
+    error::SparkError,
+    indexer::{decode_events, MarketEvent},
+    orderbook::{BookOrder, OrderBook},
+    simulate::Simulation,
+    utils::hex_str_from_bits256,
+    SparkContext,
+};
+
+/// What happened to one pair.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PairStatus {
+    /// Matched in this transaction, filling `amount` of base.
+    Matched { tx_id: String, amount: Quantity },
+    /// Left out before submission: an order was filled or cancelled, both are
+    /// on the same side or their prices do not cross.
+    Skipped { reason: SparkError },
+    /// Sent but reverted, or its transaction failed.
+    Failed { error: SparkError },
+    /// Never sent because the batch stopped first.
+    NotSent,
+}
+
+#[derive(Debug, Clone)]
+pub struct PairReport {
+    pub buy: Bits256,
+    pub sell: Bits256,
+    pub status: PairStatus,
+}
+
+impl PairReport {
+    /// Base amount the match filled, if it went through.
+    pub fn filled(&self) -> Option<Quantity> {
+        match self.status {
+            PairStatus::Matched { amount, .. } => Some(amount),
+            _ => None,
+        }
+    }
+
+    pub fn tx_id(&self) -> Option<&str> {
+        match &self.status {
+            PairStatus::Matched { tx_id, .. } => Some(tx_id),
+            _ => None,
+        }
+    }
+}
+
+/// One report per pushed pair, in push order, and the transactions that carried them.
+#[derive(Debug, Clone, Default)]
+pub struct BatchMatchReport {
+    pub pairs: Vec<PairReport>,
+    pub batch: BatchReport,
+    /// What each transaction would do, when the batch was simulated instead.
+    pub simulations: Vec<Simulation>,
+}
+
+impl BatchMatchReport {
+    pub fn matched(&self) -> usize {
+        self.pairs
+            .iter()
+            .filter(|pair| pair.filled().is_some())
+            .count()
+    }
+
+    pub fn skipped(&self) -> usize {
+        self.pairs
+            .iter()
+            .filter(|pair| matches!(pair.status, PairStatus::Skipped { .. }))
+            .count()
+    }
+
+    /// Base filled by every match of the batch.
+    pub fn filled(&self, base_decimals: u32) -> Result<Quantity> {
+        self.pairs
+            .iter()
+            .filter_map(PairReport::filled)
+            .try_fold(Quantity::zero(base_decimals), |total, amount| {
+                total.checked_add(amount)
+            })
+    }
+}
+
+/// Matches many buy/sell pairs in as few multicall transactions as the chain
+/// allows, chunked by a `BatchSubmitter`:
+///
+/// ```ignore
+/// let mut batch = BatchMatcher::new(&ctx);
+/// for (buy, sell) in pairs {
+///     batch.push(buy, sell);
+/// }
+/// let report = batch.submit().await?;
+/// ```
+///
+/// Every order is read back from the market first, so pairs with an order
+/// that is already filled or cancelled, or that an earlier pair of the batch
+/// fills, are skipped instead of reverting. The fill of each match is taken
+/// from the `TradeOrderEvent` it logged.
+pub struct BatchMatcher<'a> {
+    ctx: &'a SparkContext,
+    pairs: Vec<(Bits256, Bits256)>,
+    mode: SubmitMode,
+    max_calls: Option<usize>,
+}
+
+impl<'a> BatchMatcher<'a> {
+    pub fn new(ctx: &'a SparkContext) -> Self {
+        Self {
+            ctx,
+            pairs: Vec::new(),
+            mode: SubmitMode::default(),
+            max_calls: None,
+        }
+    }
+
+    pub fn mode(mut self, mode: SubmitMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Caps the matches per transaction on top of the gas and size limits.
+    pub fn max_calls(mut self, max_calls: usize) -> Self {
+        self.max_calls = Some(max_calls);
+        self
+    }
+
+    /// Adds a pair; the two orders may be given in either order.
+    pub fn push(&mut self, order: Bits256, other: Bits256) {
+        self.pairs.push((order, other));
+    }
+
+    pub fn len(&self) -> usize {
+        self.pairs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pairs.is_empty()
+    }
+
+    /// Checks every pair against the book and matches the ones that still cross.
+    pub async fn submit(self) -> Result<BatchMatchReport> {
+        let ctx = self.ctx;
+        let (submitter, mut pairs, sent) = self.prepare().await?;
+        if submitter.is_empty() {
+            return Ok(BatchMatchReport {
+                pairs,
+                ..Default::default()
+            });
+        }
+        let batch = submitter.submit().await?;
+        for (call, index) in sent.into_iter().enumerate() {
+            let pair = &mut pairs[index];
+            pair.status = match &batch.calls[call] {
+                CallStatus::Included { tx_id } => PairStatus::Matched {
+                    tx_id: tx_id.clone(),
+                    amount: filled(ctx, &batch, call, pair)?,
+                },
+                CallStatus::Failed { error } => PairStatus::Failed {
+                    error: error.clone(),
+                },
+                CallStatus::NotSent => PairStatus::NotSent,
+            };
+        }
+        Ok(BatchMatchReport {
+            pairs,
+            batch,
+            simulations: Vec::new(),
+        })
+    }
+
+    /// Checks every pair like `submit` but only dry-runs the matches. The pairs
+    /// that would be sent are left `NotSent`.
+    pub async fn simulate(self) -> Result<BatchMatchReport> {
+        let (submitter, pairs, _) = self.prepare().await?;
+        Ok(BatchMatchReport {
+            pairs,
+            simulations: submitter.simulate().await?,
+            ..Default::default()
+        })
+    }
+
+    /// Skips the pairs that no longer cross and queues a match for the others.
+    /// Returns the queued calls, a report per pair and the pair of each call.
+    async fn prepare(self) -> Result<(BatchSubmitter<'a>, Vec<PairReport>, Vec<usize>)> {
+        let ctx = self.ctx;
+        let book = OrderBook::builder(&ctx.market, &ctx.market_info)
+            .order_ids(self.pairs.iter().flat_map(|(a, b)| [*a, *b]))
+            .build()
+            .await?;
+        let mut remaining: HashMap<Bits256, &BookOrder> =
+            book.orders.iter().map(|order| (order.id, order)).collect();
+        let mut left: HashMap<Bits256, Quantity> = book
+            .orders
+            .iter()
+            .map(|order| (order.id, order.amount))
+            .collect();
+
+        let mut submitter = BatchSubmitter::new(ctx).mode(self.mode);
+        if let Some(max_calls) = self.max_calls {
+            submitter = submitter.max_calls(max_calls);
+        }
+        let mut pairs = Vec::new();
+        // Pair index of every pushed call
+        let mut sent = Vec::new();
+        for (a, b) in self.pairs {
+            let (buy, sell, status) = match buy_and_sell(&remaining, a, b) {
+                Ok((buy, sell)) => {
+                    let amount = left[&buy.id].min(left[&sell.id]);
+                    for id in [buy.id, sell.id] {
+                        let rest = left[&id].raw() - amount.raw();
+                        left.insert(id, Quantity::from_raw(rest, amount.decimals()));
+                    }
+                    submitter.push(
+                        ctx.market
+                            .get_instance()
+                            .methods()
+                            .match_order_pair(buy.id, sell.id)
+                            .with_contract_ids(&ctx.contract_ids()),
+                    );
+                    sent.push(pairs.len());
+                    (buy.id, sell.id, PairStatus::NotSent)
+                }
+                Err(reason) => (a, b, PairStatus::Skipped { reason }),
+            };
+            // An order left with nothing is filled as far as later pairs go
+            remaining.retain(|id, _| !left[id].is_zero());
+            pairs.push(PairReport { buy, sell, status });
+        }
+        Ok((submitter, pairs, sent))
+    }
+}
+
+/// The buy and the sell of a pair, if both are open and they cross.
+fn buy_and_sell<'b>(
+    open: &HashMap<Bits256, &'b BookOrder>,
+    a: Bits256,
+    b: Bits256,
+) -> Result<(&'b BookOrder, &'b BookOrder), SparkError> {
+    let find = |id: Bits256| {
+        open.get(&id).copied().ok_or_else(|| {
+            SparkError::OrderNotFound(format!(
+                "{} is filled or cancelled",
+                hex_str_from_bits256(&id)
+            ))
+        })
+    };
+    let (a, b) = (find(a)?, find(b)?);
+    let (buy, sell) = match (a.side, b.side) {
+        (OrderType::Buy, OrderType::Sell) => (a, b),
+        (OrderType::Sell, OrderType::Buy) => (b, a),
+        _ => {
+            return Err(SparkError::NotMatchable(
+                "both orders are on the same side".to_string(),
+            ))
+        }
+    };
+    if buy.price < sell.price {
+        return Err(SparkError::NotMatchable(format!(
+            "bid {} is below ask {}",
+            buy.price, sell.price
+        )));
+    }
+    Ok((buy, sell))
+}
+
+/// Base filled by the match pushed as `call`, from the trades its transaction logged.
+fn filled(
+    ctx: &SparkContext,
+    batch: &BatchReport,
+    call: usize,
+    pair: &PairReport,
+) -> Result<Quantity> {
+    let base = &ctx.market_info.base;
+    let Some(chunk) = batch.chunk_of(call) else {
+        return Ok(Quantity::zero(base.decimals));
+    };
+    let decoder = ctx.market.get_instance().log_decoder();
+    let size = decode_events(&decoder, ctx.market_info.contract_id, &chunk.receipts)?
+        .into_iter()
+        .filter_map(|(_, event)| match event {
+            MarketEvent::Trade(trade)
+                if trade.base_buy_order_id == pair.buy && trade.base_sell_order_id == pair.sell =>
+            {
+                Some(trade.trade_size)
+            }
+            _ => None,
+        })
+        .sum();
+    Ok(base.quantity(size))
+}
@@ -0,0 +1,84 @@
+use std::cmp::Reverse;
+
+use fuels::types::Bits256;
+use spark_market_sdk::OrderType;
+
+use crate::{
+    amount::{Price, Quantity},
+    orderbook::BookOrder,
+};
+
+/// A buy and a sell whose prices cross, and how much a match between them fills.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Crossing {
+    pub buy: Bits256,
+    pub sell: Bits256,
+    pub buy_price: Price,
+    pub sell_price: Price,
+    /// Base amount the match fills.
+    pub amount: Quantity,
+    /// What was left of each order before this fill.
+    pub buy_remaining: Quantity,
+    pub sell_remaining: Quantity,
+}
+
+/// Pairs crossing orders by price-time priority: the highest bid with the
+/// lowest ask, and among orders at the same price the one placed first.
+/// `placed` gives an order's place in time, lower first; orders it does not
+/// know go after the ones it does. An order partly filled by one crossing is
+/// paired again with the next ask or bid.
+pub fn crossings(orders: &[BookOrder], placed: impl Fn(&Bits256) -> Option<u64>) -> Vec<Crossing> {
+    let time = |order: &BookOrder| placed(&order.id).unwrap_or(u64::MAX);
+    let mut bids: Vec<_> = orders
+        .iter()
+        .filter(|order| order.side == OrderType::Buy && !order.amount.is_zero())
+        .collect();
+    let mut asks: Vec<_> = orders
+        .iter()
+        .filter(|order| order.side == OrderType::Sell && !order.amount.is_zero())
+        .collect();
+    bids.sort_by_key(|order| (Reverse(order.price), time(order)));
+    asks.sort_by_key(|order| (order.price, time(order)));
+
+    let mut result = Vec::new();
+    let (mut b, mut a) = (0, 0);
+    let (mut bid_left, mut ask_left) = (
+        bids.first().map(|order| order.amount),
+        asks.first().map(|order| order.amount),
+    );
+    while let (Some(bid), Some(ask), Some(bid_amount), Some(ask_amount)) =
+        (bids.get(b), asks.get(a), bid_left, ask_left)
+    {
+        if bid.price < ask.price {
+            break;
+        }
+        let amount = bid_amount.min(ask_amount);
+        result.push(Crossing {
+            buy: bid.id,
+            sell: ask.id,
+            buy_price: bid.price,
+            sell_price: ask.price,
+            amount,
+            buy_remaining: bid_amount,
+            sell_remaining: ask_amount,
+        });
+
+        bid_left = Some(Quantity::from_raw(
+            bid_amount.raw() - amount.raw(),
+            amount.decimals(),
+        ));
+        ask_left = Some(Quantity::from_raw(
+            ask_amount.raw() - amount.raw(),
+            amount.decimals(),
+        ));
+        if bid_left.is_some_and(|left| left.is_zero()) {
+            b += 1;
+            bid_left = bids.get(b).map(|order| order.amount);
+        }
+        if ask_left.is_some_and(|left| left.is_zero()) {
+            a += 1;
+            ask_left = asks.get(a).map(|order| order.amount);
+        }
+    }
+    result
+}
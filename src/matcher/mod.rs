@@ -0,0 +1,303 @@
+//! An off-chain matching engine that keeps crossing a market's book.
+//!
+//! Each cycle `Matcher` follows the market's `OpenOrderEvent`s through an
+//! `Indexer`, reads every order it knows of back from the market (dropping the
+//! filled and cancelled ones), pairs crossing buys and sells by price-time
+//! priority and matches the pairs worth it: the matcher fee the orders pay has
+//! to cover the gas of the match, priced through a `PriceSource` for the gas
+//! token. The pairs of a cycle go out together through a `BatchMatcher`, as
+//! few multicalls as the chain allows. With `MatcherConfig::dry_run` they are
+//! simulated and reported instead.
+
+mod batch;
+mod crossing;
+
+use std::{
+    collections::{hash_map::Entry, HashMap},
+    time::Duration,
+};
+
+use anyhow::Result;
+use fuels::types::Bits256;
+use tokio::time::sleep;
+
+pub use batch::{BatchMatchReport, BatchMatcher, PairReport, PairStatus};
+pub use crossing::{crossings, Crossing};
+
+use crate::{
+    amount::{Quantity, Rounding},
+    error::SparkError,
+    indexer::Indexer,
+    oracle::PriceSource,
+    orderbook::OrderBook,
+    simulate::Simulation,
+    utils::{bits256_from_hex_str, hex_str_from_bits256},
+    SparkContext,
+};
+
+#[derive(Debug, Clone)]
+pub struct MatcherConfig {
+    /// Pause between cycles.
+    pub interval: Duration,
+    /// Block to start following the market from on the first cycle.
+    pub from_height: u32,
+    /// The matcher fee has to be at least this many times the gas cost.
+    pub min_profit_ratio: f64,
+    /// Most matches submitted per cycle; the rest wait for the next one.
+    pub max_matches: usize,
+    /// Simulate each cycle's matches instead of sending them.
+    pub dry_run: bool,
+}
+
+impl Default for MatcherConfig {
+    fn default() -> Self {
+        Self {
+            interval: Duration::from_secs(5),
+            from_height: 0,
+            min_profit_ratio: 1.0,
+            max_matches: 20,
+            dry_run: false,
+        }
+    }
+}
+
+/// A crossing that was matched.
+#[derive(Debug, Clone)]
+pub struct Matched {
+    pub crossing: Crossing,
+    pub tx_id: String,
+    /// Base amount the match actually filled.
+    pub filled: Quantity,
+    /// Matcher fee expected for it, in quote.
+    pub reward: Quantity,
+    /// Gas it was estimated to cost, in quote.
+    pub gas_cost: Quantity,
+}
+
+/// What one cycle did.
+#[derive(Debug, Clone, Default)]
+pub struct MatchReport {
+    /// Open orders known after the cycle.
+    pub orders: usize,
+    pub crossings: usize,
+    pub matched: Vec<Matched>,
+    /// Crossings left alone because the fee would not cover the gas.
+    pub unprofitable: usize,
+    /// Matches that failed, with the reason.
+    pub failed: Vec<String>,
+    /// In a dry run, what each transaction of matches would do.
+    pub simulations: Vec<Simulation>,
+}
+
+impl MatchReport {
+    /// Matcher fees expected from this cycle's matches.
+    pub fn earned(&self, quote_decimals: u32) -> Result<Quantity> {
+        self.matched
+            .iter()
+            .try_fold(Quantity::zero(quote_decimals), |total, matched| {
+                total.checked_add(matched.reward)
+            })
+    }
+}
+
+pub struct Matcher<'a> {
+    ctx: &'a SparkContext,
+    indexer: Indexer,
+    gas_price: Box<dyn PriceSource>,
+    config: MatcherConfig,
+    /// Every order seen opening and not yet found closed, with its place in time.
+    known: HashMap<Bits256, u64>,
+    next_seq: u64,
+    next_height: u32,
+}
+
+impl<'a> Matcher<'a> {
+    /// `indexer` follows the market being matched; `gas_price` is the price of
+    /// the chain's gas token in the market's quote asset.
+    pub fn new(
+        ctx: &'a SparkContext,
+        indexer: Indexer,
+        gas_price: impl PriceSource + 'static,
+    ) -> Self {
+        Self {
+            ctx,
+            indexer,
+            gas_price: Box::new(gas_price),
+            config: MatcherConfig::default(),
+            known: HashMap::new(),
+            next_seq: 0,
+            next_height: 0,
+        }
+    }
+
+    pub fn with_config(mut self, config: MatcherConfig) -> Self {
+        self.next_height = config.from_height;
+        self.config = config;
+        self
+    }
+
+    /// Runs cycles forever. A failed cycle is reported and retried after the
+    /// usual interval rather than ending the loop.
+    pub async fn run(&mut self) -> Result<()> {
+        let quote_decimals = self.ctx.market_info.quote.decimals;
+        loop {
+            match self.cycle().await {
+                Ok(report) => {
+                    println!(
+                        "{} orders, {} crossings: matched {}, unprofitable {}, earned {}",
+                        report.orders,
+                        report.crossings,
+                        report.matched.len(),
+                        report.unprofitable,
+                        report.earned(quote_decimals)?
+                    );
+                    for failure in &report.failed {
+                        println!("  {}", failure);
+                    }
+                    for (tx, simulation) in report.simulations.iter().enumerate() {
+                        println!("  Dry run of transaction {}: {}", tx, simulation);
+                    }
+                }
+                Err(e) => println!("Matcher cycle failed: {}", SparkError::from(&e)),
+            }
+            sleep(self.config.interval).await;
+        }
+    }
+
+    /// One full pass: follow new orders, rebuild the book, match what crosses.
+    pub async fn cycle(&mut self) -> Result<MatchReport> {
+        self.follow().await?;
+        let ctx = self.ctx;
+        let info = &ctx.market_info;
+
+        let book = OrderBook::builder(&ctx.market, info)
+            .order_ids(self.known.keys().copied())
+            .build()
+            .await?;
+        self.known
+            .retain(|id, _| book.orders.iter().any(|order| order.id == *id));
+
+        let found = crossings(&book.orders, |id| self.known.get(id).copied());
+        let mut report = MatchReport {
+            orders: book.orders.len(),
+            crossings: found.len(),
+            ..Default::default()
+        };
+        if found.is_empty() {
+            return Ok(report);
+        }
+
+        let gas_price = self.gas_price.price().await?.price;
+        let mut batch = BatchMatcher::new(ctx);
+        let mut sent = Vec::new();
+        for crossing in found {
+            if sent.len() >= self.config.max_matches {
+                break;
+            }
+            let reward = self.reward(&crossing)?;
+            let gas_cost = match self.gas_cost(&crossing, gas_price).await {
+                Ok(cost) => cost,
+                Err(e) => {
+                    report
+                        .failed
+                        .push(describe(&crossing, SparkError::from(&e)));
+                    continue;
+                }
+            };
+            if (reward.raw() as f64) < gas_cost.raw() as f64 * self.config.min_profit_ratio {
+                report.unprofitable += 1;
+                continue;
+            }
+
+            batch.push(crossing.buy, crossing.sell);
+            sent.push((crossing, reward, gas_cost));
+        }
+        if batch.is_empty() {
+            return Ok(report);
+        }
+
+        let matches = if self.config.dry_run {
+            batch.simulate().await?
+        } else {
+            batch.submit().await?
+        };
+        report.simulations = matches.simulations;
+        for ((crossing, reward, gas_cost), pair) in sent.into_iter().zip(matches.pairs) {
+            match pair.status {
+                PairStatus::Matched { tx_id, amount } => report.matched.push(Matched {
+                    crossing,
+                    tx_id,
+                    filled: amount,
+                    reward,
+                    gas_cost,
+                }),
+                PairStatus::Skipped { reason: error } | PairStatus::Failed { error } => {
+                    report.failed.push(describe(&crossing, error))
+                }
+                PairStatus::NotSent => {}
+            }
+        }
+        Ok(report)
+    }
+
+    /// Indexes new blocks and adds the orders opened in them, oldest first.
+    async fn follow(&mut self) -> Result<()> {
+        self.indexer.sync(self.config.from_height).await?;
+        for record in self.indexer.store().opened(None, self.next_height)? {
+            let id = bits256_from_hex_str(&record.order_id)?;
+            if let Entry::Vacant(entry) = self.known.entry(id) {
+                entry.insert(self.next_seq);
+                self.next_seq += 1;
+            }
+            self.next_height = self.next_height.max(record.block_height);
+        }
+        Ok(())
+    }
+
+    /// The matcher fee both orders pay for their filled part.
+    fn reward(&self, crossing: &Crossing) -> Result<Quantity> {
+        let info = &self.ctx.market_info;
+        let fee = info.quote.quantity(info.matcher_fee);
+        let from_buy = fee.mul_div(
+            crossing.amount.raw(),
+            crossing.buy_remaining.raw(),
+            Rounding::Down,
+        )?;
+        let from_sell = fee.mul_div(
+            crossing.amount.raw(),
+            crossing.sell_remaining.raw(),
+            Rounding::Down,
+        )?;
+        from_buy.checked_add(from_sell)
+    }
+
+    /// Estimated fee of the match transaction, converted into quote.
+    async fn gas_cost(&self, crossing: &Crossing, gas_price: f64) -> Result<Quantity> {
+        let ctx = self.ctx;
+        let cost = ctx
+            .market
+            .get_instance()
+            .methods()
+            .match_order_pair(crossing.buy, crossing.sell)
+            .with_contract_ids(&ctx.contract_ids())
+            .estimate_transaction_cost(None, None)
+            .await?;
+
+        let decimals = ctx.gas_token_decimals();
+        let fee = cost.total_fee as f64 / 10f64.powi(decimals as i32);
+        Quantity::from_f64(
+            fee * gas_price,
+            ctx.market_info.quote.decimals,
+            Rounding::Up,
+        )
+    }
+}
+
+fn describe(crossing: &Crossing, error: SparkError) -> String {
+    format!(
+        "Match {} with {} failed: {}",
+        hex_str_from_bits256(&crossing.buy),
+        hex_str_from_bits256(&crossing.sell),
+        error
+    )
+}
@@ -0,0 +1,526 @@
+//! Reference prices from outside the market.
+//!
+//! A `PriceSource` gives the price of one pair. `Oracle` asks several sources at
+//! once, drops the ones that fail, are stale or sit too far from the others, and
+//! returns the median of the rest, so losing one API is a non-event.
+//!
+//! Every HTTP source takes a `with_base_url` so that tests can point it at a
+//! local mock server.
+
+use std::{
+    fmt, fs,
+    path::PathBuf,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::{anyhow, bail, Context, Result};
+use async_trait::async_trait;
+use futures::future::join_all;
+use serde_json::Value;
+
+pub const COINGECKO_URL: &str = "https://api.coingecko.com";
+pub const BINANCE_URL: &str = "https://api.binance.com";
+pub const PYTH_URL: &str = "https://hermes.pyth.network";
+
+/// Requests that take longer count as a failed source.
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(5);
+/// Prices published longer ago than this are ignored.
+pub const DEFAULT_MAX_AGE: Duration = Duration::from_secs(120);
+/// Prices further than this from the median are ignored (2%).
+pub const DEFAULT_MAX_DEVIATION_BPS: u64 = 200;
+
+/// One price as reported by one source.
+#[derive(Debug, Clone)]
+pub struct PricePoint {
+    pub source: String,
+    pub price: f64,
+    /// When the source says the price was published, or when it was fetched if it does not say.
+    pub published_at: SystemTime,
+}
+
+impl PricePoint {
+    pub fn new(source: &str, price: f64, published_at: SystemTime) -> Self {
+        Self {
+            source: source.to_string(),
+            price,
+            published_at,
+        }
+    }
+
+    pub fn age(&self) -> Duration {
+        SystemTime::now()
+            .duration_since(self.published_at)
+            .unwrap_or_default()
+    }
+}
+
+#[async_trait]
+pub trait PriceSource: Send + Sync {
+    fn name(&self) -> &str;
+
+    async fn price(&self) -> Result<PricePoint>;
+}
+
+#[async_trait]
+impl<T: PriceSource + ?Sized> PriceSource for Box<T> {
+    fn name(&self) -> &str {
+        (**self).name()
+    }
+
+    async fn price(&self) -> Result<PricePoint> {
+        (**self).price().await
+    }
+}
+
+fn http_client() -> reqwest::Client {
+    reqwest::Client::builder()
+        .timeout(DEFAULT_TIMEOUT)
+        .build()
+        .expect("default reqwest client")
+}
+
+async fn get_json(client: &reqwest::Client, url: &str) -> Result<Value> {
+    let response = client
+        .get(url)
+        .send()
+        .await
+        .with_context(|| format!("Request to {} failed", url))?;
+    let status = response.status();
+    if !status.is_success() {
+        bail!("{} returned {}", url, status);
+    }
+    Ok(response.json().await?)
+}
+
+fn unix_time(secs: u64) -> SystemTime {
+    UNIX_EPOCH + Duration::from_secs(secs)
+}
+
+/// CoinGecko's `simple/price`, e.g. `CoinGecko::new("ethereum", "usd")`.
+pub struct CoinGecko {
+    id: String,
+    vs_currency: String,
+    base_url: String,
+    client: reqwest::Client,
+}
+
+impl CoinGecko {
+    pub fn new(id: &str, vs_currency: &str) -> Self {
+        Self {
+            id: id.to_string(),
+            vs_currency: vs_currency.to_string(),
+            base_url: COINGECKO_URL.to_string(),
+            client: http_client(),
+        }
+    }
+
+    pub fn with_base_url(mut self, base_url: &str) -> Self {
+        self.base_url = base_url.trim_end_matches('/').to_string();
+        self
+    }
+}
+
+#[async_trait]
+impl PriceSource for CoinGecko {
+    fn name(&self) -> &str {
+        "coingecko"
+    }
+
+    async fn price(&self) -> Result<PricePoint> {
+        let url = format!(
+            "{}/api/v3/simple/price?ids={}&vs_currencies={}&include_last_updated_at=true",
+            self.base_url, self.id, self.vs_currency
+        );
+        let response = get_json(&self.client, &url).await?;
+        let entry = &response[&self.id];
+        let price = entry[&self.vs_currency].as_f64().ok_or_else(|| {
+            anyhow!(
+                "No {} price for {} in {}",
+                self.vs_currency,
+                self.id,
+                response
+            )
+        })?;
+        let published_at = entry["last_updated_at"]
+            .as_u64()
+            .map(unix_time)
+            .unwrap_or_else(SystemTime::now);
+        Ok(PricePoint::new(self.name(), price, published_at))
+    }
+}
+
+/// Binance's spot `ticker/price`, e.g. `Binance::new("ETHUSDT")`. The endpoint has
+/// no timestamp, so prices count as published when fetched.
+pub struct Binance {
+    symbol: String,
+    base_url: String,
+    client: reqwest::Client,
+}
+
+impl Binance {
+    pub fn new(symbol: &str) -> Self {
+        Self {
+            symbol: symbol.to_ascii_uppercase(),
+            base_url: BINANCE_URL.to_string(),
+            client: http_client(),
+        }
+    }
+
+    pub fn with_base_url(mut self, base_url: &str) -> Self {
+        self.base_url = base_url.trim_end_matches('/').to_string();
+        self
+    }
+}
+
+#[async_trait]
+impl PriceSource for Binance {
+    fn name(&self) -> &str {
+        "binance"
+    }
+
+    async fn price(&self) -> Result<PricePoint> {
+        let url = format!(
+            "{}/api/v3/ticker/price?symbol={}",
+            self.base_url, self.symbol
+        );
+        let response = get_json(&self.client, &url).await?;
+        // Binance sends prices as strings to keep their precision
+        let price = response["price"]
+            .as_str()
+            .and_then(|price| price.parse::<f64>().ok())
+            .ok_or_else(|| anyhow!("No {} price in {}", self.symbol, response))?;
+        Ok(PricePoint::new(self.name(), price, SystemTime::now()))
+    }
+}
+
+/// A Pyth price feed read from a Hermes endpoint, e.g. the ETH/USD feed id.
+pub struct Pyth {
+    feed_id: String,
+    base_url: String,
+    client: reqwest::Client,
+}
+
+impl Pyth {
+    pub fn new(feed_id: &str) -> Self {
+        Self {
+            feed_id: feed_id.trim_start_matches("0x").to_ascii_lowercase(),
+            base_url: PYTH_URL.to_string(),
+            client: http_client(),
+        }
+    }
+
+    pub fn with_base_url(mut self, base_url: &str) -> Self {
+        self.base_url = base_url.trim_end_matches('/').to_string();
+        self
+    }
+}
+
+#[async_trait]
+impl PriceSource for Pyth {
+    fn name(&self) -> &str {
+        "pyth"
+    }
+
+    async fn price(&self) -> Result<PricePoint> {
+        let url = format!(
+            "{}/v2/updates/price/latest?ids[]={}",
+            self.base_url, self.feed_id
+        );
+        let response = get_json(&self.client, &url).await?;
+        let feed = response["parsed"]
+            .as_array()
+            .and_then(|feeds| {
+                feeds.iter().find(|feed| {
+                    feed["id"].as_str().map(|id| id.trim_start_matches("0x"))
+                        == Some(self.feed_id.as_str())
+                })
+            })
+            .ok_or_else(|| anyhow!("Feed {} missing from Pyth response", self.feed_id))?;
+
+        // The price is an integer string scaled by 10^expo
+        let price = &feed["price"];
+        let mantissa = price["price"]
+            .as_str()
+            .and_then(|price| price.parse::<i64>().ok())
+            .ok_or_else(|| anyhow!("Malformed Pyth price {}", price))?;
+        let expo = price["expo"]
+            .as_i64()
+            .ok_or_else(|| anyhow!("Malformed Pyth exponent {}", price))?;
+        let published_at = price["publish_time"]
+            .as_u64()
+            .map(unix_time)
+            .ok_or_else(|| anyhow!("Pyth price {} has no publish time", price))?;
+
+        Ok(PricePoint::new(
+            self.name(),
+            mantissa as f64 * 10f64.powi(expo as i32),
+            published_at,
+        ))
+    }
+}
+
+/// A fixed price, for tests and for pegged assets.
+pub struct StaticPrice {
+    name: String,
+    price: f64,
+}
+
+impl StaticPrice {
+    pub fn new(name: &str, price: f64) -> Self {
+        Self {
+            name: name.to_string(),
+            price,
+        }
+    }
+}
+
+#[async_trait]
+impl PriceSource for StaticPrice {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn price(&self) -> Result<PricePoint> {
+        Ok(PricePoint::new(&self.name, self.price, SystemTime::now()))
+    }
+}
+
+/// A price written to a file by some other process, as a plain number. The
+/// file's modification time is the publish time, so a stuck writer shows up
+/// as a stale price.
+pub struct FilePrice {
+    name: String,
+    path: PathBuf,
+}
+
+impl FilePrice {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        Self {
+            name: format!("file:{}", path.display()),
+            path,
+        }
+    }
+}
+
+#[async_trait]
+impl PriceSource for FilePrice {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn price(&self) -> Result<PricePoint> {
+        let contents = fs::read_to_string(&self.path)
+            .with_context(|| format!("Cannot read price file {}", self.path.display()))?;
+        let price = contents.trim().parse::<f64>().map_err(|e| {
+            anyhow!(
+                "Invalid price {:?} in {}: {}",
+                contents.trim(),
+                self.path.display(),
+                e
+            )
+        })?;
+        let published_at = fs::metadata(&self.path)?.modified()?;
+        Ok(PricePoint::new(&self.name, price, published_at))
+    }
+}
+
+/// A source left out of a consensus price, and why.
+#[derive(Debug, Clone)]
+pub struct Rejected {
+    pub source: String,
+    pub reason: String,
+}
+
+/// The agreed price and the points it was computed from.
+#[derive(Debug, Clone)]
+pub struct Consensus {
+    pub price: f64,
+    pub used: Vec<PricePoint>,
+    pub rejected: Vec<Rejected>,
+}
+
+impl fmt::Display for Consensus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let used: Vec<_> = self.used.iter().map(|p| p.source.as_str()).collect();
+        write!(f, "{:.6} from {}", self.price, used.join(", "))?;
+        for rejected in &self.rejected {
+            write!(f, "; {} rejected: {}", rejected.source, rejected.reason)?;
+        }
+        Ok(())
+    }
+}
+
+/// Median of several sources with staleness and outlier checks.
+pub struct Oracle {
+    sources: Vec<Box<dyn PriceSource>>,
+    max_age: Duration,
+    max_deviation_bps: u64,
+    min_sources: usize,
+}
+
+impl Oracle {
+    pub fn new() -> Self {
+        Self {
+            sources: Vec::new(),
+            max_age: DEFAULT_MAX_AGE,
+            max_deviation_bps: DEFAULT_MAX_DEVIATION_BPS,
+            min_sources: 1,
+        }
+    }
+
+    /// CoinGecko, Binance and Pyth for the USD price of a well known asset.
+    pub fn usd(symbol: &str) -> Result<Self> {
+        let (coingecko, binance, pyth) = match symbol.to_ascii_uppercase().as_str() {
+            "BTC" => (
+                "bitcoin",
+                "BTCUSDT",
+                "e62df6c8b4a85fe1a67db44dc12de5db330f7ac66b72dc658afedf0f4a415b43",
+            ),
+            "ETH" => (
+                "ethereum",
+                "ETHUSDT",
+                "ff61491a931112ddf1bd8147cd1b641375f79f5825126d665480874634fd0ace",
+            ),
+            other => bail!("No default price sources for {}", other),
+        };
+        Ok(Self::new()
+            .source(CoinGecko::new(coingecko, "usd"))
+            .source(Binance::new(binance))
+            .source(Pyth::new(pyth)))
+    }
+
+    pub fn source(mut self, source: impl PriceSource + 'static) -> Self {
+        self.sources.push(Box::new(source));
+        self
+    }
+
+    pub fn max_age(mut self, max_age: Duration) -> Self {
+        self.max_age = max_age;
+        self
+    }
+
+    pub fn max_deviation_bps(mut self, bps: u64) -> Self {
+        self.max_deviation_bps = bps;
+        self
+    }
+
+    /// Sources that must agree for a price to be returned. One by default, so
+    /// any single healthy source is enough.
+    pub fn min_sources(mut self, count: usize) -> Self {
+        self.min_sources = count.max(1);
+        self
+    }
+
+    /// Asks every source at once and returns the median of the usable answers.
+    /// When only two answer and they disagree, the one added first is used.
+    pub async fn consensus(&self) -> Result<Consensus> {
+        let answers = join_all(self.sources.iter().map(|source| source.price())).await;
+
+        let mut points = Vec::new();
+        let mut rejected = Vec::new();
+        for (source, answer) in self.sources.iter().zip(answers) {
+            let reason = match answer {
+                Err(e) => e.to_string(),
+                Ok(point) if !point.price.is_finite() || point.price <= 0.0 => {
+                    format!("invalid price {}", point.price)
+                }
+                Ok(point) if point.age() > self.max_age => {
+                    format!("stale by {}s", point.age().as_secs())
+                }
+                Ok(point) => {
+                    points.push(point);
+                    continue;
+                }
+            };
+            rejected.push(Rejected {
+                source: source.name().to_string(),
+                reason,
+            });
+        }
+
+        let max_deviation = self.max_deviation_bps as f64 / 10_000.0;
+        let deviates = |price: f64, center: f64| (price - center).abs() / center > max_deviation;
+        if points.len() >= 3 {
+            // Outliers are judged against the median of everything that answered
+            let center = median(&points).expect("at least three points");
+            let (outliers, kept): (Vec<_>, Vec<_>) =
+                points.into_iter().partition(|p| deviates(p.price, center));
+            points = kept;
+            rejected.extend(outliers.into_iter().map(|p| Rejected {
+                reason: format!(
+                    "{:.6} is more than {} bps from the median {:.6}",
+                    p.price, self.max_deviation_bps, center
+                ),
+                source: p.source,
+            }));
+        } else if points.len() == 2 && deviates(points[1].price, points[0].price) {
+            // Two prices have no median to tell which is wrong, so the source
+            // added first wins
+            let second = points.pop().expect("two points");
+            rejected.push(Rejected {
+                reason: format!(
+                    "{:.6} is more than {} bps from {} at {:.6}",
+                    second.price, self.max_deviation_bps, points[0].source, points[0].price
+                ),
+                source: second.source,
+            });
+        }
+
+        if points.len() < self.min_sources {
+            let reasons: Vec<_> = rejected
+                .iter()
+                .map(|r| format!("{}: {}", r.source, r.reason))
+                .collect();
+            bail!(
+                "Only {} of {} price sources usable, {} required ({})",
+                points.len(),
+                self.sources.len(),
+                self.min_sources,
+                reasons.join("; ")
+            );
+        }
+
+        Ok(Consensus {
+            price: median(&points).expect("at least one point"),
+            used: points,
+            rejected,
+        })
+    }
+}
+
+impl Default for Oracle {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// An oracle is itself a source, so consensus prices can be nested.
+#[async_trait]
+impl PriceSource for Oracle {
+    fn name(&self) -> &str {
+        "oracle"
+    }
+
+    async fn price(&self) -> Result<PricePoint> {
+        let consensus = self.consensus().await?;
+        let published_at = consensus
+            .used
+            .iter()
+            .map(|p| p.published_at)
+            .min()
+            .unwrap_or_else(SystemTime::now);
+        Ok(PricePoint::new(self.name(), consensus.price, published_at))
+    }
+}
+
+fn median(points: &[PricePoint]) -> Option<f64> {
+    let mut prices: Vec<f64> = points.iter().map(|p| p.price).collect();
+    prices.sort_by(|a, b| a.total_cmp(b));
+    let mid = prices.len() / 2;
+    match prices.len() {
+        0 => None,
+        n if n % 2 == 1 => Some(prices[mid]),
+        _ => Some((prices[mid - 1] + prices[mid]) / 2.0),
+    }
+}
@@ -0,0 +1,340 @@
+//! A snapshot of a market's open orders, aggregated into price levels.
+//!
+//! The market contract has no "list all orders" getter, so the order ids have to
+//! come from somewhere: the market's event log indexed into an `EventStore`, the
+//! makers we know about (`user_orders`), `OpenOrderEvent` logs in transaction
+//! receipts, or ids collected elsewhere. Every id is then read back with
+//! `order()`, which drops the ones that were filled or cancelled.
+
+use std::{collections::HashSet, fmt::Write, path::Path};
+
+use anyhow::{anyhow, Result};
+use fuels::{
+    accounts::provider::Provider,
+    tx::Receipt,
+    types::{tx_status::TxStatus, Bits256, Bytes32, Identity},
+};
+use spark_market_sdk::{OpenOrderEvent, OrderType, SparkMarketContract};
+
+use crate::{
+    amount::{Price, Quantity, Rounding},
+    indexer::{EventStore, Indexer},
+    market::MarketInfo,
+    utils::{bits256_from_hex_str, hex_str_from_bits256},
+};
+
+/// One open order, with amounts in market units.
+#[derive(Debug, Clone)]
+pub struct BookOrder {
+    pub id: Bits256,
+    pub side: OrderType,
+    pub amount: Quantity,
+    pub price: Price,
+    pub owner: Identity,
+}
+
+/// All orders on one side at one price.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Level {
+    pub price: Price,
+    pub amount: Quantity,
+    pub orders: usize,
+    /// Amount available at this level and every better one.
+    pub depth: Quantity,
+}
+
+/// Collects order ids from makers, receipts and transactions, then reads them
+/// from the market with `build`.
+pub struct OrderBookBuilder<'a> {
+    market: &'a SparkMarketContract,
+    info: &'a MarketInfo,
+    makers: Vec<Identity>,
+    ids: Vec<Bits256>,
+}
+
+impl<'a> OrderBookBuilder<'a> {
+    pub fn new(market: &'a SparkMarketContract, info: &'a MarketInfo) -> Self {
+        Self {
+            market,
+            info,
+            makers: Vec::new(),
+            ids: Vec::new(),
+        }
+    }
+
+    /// Includes every open order of `maker`.
+    pub fn maker(mut self, maker: Identity) -> Self {
+        self.makers.push(maker);
+        self
+    }
+
+    pub fn makers(mut self, makers: impl IntoIterator<Item = Identity>) -> Self {
+        self.makers.extend(makers);
+        self
+    }
+
+    /// Includes orders by id, e.g. from an indexer. Closed ones are skipped.
+    pub fn order_ids(mut self, ids: impl IntoIterator<Item = Bits256>) -> Self {
+        self.ids.extend(ids);
+        self
+    }
+
+    /// Includes every order the market's log shows opening, after indexing new
+    /// blocks into the event database at `db`. Only the orders of `owners` are
+    /// included unless it is empty.
+    pub async fn indexed(
+        mut self,
+        provider: &Provider,
+        db: &Path,
+        owners: &[Identity],
+    ) -> Result<Self> {
+        let store = EventStore::open(db, self.info.contract_id)?;
+        let mut indexer = Indexer::new(self.market, provider.clone(), store);
+        indexer.sync(0).await?;
+
+        let store = indexer.store();
+        let records = if owners.is_empty() {
+            store.opened(None, 0)?
+        } else {
+            owners
+                .iter()
+                .map(|owner| store.opened(Some(owner), 0))
+                .collect::<Result<Vec<_>>>()?
+                .concat()
+        };
+        for record in records {
+            self.ids.push(bits256_from_hex_str(&record.order_id)?);
+        }
+        Ok(self)
+    }
+
+    /// Includes the orders opened in `receipts`, found through their `OpenOrderEvent` logs.
+    pub fn receipts(mut self, receipts: &[Receipt]) -> Result<Self> {
+        let events = self
+            .market
+            .get_instance()
+            .log_decoder()
+            .decode_logs_with_type::<OpenOrderEvent>(receipts)?;
+        self.ids
+            .extend(events.into_iter().map(|event| event.order_id));
+        Ok(self)
+    }
+
+    /// Includes the orders opened by the given transactions.
+    pub async fn transactions(mut self, provider: &Provider, tx_ids: &[Bytes32]) -> Result<Self> {
+        for tx_id in tx_ids {
+            let receipts = match provider.tx_status(tx_id).await? {
+                TxStatus::Success { receipts } => receipts,
+                _ => continue,
+            };
+            self = self.receipts(&receipts)?;
+        }
+        Ok(self)
+    }
+
+    /// Reads every collected order from the market and aggregates the book.
+    pub async fn build(self) -> Result<OrderBook> {
+        let mut ids = self.ids;
+        for maker in self.makers {
+            ids.extend(self.market.user_orders(maker).await?.value);
+        }
+
+        let mut seen = HashSet::new();
+        let mut orders = Vec::new();
+        for id in ids {
+            if !seen.insert(id) {
+                continue;
+            }
+            let Some(order) = self.market.order(id).await?.value else {
+                continue;
+            };
+            orders.push(BookOrder {
+                id,
+                side: order.order_type,
+                amount: self.info.base.quantity(order.amount),
+                price: self.info.price(order.price),
+                owner: order.owner,
+            });
+        }
+
+        OrderBook::from_orders(self.info, orders)
+    }
+}
+
+/// Bids from the highest price down, asks from the lowest price up.
+#[derive(Debug, Clone)]
+pub struct OrderBook {
+    pub market: MarketInfo,
+    pub orders: Vec<BookOrder>,
+    pub bids: Vec<Level>,
+    pub asks: Vec<Level>,
+}
+
+impl OrderBook {
+    pub fn builder<'a>(
+        market: &'a SparkMarketContract,
+        info: &'a MarketInfo,
+    ) -> OrderBookBuilder<'a> {
+        OrderBookBuilder::new(market, info)
+    }
+
+    /// Aggregates already fetched orders.
+    pub fn from_orders(info: &MarketInfo, orders: Vec<BookOrder>) -> Result<Self> {
+        let mut bids = Vec::new();
+        let mut asks = Vec::new();
+        for order in &orders {
+            match order.side {
+                OrderType::Buy => bids.push(order),
+                OrderType::Sell => asks.push(order),
+            }
+        }
+        bids.sort_by(|a, b| b.price.cmp(&a.price));
+        asks.sort_by(|a, b| a.price.cmp(&b.price));
+
+        Ok(Self {
+            market: info.clone(),
+            bids: levels(&bids, info.base.decimals)?,
+            asks: levels(&asks, info.base.decimals)?,
+            orders,
+        })
+    }
+
+    pub fn best_bid(&self) -> Option<&Level> {
+        self.bids.first()
+    }
+
+    pub fn best_ask(&self) -> Option<&Level> {
+        self.asks.first()
+    }
+
+    /// Best ask minus best bid, zero when the book is crossed.
+    pub fn spread(&self) -> Option<Price> {
+        let (bid, ask) = (self.best_bid()?, self.best_ask()?);
+        Some(
+            self.market
+                .price(ask.price.raw().saturating_sub(bid.price.raw())),
+        )
+    }
+
+    /// Halfway between the best bid and ask, rounded down.
+    pub fn mid(&self) -> Option<Price> {
+        let (bid, ask) = (self.best_bid()?, self.best_ask()?);
+        let sum = u128::from(bid.price.raw()) + u128::from(ask.price.raw());
+        Some(self.market.price((sum / 2) as u64))
+    }
+
+    /// Whether some bid is at or above some ask, i.e. there is something to match.
+    pub fn is_crossed(&self) -> bool {
+        matches!(
+            (self.best_bid(), self.best_ask()),
+            (Some(bid), Some(ask)) if bid.price >= ask.price
+        )
+    }
+
+    /// Total base amount on one side.
+    pub fn total(&self, side: OrderType) -> Quantity {
+        let levels = match side {
+            OrderType::Buy => &self.bids,
+            OrderType::Sell => &self.asks,
+        };
+        levels
+            .last()
+            .map(|level| level.depth)
+            .unwrap_or_else(|| Quantity::zero(self.market.base.decimals))
+    }
+
+    /// Quote value of one side of the book at its own prices.
+    pub fn notional(&self, side: OrderType) -> Result<Quantity> {
+        let levels = match side {
+            OrderType::Buy => &self.bids,
+            OrderType::Sell => &self.asks,
+        };
+        levels.iter().try_fold(
+            Quantity::zero(self.market.quote.decimals),
+            |total, level| {
+                let value = self
+                    .market
+                    .quote_for(level.amount, level.price, Rounding::Down)?;
+                total.checked_add(value)
+            },
+        )
+    }
+
+    /// A text ladder with the best `depth` levels per side, asks above bids:
+    ///
+    /// ```text
+    /// ETH_USDC            price         amount          depth  orders
+    /// ask          3210.000000000    0.500000000    1.500000000       2
+    /// ask          3205.000000000    1.000000000    1.000000000       1
+    /// --- spread 5.000000000, mid 3202.500000000 ---
+    /// bid          3200.000000000    0.250000000    0.250000000       1
+    /// ```
+    pub fn ladder(&self, depth: usize) -> String {
+        let mut out = String::new();
+        let _ = writeln!(
+            out,
+            "{:<8} {:>20} {:>20} {:>20} {:>7}",
+            self.market.name(),
+            "price",
+            "amount",
+            "depth",
+            "orders"
+        );
+        let row = |out: &mut String, side: &str, level: &Level| {
+            let _ = writeln!(
+                out,
+                "{:<8} {:>20} {:>20} {:>20} {:>7}",
+                side,
+                level.price.to_string(),
+                level.amount.to_string(),
+                level.depth.to_string(),
+                level.orders
+            );
+        };
+
+        for level in self.asks.iter().take(depth).rev() {
+            row(&mut out, "ask", level);
+        }
+        let _ = match (self.spread(), self.mid()) {
+            (Some(spread), Some(mid)) if self.is_crossed() => {
+                writeln!(out, "--- crossed, spread {}, mid {} ---", spread, mid)
+            }
+            (Some(spread), Some(mid)) => {
+                writeln!(out, "--- spread {}, mid {} ---", spread, mid)
+            }
+            _ => writeln!(out, "--- one sided book ---"),
+        };
+        for level in self.bids.iter().take(depth) {
+            row(&mut out, "bid", level);
+        }
+        out.trim_end().to_string()
+    }
+}
+
+/// Groups orders already sorted best price first into levels with running depth.
+fn levels(orders: &[&BookOrder], base_decimals: u32) -> Result<Vec<Level>> {
+    let mut levels: Vec<Level> = Vec::new();
+    let mut depth = Quantity::zero(base_decimals);
+    for order in orders {
+        depth = depth.checked_add(order.amount).map_err(|_| {
+            anyhow!(
+                "Book depth overflows at order {}",
+                hex_str_from_bits256(&order.id)
+            )
+        })?;
+        match levels.last_mut() {
+            Some(level) if level.price == order.price => {
+                level.amount = level.amount.checked_add(order.amount)?;
+                level.orders += 1;
+                level.depth = depth;
+            }
+            _ => levels.push(Level {
+                price: order.price,
+                amount: order.amount,
+                orders: 1,
+                depth,
+            }),
+        }
+    }
+    Ok(levels)
+}
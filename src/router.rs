@@ -0,0 +1,666 @@
+//! Routing a trade across every registered market.
+//!
+//! The same asset often trades in several markets, e.g. ETH against USDC and
+//! USDC against TRMP. `Router` asks the registry for every market between the
+//! configured assets, reads their books and plans "receive this much of one
+//! asset paying with another" over the direct markets and two-hop paths
+//! through a third asset, e.g. TRMP→USDC→ETH. The amount is cut into slices
+//! that each take the cheapest path left by the slices before them, so a large
+//! trade spreads over several paths once the best one gets expensive. Planned
+//! amounts include the highest taker fee of each market, and buys the matcher
+//! fee as well.
+//!
+//! `RoutePlan::execute` takes every leg with `fulfill_many` against the orders
+//! the plan walked. The first leg's input is deposited as far as the market
+//! account lacks it, each leg's proceeds move on to the next market with
+//! `withdraw_to_market`, and the last leg's proceeds stay in its market account.
+//! `RoutePlan::simulate` dry-runs all of it as one multicall instead.
+
+use std::{collections::HashMap, path::Path};
+
+use anyhow::{anyhow, bail, Result};
+use fuels::{
+    prelude::CallParameters,
+    programs::calls::CallHandler,
+    types::{AssetId, Bits256, ContractId, Identity},
+};
+use spark_market_sdk::{Account, AssetType, LimitType, OrderType};
+
+use crate::{
+    amount::{Price, Quantity, Rounding},
+    assets::AssetInfo,
+    batch::{MultiCall, DEPOSIT_GAS},
+    fees::{FeeSchedule, FEE_SCALE},
+    market::{self, MarketInfo},
+    orderbook::{BookOrder, OrderBook},
+    simulate::{Simulation, Simulator},
+    SparkContext,
+};
+
+/// Slices a planned amount is cut into.
+pub const DEFAULT_SLICES: usize = 10;
+
+/// A market and its book as the router sees it.
+#[derive(Debug, Clone)]
+pub struct Venue {
+    pub info: MarketInfo,
+    pub book: OrderBook,
+}
+
+impl Venue {
+    /// What the market trades `pay` into `receive` with: a buy pays quote for
+    /// base, a sell pays base for quote.
+    fn side(&self, pay: &AssetInfo, receive: &AssetInfo) -> Option<OrderType> {
+        let (base, quote) = (self.info.base.asset_id, self.info.quote.asset_id);
+        if base == receive.asset_id && quote == pay.asset_id {
+            Some(OrderType::Buy)
+        } else if base == pay.asset_id && quote == receive.asset_id {
+            Some(OrderType::Sell)
+        } else {
+            None
+        }
+    }
+
+    fn other(&self, asset: &AssetInfo) -> Option<&AssetInfo> {
+        if self.info.base.asset_id == asset.asset_id {
+            Some(&self.info.quote)
+        } else if self.info.quote.asset_id == asset.asset_id {
+            Some(&self.info.base)
+        } else {
+            None
+        }
+    }
+
+    /// Highest taker fee of any tier, since the trader's tier is not known here.
+    fn taker_bps(&self) -> u64 {
+        FeeSchedule::from_market(&self.info).max_taker_bps()
+    }
+}
+
+/// One trade on one market.
+#[derive(Debug, Clone)]
+pub struct Leg {
+    pub market: ContractId,
+    /// `Buy` takes asks, paying quote for base; `Sell` takes bids.
+    pub side: OrderType,
+    pub pay: AssetInfo,
+    pub receive: AssetInfo,
+    /// Spent, taker fee included.
+    pub input: Quantity,
+    /// Received, taker fee deducted.
+    pub output: Quantity,
+    /// Base amount traded, as `fulfill_many` takes it.
+    pub base: Quantity,
+    /// Worst price walked: the highest ask of a buy, the lowest bid of a sell.
+    pub limit_price: Price,
+    /// Orders taken, with the base amount taken from each.
+    pub orders: Vec<(Bits256, Quantity)>,
+}
+
+impl Leg {
+    /// Adds another slice taken on the same market and side.
+    fn merge(&mut self, other: Leg) -> Result<()> {
+        self.input = self.input.checked_add(other.input)?;
+        self.output = self.output.checked_add(other.output)?;
+        self.base = self.base.checked_add(other.base)?;
+        self.limit_price = match self.side {
+            OrderType::Buy => self.limit_price.max(other.limit_price),
+            OrderType::Sell => self.limit_price.min(other.limit_price),
+        };
+        for (id, amount) in other.orders {
+            match self.orders.iter_mut().find(|(taken, _)| *taken == id) {
+                Some((_, total)) => *total = total.checked_add(amount)?,
+                None => self.orders.push((id, amount)),
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A direct or two-hop path and what it carries.
+#[derive(Debug, Clone)]
+pub struct Route {
+    pub legs: Vec<Leg>,
+}
+
+impl Route {
+    pub fn input(&self) -> Quantity {
+        self.legs[0].input
+    }
+
+    pub fn output(&self) -> Quantity {
+        self.legs[self.legs.len() - 1].output
+    }
+
+    /// Paid per unit received.
+    pub fn average_price(&self) -> f64 {
+        average_price(self.input(), self.output())
+    }
+}
+
+/// How to receive `amount` of `receive` paying with `pay`.
+#[derive(Debug, Clone)]
+pub struct RoutePlan {
+    pub pay: AssetInfo,
+    pub receive: AssetInfo,
+    pub amount: Quantity,
+    pub routes: Vec<Route>,
+}
+
+impl RoutePlan {
+    pub fn input(&self) -> Result<Quantity> {
+        self.routes
+            .iter()
+            .try_fold(Quantity::zero(self.pay.decimals), |total, route| {
+                total.checked_add(route.input())
+            })
+    }
+
+    pub fn output(&self) -> Result<Quantity> {
+        self.routes
+            .iter()
+            .try_fold(Quantity::zero(self.receive.decimals), |total, route| {
+                total.checked_add(route.output())
+            })
+    }
+
+    /// Paid per unit received over every route.
+    pub fn average_price(&self) -> Result<f64> {
+        Ok(average_price(self.input()?, self.output()?))
+    }
+
+    /// Takes every leg; `slippage_bps` is how far past each leg's limit price
+    /// `fulfill_many` may fill.
+    pub async fn execute(&self, ctx: &SparkContext, slippage_bps: u64) -> Result<RouteReport> {
+        let user = ctx.wallet_id();
+        let mut report = RouteReport {
+            pay: self.pay.clone(),
+            receive: self.receive.clone(),
+            legs: Vec::new(),
+            spent: Quantity::zero(self.pay.decimals),
+            received: Quantity::zero(self.receive.decimals),
+        };
+
+        for (r, route) in self.routes.iter().enumerate() {
+            // Proceeds of the previous leg: market, asset type and amount
+            let mut carried: Option<(ContractId, AssetType, Quantity)> = None;
+            for (i, leg) in route.legs.iter().enumerate() {
+                let market = ctx.connect_market(leg.market).await;
+                let contract_ids = ctx.contract_ids_of(leg.market);
+                let (pay_type, receive_type) = match leg.side {
+                    OrderType::Buy => (AssetType::Quote, AssetType::Base),
+                    OrderType::Sell => (AssetType::Base, AssetType::Quote),
+                };
+
+                match carried.take() {
+                    Some((from, asset_type, amount)) => {
+                        // The receiving market takes part in the call
+                        let mut ids = ctx.contract_ids_of(from);
+                        ids.push(leg.market.into());
+                        ids.extend(contract_ids.iter().cloned());
+                        ctx.connect_market(from)
+                            .await
+                            .get_instance()
+                            .methods()
+                            .withdraw_to_market(amount.raw(), asset_type, leg.market)
+                            .with_contract_ids(&ids)
+                            .call()
+                            .await?;
+                    }
+                    None => {
+                        let account = market.account(user.clone()).await?.value;
+                        let liquid = liquid(&account, &pay_type);
+                        if liquid < leg.input.raw() {
+                            market
+                                .get_instance()
+                                .methods()
+                                .deposit()
+                                .with_contract_ids(&contract_ids)
+                                .call_params(CallParameters::new(
+                                    leg.input.raw() - liquid,
+                                    leg.pay.asset_id,
+                                    DEPOSIT_GAS,
+                                ))?
+                                .call()
+                                .await?;
+                        }
+                    }
+                }
+
+                let before = market.account(user.clone()).await?.value;
+                // A sell can only hand over what the account holds
+                let base = match leg.side {
+                    OrderType::Buy => leg.base.raw(),
+                    OrderType::Sell => leg.base.raw().min(before.liquid.base),
+                };
+                let orders = leg.orders.iter().map(|(id, _)| *id).collect();
+                let response = market
+                    .get_instance()
+                    .methods()
+                    .fulfill_order_many(
+                        base,
+                        leg.side.clone(),
+                        LimitType::IOC,
+                        leg.limit_price.raw(),
+                        slippage_bps,
+                        orders,
+                    )
+                    .with_contract_ids(&contract_ids)
+                    .call()
+                    .await?;
+                let after = market.account(user.clone()).await?.value;
+
+                let spent = leg
+                    .pay
+                    .quantity(liquid(&before, &pay_type).saturating_sub(liquid(&after, &pay_type)));
+                let received = leg.receive.quantity(
+                    liquid(&after, &receive_type).saturating_sub(liquid(&before, &receive_type)),
+                );
+                if i == 0 {
+                    report.spent = report.spent.checked_add(spent)?;
+                }
+                if i == route.legs.len() - 1 {
+                    report.received = report.received.checked_add(received)?;
+                }
+                report.legs.push(LegReport {
+                    route: r,
+                    market: leg.market,
+                    side: leg.side.clone(),
+                    pay: leg.pay.clone(),
+                    receive: leg.receive.clone(),
+                    tx_id: response.tx_id.map(|id| format!("0x{}", id)),
+                    spent,
+                    received,
+                });
+                carried = Some((leg.market, receive_type, received));
+            }
+        }
+        Ok(report)
+    }
+
+    /// Dry-runs every leg as `execute` would, in one multicall. Nothing has
+    /// been received yet, so each leg's planned output is what moves on to
+    /// the next market.
+    pub async fn simulate(&self, ctx: &SparkContext, slippage_bps: u64) -> Result<Simulation> {
+        let user = ctx.wallet_id();
+        let mut simulator = Simulator::new(ctx);
+        let mut multi: MultiCall = CallHandler::new_multi_call(ctx.wallet.clone());
+        // Liquid balance not yet spent by an earlier route
+        let mut unspent: HashMap<(ContractId, AssetId), u64> = HashMap::new();
+
+        for route in &self.routes {
+            let mut carried: Option<(ContractId, AssetType, Quantity)> = None;
+            for leg in &route.legs {
+                simulator = simulator.market(ctx.market_info(leg.market).await?);
+                let market = ctx.connect_market(leg.market).await;
+                let contract_ids = ctx.contract_ids_of(leg.market);
+                let (pay_type, receive_type) = match leg.side {
+                    OrderType::Buy => (AssetType::Quote, AssetType::Base),
+                    OrderType::Sell => (AssetType::Base, AssetType::Quote),
+                };
+
+                match carried.take() {
+                    Some((from, asset_type, amount)) => {
+                        // The receiving market takes part in the call
+                        let mut ids = ctx.contract_ids_of(from);
+                        ids.push(leg.market.into());
+                        ids.extend(contract_ids.iter().cloned());
+                        multi = multi.add_call(
+                            ctx.connect_market(from)
+                                .await
+                                .get_instance()
+                                .methods()
+                                .withdraw_to_market(amount.raw(), asset_type, leg.market)
+                                .with_contract_ids(&ids),
+                        );
+                    }
+                    None => {
+                        let key = (leg.market, leg.pay.asset_id);
+                        let liquid = match unspent.get(&key) {
+                            Some(liquid) => *liquid,
+                            None => liquid(&market.account(user.clone()).await?.value, &pay_type),
+                        };
+                        let short = leg.input.raw().saturating_sub(liquid);
+                        if short > 0 {
+                            multi = multi.add_call(
+                                market
+                                    .get_instance()
+                                    .methods()
+                                    .deposit()
+                                    .with_contract_ids(&contract_ids)
+                                    .call_params(CallParameters::new(
+                                        short,
+                                        leg.pay.asset_id,
+                                        DEPOSIT_GAS,
+                                    ))?,
+                            );
+                        }
+                        unspent.insert(key, liquid.saturating_sub(leg.input.raw()));
+                    }
+                }
+
+                let orders = leg.orders.iter().map(|(id, _)| *id).collect();
+                multi = multi.add_call(
+                    market
+                        .get_instance()
+                        .methods()
+                        .fulfill_order_many(
+                            leg.base.raw(),
+                            leg.side.clone(),
+                            LimitType::IOC,
+                            leg.limit_price.raw(),
+                            slippage_bps,
+                            orders,
+                        )
+                        .with_contract_ids(&contract_ids),
+                );
+                carried = Some((leg.market, receive_type, leg.output));
+            }
+        }
+        simulator.multi_call(multi).await
+    }
+}
+
+/// What executing one leg did, measured on the market account.
+#[derive(Debug, Clone)]
+pub struct LegReport {
+    /// Index of its route in the plan.
+    pub route: usize,
+    pub market: ContractId,
+    pub side: OrderType,
+    pub pay: AssetInfo,
+    pub receive: AssetInfo,
+    pub tx_id: Option<String>,
+    pub spent: Quantity,
+    pub received: Quantity,
+}
+
+#[derive(Debug, Clone)]
+pub struct RouteReport {
+    pub pay: AssetInfo,
+    pub receive: AssetInfo,
+    /// Every leg of every route, in execution order.
+    pub legs: Vec<LegReport>,
+    /// Paid on the first legs.
+    pub spent: Quantity,
+    /// Received on the last legs.
+    pub received: Quantity,
+}
+
+impl RouteReport {
+    /// The achieved price: paid per unit received.
+    pub fn average_price(&self) -> f64 {
+        average_price(self.spent, self.received)
+    }
+}
+
+pub struct Router {
+    venues: Vec<Venue>,
+    slices: usize,
+}
+
+impl Router {
+    pub fn new(venues: Vec<Venue>) -> Self {
+        Self {
+            venues,
+            slices: DEFAULT_SLICES,
+        }
+    }
+
+    /// Every market the registry lists between the configured assets, with the
+    /// books found by indexing each market's events into the database at `db`.
+    /// Only the orders of `makers` are routed through unless it is empty.
+    pub async fn discover(ctx: &SparkContext, db: &Path, makers: &[Identity]) -> Result<Self> {
+        let mut venues = Vec::new();
+        for contract_id in market::registered_markets(&ctx.registry, &ctx.assets).await? {
+            let info = ctx.market_info(contract_id).await?;
+            let market = ctx.connect_market(contract_id).await;
+            let book = OrderBook::builder(&market, &info)
+                .indexed(&ctx.provider, db, makers)
+                .await?
+                .build()
+                .await?;
+            venues.push(Venue { info, book });
+        }
+        Ok(Self::new(venues))
+    }
+
+    /// Slices a planned amount is cut into; more split finer and plan slower.
+    pub fn slices(mut self, slices: usize) -> Self {
+        self.slices = slices.max(1);
+        self
+    }
+
+    pub fn venues(&self) -> &[Venue] {
+        &self.venues
+    }
+
+    /// Plans receiving `amount` of `receive` paying with `pay`.
+    pub fn plan(
+        &self,
+        pay: &AssetInfo,
+        receive: &AssetInfo,
+        amount: Quantity,
+    ) -> Result<RoutePlan> {
+        if amount.is_zero() {
+            bail!("Nothing to route");
+        }
+        let paths = self.paths(pay, receive);
+        if paths.is_empty() {
+            bail!("No market path from {} to {}", pay.symbol, receive.symbol);
+        }
+
+        let mut taken = HashMap::new();
+        let mut routes: Vec<Option<Route>> = vec![None; paths.len()];
+        for slice in slices(amount, self.slices) {
+            let mut best: Option<(usize, Vec<Leg>)> = None;
+            for (i, path) in paths.iter().enumerate() {
+                let Some(legs) = self.walk_path(path, slice, &taken)? else {
+                    continue;
+                };
+                if best
+                    .as_ref()
+                    .map_or(true, |(_, best)| legs[0].input.raw() < best[0].input.raw())
+                {
+                    best = Some((i, legs));
+                }
+            }
+            let (i, legs) = best.ok_or_else(|| {
+                anyhow!(
+                    "Not enough liquidity to receive {} {} paying with {}",
+                    amount,
+                    receive.symbol,
+                    pay.symbol
+                )
+            })?;
+
+            for leg in &legs {
+                for (id, amount) in &leg.orders {
+                    *taken.entry(*id).or_insert(0) += amount.raw();
+                }
+            }
+            match &mut routes[i] {
+                Some(route) => {
+                    for (total, leg) in route.legs.iter_mut().zip(legs) {
+                        total.merge(leg)?;
+                    }
+                }
+                None => routes[i] = Some(Route { legs }),
+            }
+        }
+
+        Ok(RoutePlan {
+            pay: pay.clone(),
+            receive: receive.clone(),
+            amount,
+            routes: routes.into_iter().flatten().collect(),
+        })
+    }
+
+    /// Direct markets and two-hop paths, as venue indices with the side taken.
+    fn paths(&self, pay: &AssetInfo, receive: &AssetInfo) -> Vec<Vec<(usize, OrderType)>> {
+        let mut paths = Vec::new();
+        for (i, first) in self.venues.iter().enumerate() {
+            if let Some(side) = first.side(pay, receive) {
+                paths.push(vec![(i, side)]);
+                continue;
+            }
+            let Some(middle) = first.other(pay) else {
+                continue;
+            };
+            let Some(first_side) = first.side(pay, middle) else {
+                continue;
+            };
+            for (j, second) in self.venues.iter().enumerate() {
+                if let Some(second_side) = second.side(middle, receive) {
+                    paths.push(vec![(i, first_side.clone()), (j, second_side)]);
+                }
+            }
+        }
+        paths
+    }
+
+    /// The legs that deliver `output` through `path`, worked out from the last
+    /// leg back, or `None` when the books run dry.
+    fn walk_path(
+        &self,
+        path: &[(usize, OrderType)],
+        output: Quantity,
+        taken: &HashMap<Bits256, u64>,
+    ) -> Result<Option<Vec<Leg>>> {
+        let mut legs = Vec::with_capacity(path.len());
+        let mut output = output;
+        for (venue, side) in path.iter().rev() {
+            let Some(leg) = walk(&self.venues[*venue], side, output, taken)? else {
+                return Ok(None);
+            };
+            output = leg.input;
+            legs.push(leg);
+        }
+        legs.reverse();
+        Ok(Some(legs))
+    }
+}
+
+/// Takes `output` of the leg's received asset from one side of `venue`'s book,
+/// skipping what earlier slices `taken`.
+fn walk(
+    venue: &Venue,
+    side: &OrderType,
+    output: Quantity,
+    taken: &HashMap<Bits256, u64>,
+) -> Result<Option<Leg>> {
+    let info = &venue.info;
+    let fee_bps = venue.taker_bps();
+    if fee_bps >= FEE_SCALE {
+        bail!("{} charges a taker fee of {} bps", info.name(), fee_bps);
+    }
+
+    // A buy takes asks from the lowest, a sell bids from the highest
+    let mut book: Vec<&BookOrder> = venue
+        .book
+        .orders
+        .iter()
+        .filter(|order| order.side != *side)
+        .collect();
+    match side {
+        OrderType::Buy => book.sort_by_key(|order| order.price),
+        OrderType::Sell => book.sort_by_key(|order| std::cmp::Reverse(order.price)),
+    }
+
+    let (pay, receive) = match side {
+        OrderType::Buy => (&info.quote, &info.base),
+        OrderType::Sell => (&info.base, &info.quote),
+    };
+    // A sell has to fetch the fee on top of what it delivers
+    let mut need = match side {
+        OrderType::Buy => output.raw(),
+        OrderType::Sell => output
+            .mul_div(FEE_SCALE, FEE_SCALE - fee_bps, Rounding::Up)?
+            .raw(),
+    };
+    let mut paid = Quantity::zero(pay.decimals);
+    let mut base = Quantity::zero(info.base.decimals);
+    let mut orders = Vec::new();
+    let mut limit_price = None;
+    for order in book {
+        if need == 0 {
+            break;
+        }
+        let left = order.amount.raw() - taken.get(&order.id).copied().unwrap_or(0);
+        if left == 0 {
+            continue;
+        }
+        let take = match side {
+            OrderType::Buy => left.min(need),
+            OrderType::Sell => {
+                let wanted = info.base_for(receive.quantity(need), order.price, Rounding::Up)?;
+                left.min(wanted.raw())
+            }
+        };
+        let take = info.base.quantity(take);
+        let value = match side {
+            OrderType::Buy => info.quote_for(take, order.price, Rounding::Up)?,
+            OrderType::Sell => info.quote_for(take, order.price, Rounding::Down)?,
+        };
+        match side {
+            OrderType::Buy => {
+                paid = paid.checked_add(value)?;
+                need -= take.raw();
+            }
+            OrderType::Sell => {
+                paid = paid.checked_add(take)?;
+                need = need.saturating_sub(value.raw());
+            }
+        }
+        base = base.checked_add(take)?;
+        orders.push((order.id, take));
+        limit_price = Some(order.price);
+    }
+    let Some(limit_price) = limit_price.filter(|_| need == 0) else {
+        return Ok(None);
+    };
+
+    // A buy pays the fees on top of the quote it spends
+    let input = match side {
+        OrderType::Buy => {
+            let fees = FeeSchedule::from_market(info).reserve(paid, fee_bps)?;
+            paid.checked_add(fees)?
+        }
+        OrderType::Sell => paid,
+    };
+    Ok(Some(Leg {
+        market: info.contract_id,
+        side: side.clone(),
+        pay: pay.clone(),
+        receive: receive.clone(),
+        input,
+        output,
+        base,
+        limit_price,
+        orders,
+    }))
+}
+
+/// `amount` cut into `count` nearly equal non-zero parts.
+fn slices(amount: Quantity, count: usize) -> Vec<Quantity> {
+    let count = (count as u64).min(amount.raw()).max(1);
+    let (each, rest) = (amount.raw() / count, amount.raw() % count);
+    (0..count)
+        .map(|i| Quantity::from_raw(each + u64::from(i < rest), amount.decimals()))
+        .collect()
+}
+
+fn liquid(account: &Account, asset_type: &AssetType) -> u64 {
+    match asset_type {
+        AssetType::Base => account.liquid.base,
+        AssetType::Quote => account.liquid.quote,
+    }
+}
+
+fn average_price(paid: Quantity, received: Quantity) -> f64 {
+    if received.is_zero() {
+        return 0.0;
+    }
+    paid.to_f64() / received.to_f64()
+}
@@ -0,0 +1,304 @@
+//! Dry runs of state-changing calls.
+//!
+//! `Simulator` runs a call or multicall through the SDK's `simulate()` instead
+//! of submitting it, against the chain as it is now. The receipts are decoded
+//! into what the transaction would do: the market events it would log (orders
+//! opened, cancelled and filled, deposits and withdrawals), the balances those
+//! events leave behind compared with the accounts as they are now, the gas it
+//! burns and its estimated fee. Nothing reaches the chain, so a cancel-all or a
+//! rebalance can be reviewed first.
+
+use std::fmt::{self, Debug};
+
+use anyhow::Result;
+use fuels::{
+    accounts::{provider::TransactionCost, wallet::WalletUnlocked},
+    core::traits::{Parameterize, Tokenizable},
+    programs::calls::{CallHandler, ContractCall, Execution},
+    tx::Receipt,
+    types::{
+        errors::{transaction::Reason, Error as FuelsError},
+        ContractId, Identity,
+    },
+};
+use spark_market_sdk::{Account, CancelOrderEvent, OpenOrderEvent, TradeOrderEvent};
+
+use crate::{
+    amount::Quantity,
+    assets::AssetInfo,
+    batch::MultiCall,
+    error::SparkError,
+    indexer::{decode_events, MarketEvent},
+    market::MarketInfo,
+    SparkContext,
+};
+
+/// How one asset of a market account would change.
+#[derive(Debug, Clone)]
+pub struct BalanceChange {
+    pub market: ContractId,
+    pub user: Identity,
+    pub asset: AssetInfo,
+    pub liquid_before: u64,
+    pub liquid_after: u64,
+    pub locked_before: u64,
+    pub locked_after: u64,
+}
+
+impl BalanceChange {
+    pub fn liquid_delta(&self) -> i128 {
+        self.liquid_after as i128 - self.liquid_before as i128
+    }
+
+    pub fn locked_delta(&self) -> i128 {
+        self.locked_after as i128 - self.locked_before as i128
+    }
+}
+
+/// What a transaction would do, had it been sent.
+#[derive(Debug, Clone)]
+pub struct Simulation {
+    /// Why it would revert; `None` when it would go through.
+    pub error: Option<SparkError>,
+    /// Gas the script used in the dry run.
+    pub gas_used: u64,
+    /// Estimated fee in the gas token, when the transaction could be estimated.
+    pub fee: Option<Quantity>,
+    /// Decoded events in log order, with the market that logged them. Empty
+    /// when it would revert.
+    pub events: Vec<(ContractId, MarketEvent)>,
+    /// Every account balance the events change.
+    pub balances: Vec<BalanceChange>,
+    /// The markets whose logs were decoded.
+    pub markets: Vec<MarketInfo>,
+    pub receipts: Vec<Receipt>,
+}
+
+impl Simulation {
+    pub fn is_success(&self) -> bool {
+        self.error.is_none()
+    }
+
+    pub fn market(&self, contract_id: ContractId) -> Option<&MarketInfo> {
+        self.markets
+            .iter()
+            .find(|market| market.contract_id == contract_id)
+    }
+
+    pub fn opened(&self) -> Vec<&OpenOrderEvent> {
+        self.events
+            .iter()
+            .filter_map(|(_, event)| match event {
+                MarketEvent::Open(e) => Some(e),
+                _ => None,
+            })
+            .collect()
+    }
+
+    pub fn cancelled(&self) -> Vec<&CancelOrderEvent> {
+        self.events
+            .iter()
+            .filter_map(|(_, event)| match event {
+                MarketEvent::Cancel(e) => Some(e),
+                _ => None,
+            })
+            .collect()
+    }
+
+    pub fn trades(&self) -> Vec<&TradeOrderEvent> {
+        self.events
+            .iter()
+            .filter_map(|(_, event)| match event {
+                MarketEvent::Trade(e) => Some(e),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// The change to one user's balance of one asset, if any.
+    pub fn balance(&self, user: &Identity, asset: &AssetInfo) -> Option<&BalanceChange> {
+        self.balances
+            .iter()
+            .find(|change| change.user == *user && change.asset.asset_id == asset.asset_id)
+    }
+}
+
+/// One line: why it would revert, or the gas, fee and orders it would touch.
+impl fmt::Display for Simulation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(error) = &self.error {
+            return write!(f, "would revert: {}", error);
+        }
+        write!(f, "{} gas", self.gas_used)?;
+        if let Some(fee) = self.fee {
+            write!(f, ", fee {}", fee)?;
+        }
+        write!(
+            f,
+            ": {} opened, {} cancelled, {} trades",
+            self.opened().len(),
+            self.cancelled().len(),
+            self.trades().len()
+        )
+    }
+}
+
+/// Dry-runs calls built the same way as for sending them:
+///
+/// ```ignore
+/// let simulation = Simulator::new(&ctx)
+///     .call(ctx.market.get_instance().methods().cancel_order(id))
+///     .await?;
+/// for order in simulation.cancelled() { ... }
+/// ```
+///
+/// Only the logs of the context's market are decoded unless other markets are
+/// added with `market`, e.g. for a `withdraw_to_market`.
+pub struct Simulator<'a> {
+    ctx: &'a SparkContext,
+    markets: Vec<MarketInfo>,
+}
+
+impl<'a> Simulator<'a> {
+    pub fn new(ctx: &'a SparkContext) -> Self {
+        Self {
+            ctx,
+            markets: vec![ctx.market_info.clone()],
+        }
+    }
+
+    /// Also decodes the events and balances of `market`.
+    pub fn market(mut self, market: MarketInfo) -> Self {
+        if self
+            .markets
+            .iter()
+            .all(|known| known.contract_id != market.contract_id)
+        {
+            self.markets.push(market);
+        }
+        self
+    }
+
+    pub async fn call<T>(
+        &self,
+        mut call: CallHandler<WalletUnlocked, ContractCall, T>,
+    ) -> Result<Simulation>
+    where
+        T: Tokenizable + Parameterize + Debug,
+    {
+        // A call that would revert cannot be estimated, but can still be run
+        let cost = call.estimate_transaction_cost(None, None).await.ok();
+        let outcome = call
+            .simulate(Execution::Realistic)
+            .await
+            .map(|response| response.receipts);
+        self.decode(outcome, cost).await
+    }
+
+    pub async fn multi_call(&self, mut multi: MultiCall) -> Result<Simulation> {
+        let cost = multi.estimate_transaction_cost(None, None).await.ok();
+        let outcome = multi
+            .simulate(Execution::Realistic)
+            .await
+            .map(|response| response.receipts);
+        self.decode(outcome, cost).await
+    }
+
+    async fn decode(
+        &self,
+        outcome: fuels::types::errors::Result<Vec<Receipt>>,
+        cost: Option<TransactionCost>,
+    ) -> Result<Simulation> {
+        let ctx = self.ctx;
+        // Only a revert is an outcome, a node or wallet failure is an error
+        let (receipts, error) = match outcome {
+            Ok(receipts) => (receipts, None),
+            Err(e) => match &e {
+                FuelsError::Transaction(Reason::Reverted { receipts, .. }) => {
+                    (receipts.clone(), Some(SparkError::from(&e)))
+                }
+                _ => return Err(e.into()),
+            },
+        };
+        let gas_used = receipts
+            .iter()
+            .find_map(|receipt| match receipt {
+                Receipt::ScriptResult { gas_used, .. } => Some(*gas_used),
+                _ => None,
+            })
+            .unwrap_or_default();
+
+        // A reverted transaction changes nothing, whatever it logged first
+        let effective: &[Receipt] = if error.is_none() { &receipts[..] } else { &[] };
+        let mut events = Vec::new();
+        let mut balances = Vec::new();
+        for info in &self.markets {
+            let market = ctx.connect_market(info.contract_id).await;
+            let decoded = decode_events(
+                &market.get_instance().log_decoder(),
+                info.contract_id,
+                effective,
+            )?;
+
+            // The last account logged for a user is where the transaction leaves it
+            let mut after: Vec<(Identity, Account)> = Vec::new();
+            for (_, event) in &decoded {
+                for (user, account) in event.balances() {
+                    match after.iter_mut().find(|(known, _)| known == user) {
+                        Some(entry) => entry.1 = account.clone(),
+                        None => after.push((user.clone(), account.clone())),
+                    }
+                }
+            }
+            for (user, account) in after {
+                let before = market.account(user.clone()).await?.value;
+                let sides = [
+                    (
+                        &info.base,
+                        (before.liquid.base, account.liquid.base),
+                        (before.locked.base, account.locked.base),
+                    ),
+                    (
+                        &info.quote,
+                        (before.liquid.quote, account.liquid.quote),
+                        (before.locked.quote, account.locked.quote),
+                    ),
+                ];
+                for (asset, (liquid_before, liquid_after), (locked_before, locked_after)) in sides {
+                    if liquid_before == liquid_after && locked_before == locked_after {
+                        continue;
+                    }
+                    balances.push(BalanceChange {
+                        market: info.contract_id,
+                        user: user.clone(),
+                        asset: asset.clone(),
+                        liquid_before,
+                        liquid_after,
+                        locked_before,
+                        locked_after,
+                    });
+                }
+            }
+
+            events.extend(
+                decoded
+                    .into_iter()
+                    .map(|(index, event)| (index, info.contract_id, event)),
+            );
+        }
+        events.sort_by_key(|(index, _, _)| *index);
+
+        Ok(Simulation {
+            error,
+            gas_used,
+            fee: cost.map(|cost| Quantity::from_raw(cost.total_fee, ctx.gas_token_decimals())),
+            events: events
+                .into_iter()
+                .map(|(_, market, event)| (market, event))
+                .collect(),
+            balances,
+            markets: self.markets.clone(),
+            receipts,
+        })
+    }
+}
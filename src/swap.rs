@@ -0,0 +1,340 @@
+//! Taking liquidity from a market's book.
+//!
+//! `Swap::preview` walks the resting orders on the other side of the book from
+//! the best price, stopping at the worst price `max_slippage_bps` allows, and
+//! works out the expected fill, the fees and the price impact before anything
+//! is sent. `Swap::execute` then takes exactly the previewed orders with
+//! `fulfill_many`: `LimitType::IOC` fills what it can, `LimitType::FOK` only
+//! goes ahead when the whole amount fills. `Swap::simulate` dry-runs the same
+//! call instead.
+
+use std::path::Path;
+
+use anyhow::{bail, Result};
+use fuels::types::{Bits256, Identity};
+use spark_market_sdk::{LimitType, OrderType};
+
+use crate::{
+    amount::{Price, Quantity, Rounding},
+    error::SparkError,
+    fees::{FeeSchedule, FEE_SCALE},
+    orderbook::OrderBook,
+    simulate::{Simulation, Simulator},
+    SparkContext,
+};
+
+/// What a swap is expected to do, from the book as it was read.
+#[derive(Debug, Clone)]
+pub struct SwapPreview {
+    /// `Buy` takes asks, paying quote for base; `Sell` takes bids.
+    pub side: OrderType,
+    pub limit_type: LimitType,
+    /// Base amount asked for.
+    pub amount: Quantity,
+    /// Base amount the orders within the slippage cover.
+    pub fill: Quantity,
+    /// Quote value of the fill, before fees.
+    pub value: Quantity,
+    /// Taker and matcher fee, in quote.
+    pub fees: Quantity,
+    /// What the swap spends: quote for a buy, base for a sell.
+    pub input: Quantity,
+    /// What it receives: base for a buy, quote after fees for a sell.
+    pub output: Quantity,
+    pub best_price: Price,
+    /// Worst price `max_slippage_bps` allows, sent to `fulfill_many`.
+    pub limit_price: Price,
+    /// Quote per base over the whole fill, before fees.
+    pub average_price: f64,
+    /// How far the average price is from the best price.
+    pub price_impact_bps: f64,
+    /// The orders taken, best first.
+    pub orders: Vec<Bits256>,
+}
+
+impl SwapPreview {
+    /// Whether the whole amount fills within the slippage.
+    pub fn is_complete(&self) -> bool {
+        self.fill == self.amount
+    }
+}
+
+/// The outcome of a swap, measured on the market account.
+#[derive(Debug, Clone)]
+pub struct SwapResult {
+    pub preview: SwapPreview,
+    pub tx_id: Option<String>,
+    pub spent: Quantity,
+    pub received: Quantity,
+}
+
+/// Swaps against one market's book:
+///
+/// ```ignore
+/// let swap = Swap::load(&ctx, Path::new("spark-events.db"), &[])
+///     .await?
+///     .limit_type(LimitType::FOK);
+/// let preview = swap.preview(OrderType::Buy, eth.parse("0.5")?, 50)?;
+/// println!("{} ETH for {} USDC", preview.output, preview.input);
+/// let result = swap.execute(&preview).await?;
+/// ```
+pub struct Swap<'a> {
+    ctx: &'a SparkContext,
+    book: OrderBook,
+    limit_type: LimitType,
+    taker_bps: u64,
+}
+
+impl<'a> Swap<'a> {
+    /// Swaps against `book`, at the highest taker fee of any tier, filling
+    /// immediately or cancelling.
+    pub fn new(ctx: &'a SparkContext, book: OrderBook) -> Self {
+        let taker_bps = FeeSchedule::from_market(&ctx.market_info).max_taker_bps();
+        Self {
+            ctx,
+            book,
+            limit_type: LimitType::IOC,
+            taker_bps,
+        }
+    }
+
+    /// Reads the book found by indexing the market's events into the database
+    /// at `db`, only the orders of `makers` unless it is empty, and the wallet's
+    /// own taker fee.
+    pub async fn load(ctx: &'a SparkContext, db: &Path, makers: &[Identity]) -> Result<Self> {
+        let book = OrderBook::builder(&ctx.market, &ctx.market_info)
+            .indexed(&ctx.provider, db, makers)
+            .await?
+            .build()
+            .await?;
+        let (_, taker) = ctx.market.protocol_fee_user(ctx.wallet_id()).await?.value;
+        Ok(Self::new(ctx, book).taker_bps(taker))
+    }
+
+    pub fn limit_type(mut self, limit_type: LimitType) -> Self {
+        self.limit_type = limit_type;
+        self
+    }
+
+    pub fn taker_bps(mut self, taker_bps: u64) -> Self {
+        self.taker_bps = taker_bps;
+        self
+    }
+
+    pub fn book(&self) -> &OrderBook {
+        &self.book
+    }
+
+    /// Expected outcome of swapping `amount` of base, taking orders up to
+    /// `max_slippage_bps` past the best price.
+    pub fn preview(
+        &self,
+        side: OrderType,
+        amount: Quantity,
+        max_slippage_bps: u64,
+    ) -> Result<SwapPreview> {
+        let info = &self.ctx.market_info;
+        if amount.is_zero() {
+            bail!("Nothing to swap");
+        }
+
+        // A buy takes asks from the lowest, a sell bids from the highest
+        let mut resting: Vec<_> = self
+            .book
+            .orders
+            .iter()
+            .filter(|order| order.side != side)
+            .collect();
+        match side {
+            OrderType::Buy => resting.sort_by_key(|order| order.price),
+            OrderType::Sell => resting.sort_by_key(|order| std::cmp::Reverse(order.price)),
+        }
+        let Some(best_price) = resting.first().map(|order| order.price) else {
+            bail!(
+                "No {} on the {} book to take",
+                match side {
+                    OrderType::Buy => "asks",
+                    OrderType::Sell => "bids",
+                },
+                info.name()
+            );
+        };
+        let slippage = i64::try_from(max_slippage_bps)?;
+        let limit_price = match side {
+            OrderType::Buy => best_price.offset_bps(slippage, Rounding::Down)?,
+            OrderType::Sell => best_price.offset_bps(-slippage, Rounding::Up)?,
+        };
+
+        // Quote is rounded against the taker
+        let rounding = match side {
+            OrderType::Buy => Rounding::Up,
+            OrderType::Sell => Rounding::Down,
+        };
+        let mut fill = Quantity::zero(info.base.decimals);
+        let mut value = Quantity::zero(info.quote.decimals);
+        let mut orders = Vec::new();
+        for order in resting {
+            let beyond = match side {
+                OrderType::Buy => order.price > limit_price,
+                OrderType::Sell => order.price < limit_price,
+            };
+            if beyond || fill == amount {
+                break;
+            }
+            let take = order.amount.min(amount.checked_sub(fill)?);
+            value = value.checked_add(info.quote_for(take, order.price, rounding)?)?;
+            fill = fill.checked_add(take)?;
+            orders.push(order.id);
+        }
+
+        let fees = FeeSchedule::from_market(info).reserve(value, self.taker_bps)?;
+        let (input, output) = match side {
+            OrderType::Buy => (value.checked_add(fees)?, fill),
+            OrderType::Sell => (fill, value.saturating_sub(fees)?),
+        };
+        let average_price = if fill.is_zero() {
+            0.0
+        } else {
+            value.to_f64() / fill.to_f64()
+        };
+        let price_impact_bps = if fill.is_zero() {
+            0.0
+        } else {
+            (average_price / best_price.to_f64() - 1.0).abs() * FEE_SCALE as f64
+        };
+
+        Ok(SwapPreview {
+            side,
+            limit_type: self.limit_type.clone(),
+            amount,
+            fill,
+            value,
+            fees,
+            input,
+            output,
+            best_price,
+            limit_price,
+            average_price,
+            price_impact_bps,
+            orders,
+        })
+    }
+
+    /// Previews and executes in one go.
+    pub async fn swap(
+        &self,
+        side: OrderType,
+        amount: Quantity,
+        max_slippage_bps: u64,
+    ) -> Result<SwapResult> {
+        let preview = self.preview(side, amount, max_slippage_bps)?;
+        self.execute(&preview).await
+    }
+
+    /// Takes the previewed orders. A fill-or-kill swap that would not fill
+    /// completely, or one the liquid balance does not cover, is not sent.
+    pub async fn execute(&self, preview: &SwapPreview) -> Result<SwapResult> {
+        let ctx = self.ctx;
+        let info = &ctx.market_info;
+        self.check(preview)?;
+
+        let user = ctx.wallet_id();
+        let before = ctx.market.account(user.clone()).await?.value;
+        let (liquid, symbol) = match preview.side {
+            OrderType::Buy => (before.liquid.quote, &info.quote.symbol),
+            OrderType::Sell => (before.liquid.base, &info.base.symbol),
+        };
+        if liquid < preview.input.raw() {
+            return Err(SparkError::InsufficientBalance(format!(
+                "needs {} {}, {} liquid",
+                preview.input,
+                symbol,
+                Quantity::from_raw(liquid, preview.input.decimals())
+            ))
+            .into());
+        }
+
+        // The slippage is already in the limit price
+        let response = ctx
+            .market
+            .get_instance()
+            .methods()
+            .fulfill_order_many(
+                preview.amount.raw(),
+                preview.side.clone(),
+                preview.limit_type.clone(),
+                preview.limit_price.raw(),
+                0,
+                preview.orders.clone(),
+            )
+            .with_contract_ids(&ctx.contract_ids())
+            .call()
+            .await?;
+        let after = ctx.market.account(user).await?.value;
+
+        let (spent, received) = match preview.side {
+            OrderType::Buy => (
+                info.quote
+                    .quantity(before.liquid.quote.saturating_sub(after.liquid.quote)),
+                info.base
+                    .quantity(after.liquid.base.saturating_sub(before.liquid.base)),
+            ),
+            OrderType::Sell => (
+                info.base
+                    .quantity(before.liquid.base.saturating_sub(after.liquid.base)),
+                info.quote
+                    .quantity(after.liquid.quote.saturating_sub(before.liquid.quote)),
+            ),
+        };
+        Ok(SwapResult {
+            preview: preview.clone(),
+            tx_id: response.tx_id.map(|id| format!("0x{}", id)),
+            spent,
+            received,
+        })
+    }
+
+    /// Dry-runs the `fulfill_order_many` that `execute` sends. A balance that
+    /// falls short shows up as the simulation's error.
+    pub async fn simulate(&self, preview: &SwapPreview) -> Result<Simulation> {
+        let ctx = self.ctx;
+        self.check(preview)?;
+        Simulator::new(ctx)
+            .call(
+                ctx.market
+                    .get_instance()
+                    .methods()
+                    .fulfill_order_many(
+                        preview.amount.raw(),
+                        preview.side.clone(),
+                        preview.limit_type.clone(),
+                        preview.limit_price.raw(),
+                        0,
+                        preview.orders.clone(),
+                    )
+                    .with_contract_ids(&ctx.contract_ids()),
+            )
+            .await
+    }
+
+    /// Refuses swaps that would take nothing, or fill-or-kill swaps that would
+    /// not fill completely.
+    fn check(&self, preview: &SwapPreview) -> Result<()> {
+        let info = &self.ctx.market_info;
+        if preview.fill.is_zero() {
+            return Err(SparkError::NotMatchable(format!(
+                "nothing within {} to take",
+                preview.limit_price
+            ))
+            .into());
+        }
+        if preview.limit_type == LimitType::FOK && !preview.is_complete() {
+            return Err(SparkError::NotMatchable(format!(
+                "only {} of {} {} fill within {}",
+                preview.fill, preview.amount, info.base.symbol, preview.limit_price
+            ))
+            .into());
+        }
+        Ok(())
+    }
+}
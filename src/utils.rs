@@ -0,0 +1,60 @@
+use std::{env, str::FromStr};
+
+use anyhow::{anyhow, Result};
+use fuels::types::{Address, AssetId, Bits256, ContractId, Identity};
+
+/// Parses a `0x`-prefixed (or bare) hex string into a `Bits256`, e.g. an order id.
+pub fn bits256_from_hex_str(hex_str: &str) -> Result<Bits256> {
+    let hex_str = hex_str.strip_prefix("0x").unwrap_or(hex_str);
+
+    let mut bytes = [0u8; 32];
+    hex::decode_to_slice(hex_str, &mut bytes)?;
+    Ok(Bits256(bytes))
+}
+
+/// Formats a `Bits256` as a `0x`-prefixed hex string.
+pub fn hex_str_from_bits256(bits: &Bits256) -> String {
+    format!("0x{}", hex::encode(bits.0))
+}
+
+pub fn parse_asset_id(asset_str: &str) -> Result<AssetId> {
+    AssetId::from_str(asset_str).map_err(|e| anyhow!("Invalid Asset ID {}: {}", asset_str, e))
+}
+
+pub fn parse_contract_id(contract_str: &str) -> Result<ContractId> {
+    ContractId::from_str(contract_str)
+        .map_err(|e| anyhow!("Invalid Contract ID {}: {}", contract_str, e))
+}
+
+/// Parses a wallet address into the `Identity` used by `account` and `user_orders`.
+pub fn parse_identity(address_str: &str) -> Result<Identity> {
+    let address = Address::from_str(address_str)
+        .map_err(|e| anyhow!("Invalid address {}: {}", address_str, e))?;
+    Ok(Identity::Address(address))
+}
+
+/// Formats an `Identity` as a `0x`-prefixed address or contract id.
+pub fn identity_str(identity: &Identity) -> String {
+    match identity {
+        Identity::Address(address) => format!("0x{}", address),
+        Identity::ContractId(contract_id) => format!("0x{}", contract_id),
+    }
+}
+
+/// Whether a script should simulate rather than send: `--dry-run` is among its
+/// arguments, or `SPARK_DRY_RUN` is set to anything but a false value, as for
+/// the `spark` CLI.
+pub fn dry_run_requested() -> bool {
+    env::args().skip(1).any(|arg| arg == "--dry-run")
+        || env::var("SPARK_DRY_RUN").is_ok_and(|value| {
+            !matches!(
+                value.trim().to_ascii_lowercase().as_str(),
+                "" | "0" | "n" | "no" | "f" | "false" | "off"
+            )
+        })
+}
+
+/// The first argument of a script that is not a `--` flag, e.g. its market.
+pub fn first_arg() -> Option<String> {
+    env::args().skip(1).find(|arg| !arg.starts_with("--"))
+}
@@ -0,0 +1,420 @@
+//! Checks orders against the market's rules before they cost gas.
+//!
+//! The market rejects orders below its minimum size or price, and an order
+//! whose base or quote its liquid balance cannot cover. `OrderValidator`
+//! repeats those checks locally, adds tick and lot sizes on top, and either
+//! rounds an order onto them or rejects it with every reason at once.
+
+use std::fmt;
+
+use anyhow::Result;
+use spark_market_sdk::OrderType;
+
+use crate::{
+    amount::{Price, Quantity, Rounding},
+    error::SparkError,
+    fees::FeeSchedule,
+    maker::DesiredOrder,
+    market::MarketInfo,
+    SparkContext,
+};
+
+/// Limits an order has to respect.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OrderRules {
+    pub min_size: Quantity,
+    pub min_price: Price,
+    /// Prices must be a multiple of this.
+    pub tick: Price,
+    /// Amounts must be a multiple of this.
+    pub lot: Quantity,
+}
+
+impl OrderRules {
+    /// The market's minimums, with a tick and lot of one base unit.
+    pub fn from_market(market: &MarketInfo) -> Self {
+        Self {
+            min_size: market.base.quantity(market.min_order_size),
+            min_price: market.price(market.min_order_price),
+            tick: market.price(1),
+            lot: market.base.quantity(1),
+        }
+    }
+}
+
+/// What to do with an order off the tick or lot size.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OnViolation {
+    /// Reject it like any other violation.
+    #[default]
+    Reject,
+    /// Round the price away from the book (buys down, sells up) and the amount
+    /// down. Orders still breaking a rule afterwards are rejected.
+    Round,
+}
+
+/// One reason an order breaks the rules.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Violation {
+    /// Price or amount given with other decimals than the market uses.
+    WrongDecimals {
+        field: &'static str,
+        decimals: u32,
+        expected: u32,
+    },
+    ZeroAmount,
+    BelowMinSize {
+        amount: Quantity,
+        min: Quantity,
+    },
+    BelowMinPrice {
+        price: Price,
+        min: Price,
+    },
+    OffTick {
+        price: Price,
+        tick: Price,
+    },
+    OffLot {
+        amount: Quantity,
+        lot: Quantity,
+    },
+    /// Amount and price are so small the order is worth no quote at all.
+    ZeroValue,
+    /// The liquid balance of `symbol` is short of what the order locks.
+    InsufficientBalance {
+        symbol: String,
+        needed: Quantity,
+        liquid: Quantity,
+    },
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Violation::WrongDecimals {
+                field,
+                decimals,
+                expected,
+            } => write!(
+                f,
+                "{} has {} decimals, the market uses {}",
+                field, decimals, expected
+            ),
+            Violation::ZeroAmount => write!(f, "amount is zero"),
+            Violation::BelowMinSize { amount, min } => {
+                write!(f, "amount {} is below the minimum of {}", amount, min)
+            }
+            Violation::BelowMinPrice { price, min } => {
+                write!(f, "price {} is below the minimum of {}", price, min)
+            }
+            Violation::OffTick { price, tick } => {
+                write!(f, "price {} is not a multiple of the tick {}", price, tick)
+            }
+            Violation::OffLot { amount, lot } => {
+                write!(f, "amount {} is not a multiple of the lot {}", amount, lot)
+            }
+            Violation::ZeroValue => write!(f, "order is worth less than one quote unit"),
+            Violation::InsufficientBalance {
+                symbol,
+                needed,
+                liquid,
+            } => write!(
+                f,
+                "needs {} {} but only {} is liquid",
+                needed, symbol, liquid
+            ),
+        }
+    }
+}
+
+/// An order that passed, possibly rounded, with what it will lock.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidOrder {
+    pub order: DesiredOrder,
+    /// Tick and lot violations that were fixed by rounding.
+    pub rounded: Vec<Violation>,
+    /// Base locked by a sell.
+    pub locked_base: Quantity,
+    /// Quote locked by a buy, fee reserve included.
+    pub locked_quote: Quantity,
+    /// Protocol and matcher fees locked on top of a buy's quote value.
+    pub fee_reserve: Quantity,
+}
+
+/// An order that breaks the rules, with every reason found.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rejected {
+    pub order: DesiredOrder,
+    pub violations: Vec<Violation>,
+}
+
+impl fmt::Display for Rejected {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let reasons: Vec<_> = self.violations.iter().map(|v| v.to_string()).collect();
+        write!(
+            f,
+            "{:?} {} at {} rejected: {}",
+            self.order.side,
+            self.order.amount,
+            self.order.price,
+            reasons.join("; ")
+        )
+    }
+}
+
+impl std::error::Error for Rejected {}
+
+impl From<&Rejected> for SparkError {
+    /// Sorted by the first violation, as the market would have reverted.
+    fn from(rejected: &Rejected) -> Self {
+        let detail = rejected.to_string();
+        match rejected.violations.first() {
+            Some(Violation::InsufficientBalance { .. }) => SparkError::InsufficientBalance(detail),
+            Some(Violation::BelowMinPrice { .. }) => SparkError::PriceTooLow(detail),
+            Some(Violation::ZeroAmount | Violation::BelowMinSize { .. } | Violation::ZeroValue) => {
+                SparkError::OrderTooSmall(detail)
+            }
+            _ => SparkError::InvalidValue(detail),
+        }
+    }
+}
+
+/// Liquid balances orders are paid from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Liquid {
+    pub base: Quantity,
+    pub quote: Quantity,
+}
+
+impl Liquid {
+    /// The wallet's liquid balances in the context's market.
+    pub async fn load(ctx: &SparkContext) -> Result<Self> {
+        let info = &ctx.market_info;
+        let account = ctx.market.account(ctx.wallet_id()).await?.value;
+        Ok(Self {
+            base: info.base.quantity(account.liquid.base),
+            quote: info.quote.quantity(account.liquid.quote),
+        })
+    }
+}
+
+/// Checks orders against `OrderRules` and, when given, a liquid balance:
+///
+/// ```ignore
+/// let validator = OrderValidator::for_wallet(&ctx).await?.on_violation(OnViolation::Round);
+/// let checked = validator.check(&order, Some(&Liquid::load(&ctx).await?))?;
+/// ```
+#[derive(Debug, Clone)]
+pub struct OrderValidator<'a> {
+    market: &'a MarketInfo,
+    rules: OrderRules,
+    on_violation: OnViolation,
+    fees: FeeSchedule,
+    fee_bps: u64,
+}
+
+impl<'a> OrderValidator<'a> {
+    /// Uses the market's rules and reserves fees at its highest tier rate.
+    pub fn new(market: &'a MarketInfo) -> Self {
+        let fees = FeeSchedule::from_market(market);
+        Self {
+            market,
+            rules: OrderRules::from_market(market),
+            on_violation: OnViolation::default(),
+            fee_bps: fees.max_reserve_bps(),
+            fees,
+        }
+    }
+
+    /// Like `new`, but reserves fees at the rate of the wallet's current tier.
+    pub async fn for_wallet(ctx: &'a SparkContext) -> Result<Self> {
+        let (maker, taker) = ctx.market.protocol_fee_user(ctx.wallet_id()).await?.value;
+        Ok(Self::new(&ctx.market_info).fee_bps(maker.max(taker)))
+    }
+
+    pub fn rules(mut self, rules: OrderRules) -> Self {
+        self.rules = rules;
+        self
+    }
+
+    pub fn on_violation(mut self, on_violation: OnViolation) -> Self {
+        self.on_violation = on_violation;
+        self
+    }
+
+    /// Protocol fee rate in basis points reserved on buys.
+    pub fn fee_bps(mut self, fee_bps: u64) -> Self {
+        self.fee_bps = fee_bps;
+        self
+    }
+
+    /// Checks one order, and that `liquid` covers it when given.
+    pub fn check(
+        &self,
+        order: &DesiredOrder,
+        liquid: Option<&Liquid>,
+    ) -> Result<ValidOrder, Rejected> {
+        let reject = |violations| Rejected {
+            order: order.clone(),
+            violations,
+        };
+        let market = self.market;
+        let (rules, round) = (&self.rules, self.on_violation == OnViolation::Round);
+
+        let decimals = [
+            ("price", order.price.decimals(), market.price_decimals),
+            ("amount", order.amount.decimals(), market.base.decimals),
+        ];
+        let wrong: Vec<_> = decimals
+            .into_iter()
+            .filter(|(_, decimals, expected)| decimals != expected)
+            .map(|(field, decimals, expected)| Violation::WrongDecimals {
+                field,
+                decimals,
+                expected,
+            })
+            .collect();
+        if !wrong.is_empty() {
+            return Err(reject(wrong));
+        }
+
+        let mut violations = Vec::new();
+        let mut rounded = Vec::new();
+        let mut valid = order.clone();
+
+        if rules.tick.raw() > 1 && order.price.raw() % rules.tick.raw() != 0 {
+            let violation = Violation::OffTick {
+                price: order.price,
+                tick: rules.tick,
+            };
+            if round {
+                let away = match order.side {
+                    OrderType::Buy => Rounding::Down,
+                    OrderType::Sell => Rounding::Up,
+                };
+                valid.price = order
+                    .price
+                    .round_to_tick(rules.tick.raw(), away)
+                    .map_err(|_| reject(vec![violation.clone()]))?;
+                rounded.push(violation);
+            } else {
+                violations.push(violation);
+            }
+        }
+        if rules.lot.raw() > 1 && order.amount.raw() % rules.lot.raw() != 0 {
+            let violation = Violation::OffLot {
+                amount: order.amount,
+                lot: rules.lot,
+            };
+            if round {
+                valid.amount = order
+                    .amount
+                    .round_to_step(rules.lot.raw(), Rounding::Down)
+                    .map_err(|_| reject(vec![violation.clone()]))?;
+                rounded.push(violation);
+            } else {
+                violations.push(violation);
+            }
+        }
+
+        if valid.amount.is_zero() {
+            violations.push(Violation::ZeroAmount);
+        } else if valid.amount < rules.min_size {
+            violations.push(Violation::BelowMinSize {
+                amount: valid.amount,
+                min: rules.min_size,
+            });
+        }
+        if valid.price < rules.min_price {
+            violations.push(Violation::BelowMinPrice {
+                price: valid.price,
+                min: rules.min_price,
+            });
+        }
+
+        let value = market
+            .quote_for(valid.amount, valid.price, Rounding::Down)
+            .map_err(|_| reject(vec![Violation::ZeroValue]))?;
+        if value.is_zero() && !valid.amount.is_zero() {
+            violations.push(Violation::ZeroValue);
+        }
+        if !violations.is_empty() {
+            return Err(reject(violations));
+        }
+
+        let (locked_base, locked_quote, fee_reserve) = self
+            .locks(&valid)
+            .map_err(|_| reject(vec![Violation::ZeroValue]))?;
+        if let Some(liquid) = liquid {
+            let needs = [
+                (&market.base.symbol, locked_base, liquid.base),
+                (&market.quote.symbol, locked_quote, liquid.quote),
+            ];
+            for (symbol, needed, liquid) in needs {
+                if needed.raw() > liquid.raw() {
+                    violations.push(Violation::InsufficientBalance {
+                        symbol: symbol.clone(),
+                        needed,
+                        liquid,
+                    });
+                }
+            }
+        }
+        if !violations.is_empty() {
+            return Err(reject(violations));
+        }
+
+        Ok(ValidOrder {
+            order: valid,
+            rounded,
+            locked_base,
+            locked_quote,
+            fee_reserve,
+        })
+    }
+
+    /// Checks orders in turn against one balance: each accepted order uses up
+    /// what it locks before the next one is checked.
+    pub fn check_all(
+        &self,
+        orders: &[DesiredOrder],
+        liquid: Option<Liquid>,
+    ) -> Vec<Result<ValidOrder, Rejected>> {
+        let mut liquid = liquid;
+        orders
+            .iter()
+            .map(|order| {
+                let checked = self.check(order, liquid.as_ref())?;
+                if let Some(left) = liquid.as_mut() {
+                    left.base = left
+                        .base
+                        .saturating_sub(checked.locked_base)
+                        .unwrap_or(left.base);
+                    left.quote = left
+                        .quote
+                        .saturating_sub(checked.locked_quote)
+                        .unwrap_or(left.quote);
+                }
+                Ok(checked)
+            })
+            .collect()
+    }
+
+    /// Base and quote an order locks, and the fee reserve within the quote.
+    /// Sells lock their base; buys lock their quote value plus the protocol
+    /// fee on it and the matcher fee.
+    pub fn locks(&self, order: &DesiredOrder) -> Result<(Quantity, Quantity, Quantity)> {
+        let market = self.market;
+        let zero_base = Quantity::zero(market.base.decimals);
+        let zero_quote = Quantity::zero(market.quote.decimals);
+        match order.side {
+            OrderType::Sell => Ok((order.amount, zero_quote, zero_quote)),
+            OrderType::Buy => {
+                let value = order.quote_value(market)?;
+                let fee = self.fees.reserve(value, self.fee_bps)?;
+                Ok((zero_base, value.checked_add(fee)?, fee))
+            }
+        }
+    }
+}
@@ -0,0 +1,755 @@
+//! Stable, serializable views of market state, and their rendering as JSON, CSV or a table.
+//!
+//! Views are flat so that every one of them maps onto CSV columns. Amounts are given
+//! both in human units (exact decimal strings) and in raw base units.
+
+use std::{fmt, str::FromStr};
+
+use anyhow::{anyhow, Result};
+use fuels::types::{AssetId, Bits256, Identity};
+use serde::Serialize;
+use serde_json::{Map, Value};
+use spark_market_sdk::OrderType;
+
+use crate::{
+    amount::Quantity,
+    assets::AssetInfo,
+    fees::{FeeSchedule, FeeTier, OrderFees},
+    indexer::{MarketEvent, TradeRecord},
+    maker::DesiredOrder,
+    market::MarketInfo,
+    orderbook::OrderBook,
+    router::{Leg, LegReport},
+    simulate::Simulation,
+    swap::{SwapPreview, SwapResult},
+    utils::{hex_str_from_bits256, identity_str},
+    validate::Liquid,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Json,
+    Csv,
+    Table,
+}
+
+impl FromStr for Format {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "json" => Ok(Format::Json),
+            "csv" => Ok(Format::Csv),
+            "table" => Ok(Format::Table),
+            other => Err(anyhow!(
+                "Unknown format {}, expected json, csv or table",
+                other
+            )),
+        }
+    }
+}
+
+impl fmt::Display for Format {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Format::Json => "json",
+            Format::Csv => "csv",
+            Format::Table => "table",
+        })
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct OrderView {
+    pub id: String,
+    pub side: String,
+    pub amount: String,
+    pub amount_raw: u64,
+    pub price: String,
+    pub price_raw: u64,
+    pub owner: String,
+}
+
+impl OrderView {
+    pub fn new(
+        market: &MarketInfo,
+        id: &Bits256,
+        order_type: &OrderType,
+        amount: u64,
+        price: u64,
+        owner: &Identity,
+    ) -> Self {
+        Self {
+            id: hex_str_from_bits256(id),
+            side: side_str(order_type).to_string(),
+            amount: market.base.quantity(amount).to_string(),
+            amount_raw: amount,
+            price: market.price(price).to_string(),
+            price_raw: price,
+            owner: identity_str(owner),
+        }
+    }
+}
+
+/// One asset of a market account.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct BalanceView {
+    pub user: String,
+    pub asset: String,
+    pub asset_id: String,
+    pub liquid: String,
+    pub liquid_raw: u64,
+    pub locked: String,
+    pub locked_raw: u64,
+}
+
+impl BalanceView {
+    pub fn new(user: &Identity, asset: &AssetInfo, liquid: u64, locked: u64) -> Self {
+        Self {
+            user: identity_str(user),
+            asset: asset.symbol.clone(),
+            asset_id: format!("0x{}", asset.asset_id),
+            liquid: asset.quantity(liquid).to_string(),
+            liquid_raw: liquid,
+            locked: asset.quantity(locked).to_string(),
+            locked_raw: locked,
+        }
+    }
+}
+
+/// One protocol fee tier. The matcher fee is repeated on every row to keep the view flat.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct FeeTierView {
+    pub volume_threshold: String,
+    pub volume_threshold_raw: u64,
+    pub maker_fee_bps: u64,
+    pub taker_fee_bps: u64,
+    pub matcher_fee: String,
+    pub matcher_fee_raw: u64,
+    /// Whether this is the tier the view was made for, e.g. a user's.
+    pub current: bool,
+}
+
+impl FeeTierView {
+    /// Fee tiers of a market; volumes and the matcher fee are in quote units.
+    pub fn from_market(market: &MarketInfo) -> Vec<Self> {
+        Self::from_schedule(&FeeSchedule::from_market(market), None)
+    }
+
+    /// Tiers lowest threshold first, marking `current`.
+    pub fn from_schedule(schedule: &FeeSchedule, current: Option<FeeTier>) -> Vec<Self> {
+        let matcher_fee = schedule.matcher_fee();
+        schedule
+            .tiers()
+            .iter()
+            .map(|tier| Self {
+                volume_threshold: tier.volume_threshold.to_string(),
+                volume_threshold_raw: tier.volume_threshold.raw(),
+                maker_fee_bps: tier.maker_bps,
+                taker_fee_bps: tier.taker_bps,
+                matcher_fee: matcher_fee.to_string(),
+                matcher_fee_raw: matcher_fee.raw(),
+                current: current == Some(*tier),
+            })
+            .collect()
+    }
+}
+
+/// Fees of a prospective order and what opening it locks, in quote units.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct OrderFeesView {
+    pub side: String,
+    pub amount: String,
+    pub price: String,
+    pub value: String,
+    /// Quote volume the user has traded, when known.
+    pub volume: Option<String>,
+    pub maker_fee_bps: u64,
+    pub taker_fee_bps: u64,
+    pub maker_fee: String,
+    pub taker_fee: String,
+    pub matcher_fee: String,
+    pub reserve: String,
+    pub reserve_raw: u64,
+    pub locked_quote: String,
+    pub locked_quote_raw: u64,
+    pub liquid_base: String,
+    pub liquid_quote: String,
+    /// Whether the liquid balances cover what the order locks: the quote for a
+    /// buy, the base amount for a sell.
+    pub covered: bool,
+}
+
+impl OrderFeesView {
+    pub fn new(
+        market: &MarketInfo,
+        order: &DesiredOrder,
+        volume: Option<Quantity>,
+        fees: &OrderFees,
+        liquid: &Liquid,
+    ) -> Self {
+        let base_covered = match order.side {
+            OrderType::Buy => true,
+            OrderType::Sell => order.amount.raw() <= liquid.base.raw(),
+        };
+        Self {
+            side: side_str(&order.side).to_string(),
+            amount: format!("{} {}", order.amount, market.base.symbol),
+            price: order.price.to_string(),
+            value: fees.value.to_string(),
+            volume: volume.map(|volume| volume.to_string()),
+            maker_fee_bps: fees.tier.maker_bps,
+            taker_fee_bps: fees.tier.taker_bps,
+            maker_fee: fees.maker_fee.to_string(),
+            taker_fee: fees.taker_fee.to_string(),
+            matcher_fee: fees.matcher_fee.to_string(),
+            reserve: fees.reserve.to_string(),
+            reserve_raw: fees.reserve.raw(),
+            locked_quote: fees.locked_quote.to_string(),
+            locked_quote_raw: fees.locked_quote.raw(),
+            liquid_base: liquid.base.to_string(),
+            liquid_quote: liquid.quote.to_string(),
+            covered: base_covered && fees.locked_quote.raw() <= liquid.quote.raw(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct MarketView {
+    pub name: String,
+    pub contract_id: String,
+    pub base: String,
+    pub base_asset_id: String,
+    pub base_decimals: u32,
+    pub quote: String,
+    pub quote_asset_id: String,
+    pub quote_decimals: u32,
+    pub price_decimals: u32,
+    pub version: u32,
+    pub implementation: Option<String>,
+    pub registered: bool,
+}
+
+impl From<&MarketInfo> for MarketView {
+    fn from(market: &MarketInfo) -> Self {
+        Self {
+            name: market.name(),
+            contract_id: format!("0x{}", market.contract_id),
+            base: market.base.symbol.clone(),
+            base_asset_id: format!("0x{}", market.base.asset_id),
+            base_decimals: market.base.decimals,
+            quote: market.quote.symbol.clone(),
+            quote_asset_id: format!("0x{}", market.quote.asset_id),
+            quote_decimals: market.quote.decimals,
+            price_decimals: market.price_decimals,
+            version: market.version,
+            implementation: market.implementation.map(|id| format!("0x{}", id)),
+            registered: market.registered,
+        }
+    }
+}
+
+/// One aggregated price level of an `OrderBook`.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct BookLevelView {
+    pub side: String,
+    pub price: String,
+    pub price_raw: u64,
+    pub amount: String,
+    pub amount_raw: u64,
+    pub depth: String,
+    pub depth_raw: u64,
+    pub orders: usize,
+}
+
+impl BookLevelView {
+    /// Asks from the highest price down, then bids, as in `OrderBook::ladder`.
+    pub fn from_book(book: &OrderBook, depth: usize) -> Vec<Self> {
+        let asks = book
+            .asks
+            .iter()
+            .take(depth)
+            .rev()
+            .map(|l| (&OrderType::Sell, l));
+        let bids = book.bids.iter().take(depth).map(|l| (&OrderType::Buy, l));
+        asks.chain(bids)
+            .map(|(side, level)| Self {
+                side: side_str(side).to_string(),
+                price: level.price.to_string(),
+                price_raw: level.price.raw(),
+                amount: level.amount.to_string(),
+                amount_raw: level.amount.raw(),
+                depth: level.depth.to_string(),
+                depth_raw: level.depth.raw(),
+                orders: level.orders,
+            })
+            .collect()
+    }
+}
+
+/// An indexed fill in market units.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct TradeView {
+    pub block_height: u32,
+    pub tx_id: String,
+    pub size: String,
+    pub size_raw: u64,
+    pub price: String,
+    pub price_raw: u64,
+    pub seller: String,
+    pub buyer: String,
+    pub sell_order_id: String,
+    pub buy_order_id: String,
+}
+
+impl TradeView {
+    pub fn new(market: &MarketInfo, trade: &TradeRecord) -> Self {
+        Self {
+            block_height: trade.block_height,
+            tx_id: trade.tx_id.clone(),
+            size: market.base.quantity(trade.size).to_string(),
+            size_raw: trade.size,
+            price: market.price(trade.price).to_string(),
+            price_raw: trade.price,
+            seller: trade.seller.clone(),
+            buyer: trade.buyer.clone(),
+            sell_order_id: trade.sell_order_id.clone(),
+            buy_order_id: trade.buy_order_id.clone(),
+        }
+    }
+}
+
+/// A deposit or withdrawal.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct TransferView {
+    pub action: String,
+    pub asset: String,
+    pub amount: String,
+    pub amount_raw: u64,
+}
+
+impl TransferView {
+    pub fn new(action: &str, asset: &AssetInfo, amount: u64) -> Self {
+        Self {
+            action: action.to_string(),
+            asset: asset.symbol.clone(),
+            amount: asset.quantity(amount).to_string(),
+            amount_raw: amount,
+        }
+    }
+}
+
+/// An order touched by a transaction, e.g. cancelled or matched.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct OrderTxView {
+    pub action: String,
+    pub order_id: String,
+    pub tx_id: Option<String>,
+}
+
+impl OrderTxView {
+    pub fn new(action: &str, order_id: &Bits256, tx_id: Option<String>) -> Self {
+        Self {
+            action: action.to_string(),
+            order_id: hex_str_from_bits256(order_id),
+            tx_id,
+        }
+    }
+}
+
+/// One leg of a planned or executed route. Planned legs carry the worst price
+/// they walk to, executed ones the transaction.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct RouteLegView {
+    pub route: usize,
+    pub market: String,
+    pub side: String,
+    pub pay: String,
+    pub pay_raw: u64,
+    pub receive: String,
+    pub receive_raw: u64,
+    pub limit_price: Option<String>,
+    pub orders: usize,
+    pub tx_id: Option<String>,
+}
+
+impl RouteLegView {
+    pub fn planned(route: usize, leg: &Leg) -> Self {
+        Self {
+            route,
+            market: format!("0x{}", leg.market),
+            side: side_str(&leg.side).to_string(),
+            pay: format!("{} {}", leg.input, leg.pay.symbol),
+            pay_raw: leg.input.raw(),
+            receive: format!("{} {}", leg.output, leg.receive.symbol),
+            receive_raw: leg.output.raw(),
+            limit_price: Some(leg.limit_price.to_string()),
+            orders: leg.orders.len(),
+            tx_id: None,
+        }
+    }
+
+    pub fn executed(leg: &LegReport) -> Self {
+        Self {
+            route: leg.route,
+            market: format!("0x{}", leg.market),
+            side: side_str(&leg.side).to_string(),
+            pay: format!("{} {}", leg.spent, leg.pay.symbol),
+            pay_raw: leg.spent.raw(),
+            receive: format!("{} {}", leg.received, leg.receive.symbol),
+            receive_raw: leg.received.raw(),
+            limit_price: None,
+            orders: 0,
+            tx_id: leg.tx_id.clone(),
+        }
+    }
+}
+
+/// What a swap is expected to do and, once executed, what it did.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SwapView {
+    pub side: String,
+    pub limit_type: String,
+    pub amount: String,
+    pub fill: String,
+    pub fill_raw: u64,
+    pub complete: bool,
+    pub input: String,
+    pub input_raw: u64,
+    pub output: String,
+    pub output_raw: u64,
+    pub fees: String,
+    pub best_price: String,
+    pub limit_price: String,
+    pub average_price: f64,
+    pub price_impact_bps: f64,
+    pub orders: usize,
+    pub tx_id: Option<String>,
+    pub spent: Option<String>,
+    pub received: Option<String>,
+}
+
+impl SwapView {
+    pub fn preview(market: &MarketInfo, preview: &SwapPreview) -> Self {
+        let (input, output) = match preview.side {
+            OrderType::Buy => (&market.quote, &market.base),
+            OrderType::Sell => (&market.base, &market.quote),
+        };
+        Self {
+            side: side_str(&preview.side).to_string(),
+            limit_type: format!("{:?}", preview.limit_type),
+            amount: format!("{} {}", preview.amount, market.base.symbol),
+            fill: format!("{} {}", preview.fill, market.base.symbol),
+            fill_raw: preview.fill.raw(),
+            complete: preview.is_complete(),
+            input: format!("{} {}", preview.input, input.symbol),
+            input_raw: preview.input.raw(),
+            output: format!("{} {}", preview.output, output.symbol),
+            output_raw: preview.output.raw(),
+            fees: format!("{} {}", preview.fees, market.quote.symbol),
+            best_price: preview.best_price.to_string(),
+            limit_price: preview.limit_price.to_string(),
+            average_price: preview.average_price,
+            price_impact_bps: preview.price_impact_bps,
+            orders: preview.orders.len(),
+            tx_id: None,
+            spent: None,
+            received: None,
+        }
+    }
+
+    pub fn executed(market: &MarketInfo, result: &SwapResult) -> Self {
+        let (input, output) = match result.preview.side {
+            OrderType::Buy => (&market.quote, &market.base),
+            OrderType::Sell => (&market.base, &market.quote),
+        };
+        Self {
+            tx_id: result.tx_id.clone(),
+            spent: Some(format!("{} {}", result.spent, input.symbol)),
+            received: Some(format!("{} {}", result.received, output.symbol)),
+            ..Self::preview(market, &result.preview)
+        }
+    }
+}
+
+/// One expected effect of a simulated transaction: an event it would log, a
+/// balance it would change, its gas, or why it would revert.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct EffectView {
+    /// Position of the transaction among those simulated.
+    pub tx: usize,
+    /// `open`, `cancel`, `trade`, `deposit`, `withdraw`, `balance`, `gas` or `revert`.
+    pub effect: String,
+    pub market: Option<String>,
+    pub user: Option<String>,
+    pub order_id: Option<String>,
+    pub side: Option<String>,
+    pub asset: Option<String>,
+    /// Amount moved, or on `balance` rows the change of the liquid balance.
+    pub amount: Option<String>,
+    pub amount_raw: Option<i128>,
+    pub price: Option<String>,
+    pub price_raw: Option<u64>,
+    /// Change of the locked balance, on `balance` rows.
+    pub locked: Option<String>,
+    pub locked_raw: Option<i128>,
+    pub detail: Option<String>,
+}
+
+impl EffectView {
+    fn new(tx: usize, effect: &str) -> Self {
+        Self {
+            tx,
+            effect: effect.to_string(),
+            market: None,
+            user: None,
+            order_id: None,
+            side: None,
+            asset: None,
+            amount: None,
+            amount_raw: None,
+            price: None,
+            price_raw: None,
+            locked: None,
+            locked_raw: None,
+            detail: None,
+        }
+    }
+
+    /// Every effect of the `tx`th simulated transaction: its events in log
+    /// order, then the balance changes, then the gas.
+    pub fn from_simulation(tx: usize, simulation: &Simulation) -> Vec<Self> {
+        let mut rows = Vec::new();
+        if let Some(error) = &simulation.error {
+            rows.push(Self {
+                detail: Some(error.to_string()),
+                ..Self::new(tx, "revert")
+            });
+        }
+
+        for (contract_id, event) in &simulation.events {
+            let Some(market) = simulation.market(*contract_id) else {
+                continue;
+            };
+            let row = Self {
+                market: Some(market.name()),
+                ..Self::new(tx, "")
+            };
+            rows.push(match event {
+                MarketEvent::Open(e) => Self {
+                    effect: "open".to_string(),
+                    user: Some(identity_str(&e.user)),
+                    order_id: Some(hex_str_from_bits256(&e.order_id)),
+                    side: Some(side_str(&e.order_type).to_string()),
+                    asset: Some(market.base.symbol.clone()),
+                    amount: Some(market.base.quantity(e.amount).to_string()),
+                    amount_raw: Some(e.amount.into()),
+                    price: Some(market.price(e.price).to_string()),
+                    price_raw: Some(e.price),
+                    ..row
+                },
+                MarketEvent::Cancel(e) => Self {
+                    effect: "cancel".to_string(),
+                    user: Some(identity_str(&e.user)),
+                    order_id: Some(hex_str_from_bits256(&e.order_id)),
+                    ..row
+                },
+                MarketEvent::Trade(e) => Self {
+                    effect: "trade".to_string(),
+                    user: Some(identity_str(&e.order_matcher)),
+                    asset: Some(market.base.symbol.clone()),
+                    amount: Some(market.base.quantity(e.trade_size).to_string()),
+                    amount_raw: Some(e.trade_size.into()),
+                    price: Some(market.price(e.trade_price).to_string()),
+                    price_raw: Some(e.trade_price),
+                    detail: Some(format!(
+                        "buy {} sell {}",
+                        hex_str_from_bits256(&e.base_buy_order_id),
+                        hex_str_from_bits256(&e.base_sell_order_id)
+                    )),
+                    ..row
+                },
+                MarketEvent::Deposit(e) => {
+                    Self::transfer(row, "deposit", market, &e.user, e.asset, e.amount)
+                }
+                MarketEvent::Withdraw(e) => {
+                    Self::transfer(row, "withdraw", market, &e.user, e.asset, e.amount)
+                }
+            });
+        }
+
+        for change in &simulation.balances {
+            let (liquid, locked) = (change.liquid_delta(), change.locked_delta());
+            rows.push(Self {
+                market: simulation.market(change.market).map(MarketInfo::name),
+                user: Some(identity_str(&change.user)),
+                asset: Some(change.asset.symbol.clone()),
+                amount: Some(signed(&change.asset, liquid)),
+                amount_raw: Some(liquid),
+                locked: Some(signed(&change.asset, locked)),
+                locked_raw: Some(locked),
+                detail: Some(format!(
+                    "liquid {} locked {}",
+                    change.asset.quantity(change.liquid_after),
+                    change.asset.quantity(change.locked_after)
+                )),
+                ..Self::new(tx, "balance")
+            });
+        }
+
+        rows.push(Self {
+            amount: simulation.fee.map(|fee| fee.to_string()),
+            amount_raw: simulation.fee.map(|fee| fee.raw().into()),
+            detail: Some(format!("{} gas used", simulation.gas_used)),
+            ..Self::new(tx, "gas")
+        });
+        rows
+    }
+
+    /// A deposit or withdrawal of one of the market's assets.
+    fn transfer(
+        row: Self,
+        effect: &str,
+        market: &MarketInfo,
+        user: &Identity,
+        asset_id: AssetId,
+        amount: u64,
+    ) -> Self {
+        let asset = [&market.base, &market.quote]
+            .into_iter()
+            .find(|asset| asset.asset_id == asset_id);
+        Self {
+            effect: effect.to_string(),
+            user: Some(identity_str(user)),
+            asset: Some(asset.map_or(format!("0x{}", asset_id), |a| a.symbol.clone())),
+            amount: asset.map(|a| a.quantity(amount).to_string()),
+            amount_raw: Some(amount.into()),
+            ..row
+        }
+    }
+}
+
+/// A balance change with its sign, e.g. `-0.5` or `+12`.
+fn signed(asset: &AssetInfo, delta: i128) -> String {
+    let amount = asset.quantity(delta.unsigned_abs() as u64);
+    if delta < 0 {
+        format!("-{}", amount)
+    } else {
+        format!("+{}", amount)
+    }
+}
+
+/// `"buy"` or `"sell"`.
+pub fn side_str(order_type: &OrderType) -> &'static str {
+    match order_type {
+        OrderType::Buy => "buy",
+        OrderType::Sell => "sell",
+    }
+}
+
+/// Renders a list of views. JSON gives an array, CSV and table one row per view.
+/// The columns come from the view type, so CSV has a header even without rows.
+pub fn render<T: Serialize + Default>(rows: &[T], format: Format) -> Result<String> {
+    if format == Format::Json {
+        return Ok(serde_json::to_string_pretty(rows)?);
+    }
+    let columns = columns(&T::default())?;
+    let rows = rows.iter().map(to_object).collect::<Result<Vec<_>>>()?;
+    Ok(render_rows(&columns, &rows, format))
+}
+
+/// Renders a single view. JSON gives an object rather than a one element array.
+pub fn render_one<T: Serialize>(row: &T, format: Format) -> Result<String> {
+    if format == Format::Json {
+        return Ok(serde_json::to_string_pretty(row)?);
+    }
+    Ok(render_rows(&columns(row)?, &[to_object(row)?], format))
+}
+
+fn render_rows(columns: &[String], rows: &[Map<String, Value>], format: Format) -> String {
+    match format {
+        Format::Csv => render_csv(columns, rows),
+        _ => render_table(columns, rows),
+    }
+}
+
+fn columns<T: Serialize>(row: &T) -> Result<Vec<String>> {
+    Ok(to_object(row)?.keys().cloned().collect())
+}
+
+fn to_object<T: Serialize>(row: &T) -> Result<Map<String, Value>> {
+    match serde_json::to_value(row)? {
+        Value::Object(map) => Ok(map),
+        other => Err(anyhow!("Expected a flat view, got {}", other)),
+    }
+}
+
+fn cell(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+fn render_csv(columns: &[String], rows: &[Map<String, Value>]) -> String {
+    let mut lines = vec![columns
+        .iter()
+        .map(|k| csv_field(k))
+        .collect::<Vec<_>>()
+        .join(",")];
+    for row in rows {
+        lines.push(
+            row.values()
+                .map(|v| csv_field(&cell(v)))
+                .collect::<Vec<_>>()
+                .join(","),
+        );
+    }
+    lines.join("\n")
+}
+
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+fn render_table(headers: &[String], rows: &[Map<String, Value>]) -> String {
+    if rows.is_empty() {
+        return "No results".to_string();
+    }
+    let cells: Vec<Vec<String>> = rows
+        .iter()
+        .map(|row| row.values().map(cell).collect())
+        .collect();
+
+    let widths: Vec<usize> = headers
+        .iter()
+        .enumerate()
+        .map(|(i, header)| {
+            cells
+                .iter()
+                .map(|row| row[i].len())
+                .chain([header.len()])
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+
+    let format_row = |row: &[String]| {
+        row.iter()
+            .zip(&widths)
+            .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+            .collect::<Vec<_>>()
+            .join("  ")
+            .trim_end()
+            .to_string()
+    };
+
+    let mut lines = vec![format_row(headers)];
+    lines.extend(cells.iter().map(|row| format_row(row)));
+    lines.join("\n")
+}
@@ -0,0 +1,172 @@
+use anyhow::Result;
+use spark_examples::amount::{Price, Quantity, Rounding};
+
+const MODES: [Rounding; 3] = [Rounding::Down, Rounding::Up, Rounding::Nearest];
+
+#[test]
+fn parses_and_displays_exact_amounts() -> Result<()> {
+    let usdc = Quantity::from_decimal_str("0.0015", 6, Rounding::Down)?;
+    assert_eq!(usdc.raw(), 1_500);
+    assert_eq!(usdc.to_string(), "0.001500");
+
+    // Every decimal is printed, so the text parses back to the same amount
+    for (value, decimals) in [("1234.5", 9), ("0.000000001", 9), ("42", 0), ("7", 6)] {
+        let quantity = Quantity::from_decimal_str(value, decimals, Rounding::Down)?;
+        let again = Quantity::from_decimal_str(&quantity.to_string(), decimals, Rounding::Down)?;
+        assert_eq!(again, quantity);
+
+        let price = Price::from_decimal_str(value, decimals, Rounding::Down)?;
+        let again = Price::from_decimal_str(&price.to_string(), decimals, Rounding::Down)?;
+        assert_eq!(again, price);
+    }
+    assert_eq!(Quantity::from_raw(42, 0).to_string(), "42");
+    assert_eq!(Price::from_units(3_200, 9)?.to_string(), "3200.000000000");
+
+    assert_eq!(
+        Quantity::from_decimal_str("1_000.25", 2, Rounding::Down)?.raw(),
+        100_025
+    );
+    assert_eq!(
+        Quantity::from_decimal_str(".5", 1, Rounding::Down)?.raw(),
+        5
+    );
+    assert_eq!(Quantity::from_f64(0.1, 6, Rounding::Down)?.raw(), 100_000);
+
+    for invalid in ["", ".", "1.2.3", "-1", "1e5", "abc"] {
+        assert!(
+            Quantity::from_decimal_str(invalid, 6, Rounding::Down).is_err(),
+            "{:?} should not parse",
+            invalid
+        );
+    }
+    assert!(Quantity::from_f64(-1.0, 6, Rounding::Down).is_err());
+    assert!(Quantity::from_f64(f64::NAN, 6, Rounding::Down).is_err());
+    Ok(())
+}
+
+#[test]
+fn parsing_rounds_the_digits_beyond_the_decimals() -> Result<()> {
+    let parse = |value, rounding| -> Result<u64> {
+        Ok(Quantity::from_decimal_str(value, 6, rounding)?.raw())
+    };
+    assert_eq!(parse("0.0000015", Rounding::Down)?, 1);
+    assert_eq!(parse("0.0000015", Rounding::Up)?, 2);
+    assert_eq!(parse("0.0000015", Rounding::Nearest)?, 2);
+    assert_eq!(parse("0.0000014", Rounding::Nearest)?, 1);
+    assert_eq!(parse("0.0000010000001", Rounding::Up)?, 2);
+    // Dropped zeros are not a remainder
+    for rounding in MODES {
+        assert_eq!(parse("0.0000010000", rounding)?, 1);
+    }
+    Ok(())
+}
+
+#[test]
+fn quote_for_rounds_each_way() -> Result<()> {
+    // 0.5 ETH (9 decimals) at 3000 USDC (6 decimals)
+    let price = Price::from_units(3_000, 9)?;
+    let half = Quantity::from_raw(500_000_000, 9);
+    for rounding in MODES {
+        let quote = price.quote_for(half, 6, rounding)?;
+        assert_eq!(quote, Quantity::from_raw(1_500_000_000, 6));
+    }
+
+    // One base unit at 2500 is 2.5 quote units
+    let price = Price::from_units(2_500, 9)?;
+    let unit = Quantity::from_raw(1, 9);
+    assert_eq!(price.quote_for(unit, 6, Rounding::Down)?.raw(), 2);
+    assert_eq!(price.quote_for(unit, 6, Rounding::Up)?.raw(), 3);
+    assert_eq!(price.quote_for(unit, 6, Rounding::Nearest)?.raw(), 3);
+
+    // And at 2400, 2.4
+    let price = Price::from_units(2_400, 9)?;
+    assert_eq!(price.quote_for(unit, 6, Rounding::Nearest)?.raw(), 2);
+    Ok(())
+}
+
+#[test]
+fn base_for_inverts_quote_for() -> Result<()> {
+    let price = Price::from_units(3_000, 9)?;
+    let quote = Quantity::from_units(1_500, 6)?;
+    for rounding in MODES {
+        let base = price.base_for(quote, 9, rounding)?;
+        assert_eq!(base, Quantity::from_raw(500_000_000, 9));
+        assert_eq!(price.quote_for(base, 6, rounding)?, quote);
+    }
+
+    // One quote unit buys a third of a base unit at 3000, half of one at 2000
+    let unit = Quantity::from_raw(1, 6);
+    let price = Price::from_units(3_000, 9)?;
+    assert_eq!(price.base_for(unit, 9, Rounding::Down)?.raw(), 0);
+    assert_eq!(price.base_for(unit, 9, Rounding::Up)?.raw(), 1);
+    assert_eq!(price.base_for(unit, 9, Rounding::Nearest)?.raw(), 0);
+    let price = Price::from_units(2_000, 9)?;
+    assert_eq!(price.base_for(unit, 9, Rounding::Nearest)?.raw(), 1);
+
+    assert!(Price::from_raw(0, 9)
+        .base_for(unit, 9, Rounding::Down)
+        .is_err());
+    Ok(())
+}
+
+#[test]
+fn round_to_tick_rounds_each_way() -> Result<()> {
+    let price = Price::from_raw(1_234, 9);
+    assert_eq!(price.round_to_tick(100, Rounding::Down)?.raw(), 1_200);
+    assert_eq!(price.round_to_tick(100, Rounding::Up)?.raw(), 1_300);
+    assert_eq!(price.round_to_tick(100, Rounding::Nearest)?.raw(), 1_200);
+    assert_eq!(
+        Price::from_raw(1_250, 9)
+            .round_to_tick(100, Rounding::Nearest)?
+            .raw(),
+        1_300
+    );
+    for rounding in MODES {
+        assert_eq!(
+            Price::from_raw(1_200, 9)
+                .round_to_tick(100, rounding)?
+                .raw(),
+            1_200
+        );
+    }
+    assert!(price.round_to_tick(0, Rounding::Down).is_err());
+
+    let lots = Quantity::from_raw(1_234, 6);
+    assert_eq!(lots.round_to_step(1_000, Rounding::Up)?.raw(), 2_000);
+    assert!(Quantity::from_raw(u64::MAX, 0)
+        .round_to_step(10, Rounding::Up)
+        .is_err());
+    Ok(())
+}
+
+#[test]
+fn overflows_are_errors() -> Result<()> {
+    // Past u64, and past u128 once decimals reach 20
+    assert!(Quantity::from_units(19, 18).is_err());
+    assert!(Quantity::from_units(u64::MAX, 20).is_err());
+    assert!(Price::from_units(u64::MAX, 20).is_err());
+    assert!(Quantity::from_units(1, 39).is_err());
+    assert!(Quantity::from_decimal_str("18446744073709551616", 0, Rounding::Down).is_err());
+
+    let max = Quantity::from_raw(u64::MAX, 6);
+    assert!(max.checked_add(Quantity::from_raw(1, 6)).is_err());
+    assert!(Quantity::zero(6)
+        .checked_sub(Quantity::from_raw(1, 6))
+        .is_err());
+    assert!(max.mul_div(2, 1, Rounding::Down).is_err());
+    assert!(max.mul_div(1, 0, Rounding::Down).is_err());
+    assert!(max.rescale(9, Rounding::Down).is_err());
+    // Quantities of different assets do not mix
+    assert!(max.checked_add(Quantity::from_raw(1, 9)).is_err());
+
+    let price = Price::from_raw(u64::MAX, 9);
+    assert!(price.quote_for(max, 6, Rounding::Down).is_err());
+    assert!(price
+        .base_for(Quantity::from_raw(1, 30), 9, Rounding::Down)
+        .is_err());
+    assert!(price.offset_bps(100, Rounding::Down).is_err());
+    assert!(Price::from_raw(1, 9)
+        .offset_bps(-10_001, Rounding::Down)
+        .is_err());
+    Ok(())
+}
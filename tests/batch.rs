@@ -0,0 +1,124 @@
+mod harness;
+
+use anyhow::Result;
+use fuels::types::Bits256;
+use harness::TestMarket;
+use spark_examples::{
+    batch::{BatchSubmitter, CallStatus, OnRevert},
+    SparkContext, SparkError,
+};
+use spark_market_sdk::OrderType;
+
+#[tokio::test]
+async fn splits_calls_into_transactions() -> Result<()> {
+    let test = TestMarket::new().await?;
+    let ctx = test.context(test.wallet(1)).await?;
+    let info = &ctx.market_info;
+    ctx.market
+        .deposit(info.base.units(10)?.raw(), info.base.asset_id)
+        .await?;
+
+    let mut batch = BatchSubmitter::new(&ctx).max_calls(10);
+    for i in 0..25 {
+        let price = info.price_from_units(3_000 + i)?;
+        batch.push(ctx.market.get_instance().methods().open_order(
+            info.base.parse("0.01")?.raw(),
+            OrderType::Sell,
+            price.raw(),
+        ));
+    }
+    assert_eq!(batch.len(), 25);
+
+    let report = batch.submit().await?;
+    report.ensure_success()?;
+    let sizes: Vec<_> = report.chunks.iter().map(|c| c.calls.len()).collect();
+    assert_eq!(sizes, vec![10, 10, 5]);
+    assert_eq!(report.tx_ids().len(), 3);
+    assert!(report.chunks.iter().all(|c| c.estimated_gas > 0));
+    assert_eq!(
+        report.chunk_of(24).unwrap().calls,
+        (20..25).collect::<Vec<_>>()
+    );
+    assert!(report.tx_id_of(0).is_some());
+    assert_eq!(
+        ctx.market.user_orders(ctx.wallet_id()).await?.value.len(),
+        25
+    );
+    Ok(())
+}
+
+async fn two_orders_and_a_stranger(ctx: &SparkContext) -> Result<Vec<Bits256>> {
+    let info = &ctx.market_info;
+    ctx.market
+        .deposit(info.base.units(10)?.raw(), info.base.asset_id)
+        .await?;
+
+    let mut ids = Vec::new();
+    for price in ["3000", "3001"] {
+        let price = info.parse_price(price)?.raw();
+        let amount = info.base.parse("0.01")?.raw();
+        ids.push(
+            ctx.market
+                .open_order(amount, OrderType::Sell, price)
+                .await?
+                .value,
+        );
+    }
+    // Nobody ever opened this one, so cancelling it reverts
+    ids.insert(1, Bits256([7; 32]));
+    Ok(ids)
+}
+
+#[tokio::test]
+async fn drops_a_reverting_call_and_sends_the_rest() -> Result<()> {
+    let test = TestMarket::new().await?;
+    let ctx = test.context(test.wallet(1)).await?;
+    let ids = two_orders_and_a_stranger(&ctx).await?;
+
+    let mut batch = BatchSubmitter::new(&ctx);
+    for id in &ids {
+        batch.push(ctx.market.get_instance().methods().cancel_order(*id));
+    }
+    let report = batch.submit().await?;
+
+    assert!(!report.is_success());
+    assert_eq!(report.included(), 2);
+    assert!(report.tx_id_of(0).is_some());
+    assert!(report.tx_id_of(2).is_some());
+    let failures = report.failures();
+    assert_eq!(failures.len(), 1);
+    assert_eq!(failures[0].0, 1);
+    assert!(matches!(failures[0].1, SparkError::OrderNotFound(_)));
+    assert!(!failures[0].1.is_retryable());
+    assert!(report.ensure_success().is_err());
+    assert!(ctx
+        .market
+        .user_orders(ctx.wallet_id())
+        .await?
+        .value
+        .is_empty());
+    Ok(())
+}
+
+#[tokio::test]
+async fn stops_at_a_reverting_call_when_asked() -> Result<()> {
+    let test = TestMarket::new().await?;
+    let ctx = test.context(test.wallet(1)).await?;
+    let ids = two_orders_and_a_stranger(&ctx).await?;
+
+    let mut batch = BatchSubmitter::new(&ctx).on_revert(OnRevert::Stop);
+    for id in &ids {
+        batch.push(ctx.market.get_instance().methods().cancel_order(*id));
+    }
+    let report = batch.submit().await?;
+
+    assert!(matches!(report.calls[1], CallStatus::Failed { .. }));
+    assert_eq!(report.calls[2], CallStatus::NotSent);
+    assert!(!ctx
+        .market
+        .user_orders(ctx.wallet_id())
+        .await?
+        .value
+        .is_empty());
+    Ok(())
+}
@@ -0,0 +1,220 @@
+use std::{
+    env, fs,
+    path::PathBuf,
+    sync::{Mutex, MutexGuard},
+};
+
+use anyhow::Result;
+use fuels::types::{AssetId, ContractId};
+use spark_examples::config::{Config, ConfigError, Network};
+
+static ENV: Mutex<()> = Mutex::new(());
+
+/// Tests share the process environment, so they take turns and each starts
+/// without any of the variables the config reads.
+fn clean_env() -> MutexGuard<'static, ()> {
+    let guard = ENV.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    let suffixes = ["_ID", "_DECIMALS", "_CONTRACT", "_IMPLEMENTATION"];
+    for (key, _) in env::vars() {
+        if suffixes.iter().any(|suffix| key.ends_with(suffix)) {
+            env::remove_var(key);
+        }
+    }
+    for key in [
+        "SPARK_CONFIG",
+        "SPARK_NETWORK",
+        "PROVIDER",
+        "MNEMONIC",
+        "MARKET_REGISTRY",
+    ] {
+        env::remove_var(key);
+    }
+    guard
+}
+
+fn config_file(name: &str, contents: &str) -> Result<PathBuf> {
+    let path = env::temp_dir().join(format!("spark-{}-{}.toml", name, std::process::id()));
+    fs::write(&path, contents)?;
+    Ok(path)
+}
+
+fn id(byte: &str) -> String {
+    format!("0x{}", byte.repeat(32))
+}
+
+fn problems(error: anyhow::Error) -> Vec<String> {
+    error
+        .downcast::<ConfigError>()
+        .expect("a ConfigError")
+        .problems
+}
+
+#[test]
+fn reports_every_bad_key_at_once() -> Result<()> {
+    let _env = clean_env();
+    env::set_var("ETH_DECIMALS", "lots");
+    let path = config_file(
+        "bad-keys",
+        r#"
+        [networks.testnet]
+        registry = "0x12"
+
+        [networks.testnet.assets]
+        USDC = "not hex"
+        ETH = { decimals = 9 }
+
+        [networks.testnet.markets.ETH_USDC]
+        implementation = "0xzz"
+        "#,
+    )?;
+
+    let found = problems(Config::load_from(&path).unwrap_err());
+    let expected = [
+        "MNEMONIC is not set",
+        "MARKET_REGISTRY is not a valid",
+        "USDC_ID is not a valid",
+        "ETH_ID is not set",
+        "ETH_DECIMALS is not a valid number of decimals",
+        "ETH_USDC_CONTRACT_ID is not set",
+        "ETH_USDC_IMPLEMENTATION is not a valid",
+    ];
+    for problem in expected {
+        assert!(
+            found.iter().any(|message| message.starts_with(problem)),
+            "{:?} missing from {:?}",
+            problem,
+            found
+        );
+    }
+    assert_eq!(found.len(), expected.len(), "{:?}", found);
+
+    // Without a file only the keys that have no default are missing
+    let error = Config::load_from(env::temp_dir().join("spark-missing.toml")).unwrap_err();
+    assert_eq!(
+        problems(error),
+        vec!["MNEMONIC is not set", "MARKET_REGISTRY is not set"]
+    );
+    fs::remove_file(path)?;
+    Ok(())
+}
+
+#[test]
+fn environment_overrides_the_file() -> Result<()> {
+    let _env = clean_env();
+    let path = config_file(
+        "overrides",
+        &format!(
+            r#"
+            [networks.testnet]
+            provider = "file.example:4000"
+            mnemonic = "file words"
+            registry = "{}"
+
+            [networks.testnet.assets]
+            USDC = {{ id = "{}", decimals = 6 }}
+            "#,
+            id("11"),
+            id("22"),
+        ),
+    )?;
+    env::set_var("MNEMONIC", "env words");
+    env::set_var("USDC_ID", id("33"));
+    // Empty values count as unset
+    env::set_var("MARKET_REGISTRY", " ");
+
+    let config = Config::load_from(&path)?;
+    assert_eq!(config.network, Network::Testnet);
+    assert_eq!(config.provider, "file.example:4000");
+    assert_eq!(config.mnemonic, "env words");
+    assert_eq!(config.registry, id("11").parse::<ContractId>().unwrap());
+    assert_eq!(config.asset("usdc")?, id("33").parse::<AssetId>().unwrap());
+    assert_eq!(config.assets["USDC"].decimals, Some(6));
+
+    env::set_var("PROVIDER", "env.example:4000");
+    env::set_var("USDC_DECIMALS", "9");
+    let config = Config::load_from(&path)?;
+    assert_eq!(config.provider, "env.example:4000");
+    assert_eq!(config.assets["USDC"].decimals, Some(9));
+    fs::remove_file(path)?;
+    Ok(())
+}
+
+#[test]
+fn reads_legacy_environment_names_without_a_file() -> Result<()> {
+    let _env = clean_env();
+    env::set_var("MNEMONIC", "legacy words");
+    env::set_var("MARKET_REGISTRY", id("11"));
+    env::set_var("BTC_ID", id("22"));
+    env::set_var("USDC_ID", id("33"));
+    env::set_var("BTC_USDC_CONTRACT_ID", id("44"));
+    env::set_var("BTC_USDC_IMPLEMENTATION", id("55"));
+
+    let config = Config::load_from(env::temp_dir().join("spark-missing.toml"))?;
+    assert_eq!(config.provider, "testnet.fuel.network");
+    assert_eq!(config.asset("BTC")?, id("22").parse::<AssetId>().unwrap());
+    assert!(config.asset("ETH").is_err());
+    let market = config.market("btc/usdc")?;
+    let contract_id = id("44").parse::<ContractId>().unwrap();
+    assert_eq!(market.contract_id, contract_id);
+    assert_eq!(
+        config.implementation(&contract_id),
+        Some(id("55").parse::<ContractId>().unwrap())
+    );
+    assert!(config.market("ETH_USDC").is_err());
+
+    // Legacy names are optional, but still have to be valid when set
+    env::set_var("TRMP_ID", "0x1");
+    let found =
+        problems(Config::load_from(env::temp_dir().join("spark-missing.toml")).unwrap_err());
+    assert_eq!(found.len(), 1);
+    assert!(found[0].starts_with("TRMP_ID is not a valid"));
+    Ok(())
+}
+
+#[test]
+fn selects_the_network_profile() -> Result<()> {
+    let _env = clean_env();
+    let path = config_file(
+        "profiles",
+        &format!(
+            r#"
+            network = "mainnet"
+
+            [networks.testnet]
+            mnemonic = "testnet words"
+            registry = "{}"
+
+            [networks.mainnet]
+            mnemonic = "mainnet words"
+            registry = "{}"
+
+            [networks.local]
+            provider = "127.0.0.1:4001"
+            mnemonic = "local words"
+            registry = "{}"
+            "#,
+            id("11"),
+            id("22"),
+            id("33"),
+        ),
+    )?;
+
+    // The file's own choice, with the network's default provider
+    let config = Config::load_from(&path)?;
+    assert_eq!(config.network, Network::Mainnet);
+    assert_eq!(config.mnemonic, "mainnet words");
+    assert_eq!(config.provider, "mainnet.fuel.network");
+
+    // SPARK_NETWORK wins over the file
+    env::set_var("SPARK_NETWORK", "Local");
+    let config = Config::load_from(&path)?;
+    assert_eq!(config.network, Network::Local);
+    assert_eq!(config.mnemonic, "local words");
+    assert_eq!(config.provider, "127.0.0.1:4001");
+    assert_eq!(config.registry, id("33").parse::<ContractId>().unwrap());
+
+    env::set_var("SPARK_NETWORK", "devnet");
+    assert!(Config::load_from(&path).is_err());
+    fs::remove_file(path)?;
+    Ok(())
+}
@@ -0,0 +1,85 @@
+mod harness;
+
+use anyhow::{anyhow, Result};
+use harness::TestMarket;
+use spark_examples::SparkError;
+use spark_market_sdk::OrderType;
+
+#[test]
+fn market_errors_are_recognised_in_reasons() {
+    let cases = [
+        (
+            "InsufficientBalance((1000, 5000, true))",
+            SparkError::InsufficientBalance("InsufficientBalance((1000, 5000, true))".into()),
+        ),
+        (
+            "OrderNotFound(Bits256([7, 7]))",
+            SparkError::OrderNotFound("OrderNotFound(Bits256([7, 7]))".into()),
+        ),
+        ("Paused", SparkError::Paused),
+        ("NotOwner", SparkError::Unauthorized("NotOwner".into())),
+    ];
+    for (reason, expected) in cases {
+        assert_eq!(SparkError::from_reason(reason), expected);
+    }
+
+    assert!(matches!(
+        SparkError::from_reason("Revert(123)"),
+        SparkError::Reverted(_)
+    ));
+    assert!(matches!(
+        SparkError::from_reason("something else entirely"),
+        SparkError::Other(_)
+    ));
+}
+
+#[test]
+fn only_transient_errors_are_retryable() {
+    assert!(SparkError::Timeout(String::new()).is_retryable());
+    assert!(SparkError::SqueezedOut("coin spent".into()).is_retryable());
+    assert!(!SparkError::InsufficientBalance(String::new()).is_retryable());
+    assert!(!SparkError::Paused.is_retryable());
+}
+
+#[test]
+fn errors_read_as_sentences() {
+    let error = SparkError::OrderTooSmall("ZeroOrderAmount".into());
+    assert_eq!(
+        error.to_string(),
+        "Order amount is too small (ZeroOrderAmount)"
+    );
+    assert_eq!(SparkError::Paused.to_string(), "The market is paused");
+    assert_eq!(SparkError::Other("as is".into()).to_string(), "as is");
+
+    // A `SparkError` inside an anyhow chain is found again as it was
+    let wrapped = anyhow::Error::new(SparkError::Paused).context("Opening order");
+    assert_eq!(SparkError::from(&wrapped), SparkError::Paused);
+    assert!(matches!(
+        SparkError::from(anyhow!("Transaction 0x12 not included after 60s")),
+        SparkError::Timeout(_)
+    ));
+}
+
+#[tokio::test]
+async fn opening_without_a_deposit_is_insufficient_balance() -> Result<()> {
+    let test = TestMarket::new().await?;
+    let ctx = test.context(test.wallet(1)).await?;
+    let info = &ctx.market_info;
+
+    let result = ctx
+        .market
+        .open_order(
+            info.base.parse("1")?.raw(),
+            OrderType::Sell,
+            info.parse_price("3000")?.raw(),
+        )
+        .await;
+    let error = SparkError::from(result.expect_err("nothing was deposited"));
+    assert!(
+        matches!(error, SparkError::InsufficientBalance(_)),
+        "{:?}",
+        error
+    );
+    assert!(!error.is_retryable());
+    Ok(())
+}
@@ -0,0 +1,138 @@
+mod harness;
+
+use anyhow::Result;
+use fuels::types::AssetId;
+use harness::{described_eth_usdc, TestMarket};
+use spark_examples::{indexer::TradeRecord, maker::DesiredOrder, AssetInfo, FeeSchedule};
+use spark_market_sdk::{OrderType, ProtocolFee};
+
+fn usdc() -> AssetInfo {
+    AssetInfo::new("USDC", AssetId::new([2; 32]), 6)
+}
+
+// The default tiers from the README, in base 1e4 and quote base units
+fn readme_schedule() -> FeeSchedule {
+    let tier = |maker_fee, taker_fee, volume: u64| ProtocolFee {
+        maker_fee,
+        taker_fee,
+        volume_threshold: volume * 1_000_000,
+    };
+    FeeSchedule::new(
+        &[
+            tier(8, 13, 50_000),
+            tier(10, 15, 10_000),
+            tier(6, 9, 100_000),
+            tier(2, 7, 500_000),
+            tier(1, 5, 1_000_000),
+        ],
+        1_000,
+        &usdc(),
+    )
+}
+
+#[test]
+fn volume_picks_the_tier() -> Result<()> {
+    let schedule = readme_schedule();
+    let usdc = usdc();
+    let rates = |volume: &str| {
+        let tier = schedule.tier(usdc.parse(volume).unwrap());
+        (tier.maker_bps, tier.taker_bps)
+    };
+
+    assert_eq!(rates("0"), (10, 15));
+    assert_eq!(rates("49999.99"), (10, 15));
+    assert_eq!(rates("50000"), (8, 13));
+    assert_eq!(rates("750000"), (2, 7));
+    assert_eq!(rates("5000000"), (1, 5));
+    assert_eq!(schedule.max_reserve_bps(), 15);
+    assert_eq!(schedule.max_taker_bps(), 15);
+
+    // The rates the market reports pick the tier, whatever volume is known
+    let tier = schedule.user_tier(6, 9);
+    assert_eq!(tier.volume_threshold, usdc.parse("100000")?);
+    let custom = schedule.user_tier(0, 3);
+    assert!(custom.volume_threshold.is_zero());
+    assert_eq!((custom.maker_bps, custom.taker_bps), (0, 3));
+
+    let (next, missing) = schedule.next_tier(usdc.parse("60000")?).unwrap();
+    assert_eq!((next.maker_bps, next.taker_bps), (6, 9));
+    assert_eq!(missing, usdc.parse("40000")?);
+    assert!(schedule.next_tier(usdc.parse("1000000")?).is_none());
+    Ok(())
+}
+
+#[test]
+fn order_fees_and_reserve() -> Result<()> {
+    let market = described_eth_usdc();
+    let schedule = readme_schedule();
+    let buy = DesiredOrder {
+        side: OrderType::Buy,
+        amount: market.base.parse("2")?,
+        price: market.parse_price("3000")?,
+    };
+
+    // 6000 USDC at 0.08% / 0.13%, plus the 0.001 USDC matcher fee
+    let fees = schedule.order_fees(&market, &buy, market.quote.parse("50000")?)?;
+    assert_eq!(fees.value, market.quote.parse("6000")?);
+    assert_eq!(fees.maker_fee, market.quote.parse("4.8")?);
+    assert_eq!(fees.taker_fee, market.quote.parse("7.8")?);
+    assert_eq!(fees.matcher_fee, market.quote.parse("0.001")?);
+    assert_eq!(fees.reserve, market.quote.parse("7.801")?);
+    assert_eq!(fees.locked_quote, market.quote.parse("6007.801")?);
+
+    let sell = DesiredOrder {
+        side: OrderType::Sell,
+        ..buy
+    };
+    let fees = schedule.order_fees(&market, &sell, market.quote.parse("0")?)?;
+    assert!(fees.locked_quote.is_zero());
+
+    // Fills count at their quote value, whichever side the user was on
+    let fill = |size: u64, price: u64| TradeRecord {
+        block_height: 1,
+        tx_id: String::new(),
+        sell_order_id: String::new(),
+        buy_order_id: String::new(),
+        seller: String::new(),
+        buyer: String::new(),
+        matcher: String::new(),
+        size,
+        price,
+    };
+    let trades = [
+        fill(1_000_000_000, 3_000_000_000_000),
+        fill(500_000_000, 3_100_000_000_000),
+    ];
+    assert_eq!(
+        FeeSchedule::traded_volume(&market, &trades)?,
+        market.quote.parse("4550")?
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn reserve_matches_what_open_order_locks() -> Result<()> {
+    let test = TestMarket::new().await?;
+    let ctx = test.context(test.wallet(1)).await?;
+    let info = &ctx.market_info;
+    ctx.market
+        .deposit(info.quote.units(10_000)?.raw(), info.quote.asset_id)
+        .await?;
+
+    let buy = DesiredOrder {
+        side: OrderType::Buy,
+        amount: info.base.parse("1")?,
+        price: info.parse_price("3000")?,
+    };
+    // At the tier the market charges this wallet
+    let (maker_bps, taker_bps) = ctx.market.protocol_fee_user(ctx.wallet_id()).await?.value;
+    let schedule = FeeSchedule::from_market(info);
+    let fees = schedule.order_fees_in(info, &buy, schedule.user_tier(maker_bps, taker_bps))?;
+    ctx.market
+        .open_order(buy.amount.raw(), buy.side.clone(), buy.price.raw())
+        .await?;
+
+    let account = ctx.market.account(ctx.wallet_id()).await?.value;
+    assert_eq!(account.locked.quote, fees.locked_quote.raw());
+    Ok(())
+}
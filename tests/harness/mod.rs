@@ -0,0 +1,219 @@
+//! A local fuel-core node with a freshly deployed Spark market and registry.
+//!
+//! Every `TestMarket` starts its own in-process node, so tests run offline and
+//! never share state. Mock base and quote coins are minted into the genesis
+//! block for each wallet; the first wallet owns the contracts.
+
+#![allow(dead_code)]
+
+use std::collections::BTreeMap;
+
+use anyhow::Result;
+use fuels::{
+    accounts::{provider::Provider, wallet::WalletUnlocked},
+    test_helpers::{
+        launch_custom_provider_and_get_wallets, AssetConfig as CoinConfig, WalletsConfig,
+    },
+    types::{AssetId, ContractId, Identity},
+};
+use spark_examples::{
+    assets::AssetInfo,
+    config::{AssetConfig, Config, MarketConfig, Network},
+    MarketInfo, SparkContext,
+};
+use spark_market_sdk::{ProtocolFee, SparkMarketContract};
+use spark_registry_sdk::SparkRegistryContract;
+
+pub const BASE_SYMBOL: &str = "ETH";
+pub const QUOTE_SYMBOL: &str = "USDC";
+pub const BASE_DECIMALS: u32 = 9;
+pub const QUOTE_DECIMALS: u32 = 6;
+pub const PRICE_DECIMALS: u32 = 9;
+pub const MARKET_VERSION: u32 = 1;
+pub const REGISTRY_VERSION: u32 = 1;
+
+/// Market name as accepted by `SparkContext`.
+pub const MARKET: &str = "ETH_USDC";
+
+/// Matcher fee in quote base units (0.01 USDC).
+pub const MATCHER_FEE: u64 = 10_000;
+/// Single fee tier in basis points.
+pub const MAKER_FEE_BPS: u64 = 10;
+pub const TAKER_FEE_BPS: u64 = 15;
+
+/// SQLite path of an event database that lives only as long as its
+/// connection, e.g. for `Swap::load` and `Router::discover`.
+pub const EVENTS_DB: &str = ":memory:";
+
+/// Wallets started by default: the owner plus three traders.
+pub const DEFAULT_WALLETS: u64 = 4;
+/// Each wallet receives 1,000 ETH and 10,000,000 USDC.
+pub const BASE_FUNDING: u64 = 1_000 * 10u64.pow(BASE_DECIMALS);
+pub const QUOTE_FUNDING: u64 = 10_000_000 * 10u64.pow(QUOTE_DECIMALS);
+/// Gas coins per wallet.
+pub const GAS_FUNDING: u64 = 1_000_000_000;
+
+pub struct TestMarket {
+    pub provider: Provider,
+    /// Deployer of the market and registry. Also funded, and usable as a trader.
+    pub owner: WalletUnlocked,
+    /// Every funded wallet, `owner` first.
+    pub wallets: Vec<WalletUnlocked>,
+    pub base: AssetInfo,
+    pub quote: AssetInfo,
+    pub market_id: ContractId,
+    pub registry_id: ContractId,
+}
+
+impl TestMarket {
+    pub async fn new() -> Result<Self> {
+        Self::with_wallets(DEFAULT_WALLETS).await
+    }
+
+    /// Starts a node with `count` funded wallets, deploys the market and the
+    /// registry from the SDK crates' binaries and registers the market.
+    pub async fn with_wallets(count: u64) -> Result<Self> {
+        let base = AssetInfo::new(BASE_SYMBOL, AssetId::new([1; 32]), BASE_DECIMALS);
+        let quote = AssetInfo::new(QUOTE_SYMBOL, AssetId::new([2; 32]), QUOTE_DECIMALS);
+
+        let coins = vec![
+            CoinConfig {
+                id: AssetId::zeroed(),
+                num_coins: 1,
+                coin_amount: GAS_FUNDING,
+            },
+            CoinConfig {
+                id: base.asset_id,
+                num_coins: 1,
+                coin_amount: BASE_FUNDING,
+            },
+            CoinConfig {
+                id: quote.asset_id,
+                num_coins: 1,
+                coin_amount: QUOTE_FUNDING,
+            },
+        ];
+        let wallets = launch_custom_provider_and_get_wallets(
+            WalletsConfig::new_multiple_assets(count, coins),
+            None,
+            None,
+        )
+        .await?;
+        let owner = wallets[0].clone();
+        let provider = owner
+            .provider()
+            .expect("test wallets have a provider")
+            .clone();
+
+        let market = SparkMarketContract::deploy(
+            base.asset_id,
+            BASE_DECIMALS,
+            quote.asset_id,
+            QUOTE_DECIMALS,
+            owner.clone(),
+            PRICE_DECIMALS,
+            MARKET_VERSION,
+        )
+        .await?;
+        market
+            .set_protocol_fee(vec![ProtocolFee {
+                maker_fee: MAKER_FEE_BPS,
+                taker_fee: TAKER_FEE_BPS,
+                volume_threshold: 0,
+            }])
+            .await?;
+        market.set_matcher_fee(MATCHER_FEE).await?;
+        let market_id: ContractId = market.contract_id().into();
+
+        let registry = SparkRegistryContract::deploy(owner.clone(), REGISTRY_VERSION).await?;
+        registry.register_market(market_id).await?;
+        let registry_id: ContractId = registry.contract_id().into();
+
+        Ok(Self {
+            provider,
+            owner,
+            wallets,
+            base,
+            quote,
+            market_id,
+            registry_id,
+        })
+    }
+
+    /// A config for the local node with both assets and the market declared,
+    /// as `Config::load` would produce it from `spark.toml`.
+    pub fn config(&self) -> Config {
+        let assets = [&self.base, &self.quote]
+            .into_iter()
+            .map(|asset| {
+                let config = AssetConfig {
+                    id: asset.asset_id,
+                    decimals: Some(asset.decimals as u8),
+                    contract: None,
+                };
+                (asset.symbol.clone(), config)
+            })
+            .collect();
+        let markets = BTreeMap::from([(
+            MARKET.to_string(),
+            MarketConfig {
+                contract_id: self.market_id,
+                implementation: None,
+            },
+        )]);
+
+        Config {
+            network: Network::Local,
+            provider: self.provider.url().to_string(),
+            mnemonic: String::new(),
+            registry: self.registry_id,
+            assets,
+            markets,
+        }
+    }
+
+    /// A `SparkContext` on the test market signing with `wallet`.
+    pub async fn context(&self, wallet: &WalletUnlocked) -> Result<SparkContext> {
+        SparkContext::with_wallet(self.config(), wallet.clone(), MARKET).await
+    }
+
+    /// The market contract signing with `wallet`.
+    pub async fn market(&self, wallet: &WalletUnlocked) -> SparkMarketContract {
+        SparkMarketContract::new(self.market_id, wallet.clone()).await
+    }
+
+    /// Wallet `index`, `0` being the owner.
+    pub fn wallet(&self, index: usize) -> &WalletUnlocked {
+        &self.wallets[index]
+    }
+}
+
+pub fn identity(wallet: &WalletUnlocked) -> Identity {
+    wallet.address().into()
+}
+
+/// A market described by hand rather than deployed, for logic that needs no
+/// node: fee-free, without minimums and at the test market's price decimals.
+pub fn described_market(base: AssetInfo, quote: AssetInfo) -> MarketInfo {
+    MarketInfo {
+        contract_id: ContractId::default(),
+        base,
+        quote,
+        price_decimals: PRICE_DECIMALS,
+        version: MARKET_VERSION,
+        implementation: None,
+        protocol_fee: vec![],
+        matcher_fee: 0,
+        min_order_size: 0,
+        min_order_price: 0,
+        registered: true,
+    }
+}
+
+/// `described_market` with the test market's ETH and USDC.
+pub fn described_eth_usdc() -> MarketInfo {
+    described_market(
+        AssetInfo::new(BASE_SYMBOL, AssetId::new([1; 32]), BASE_DECIMALS),
+        AssetInfo::new(QUOTE_SYMBOL, AssetId::new([2; 32]), QUOTE_DECIMALS),
+    )
+}
@@ -0,0 +1,69 @@
+mod harness;
+
+use anyhow::Result;
+use harness::{identity, TestMarket};
+use spark_examples::indexer::{EventStore, Indexer};
+use spark_market_sdk::OrderType;
+
+#[tokio::test]
+async fn indexes_market_events_into_sqlite() -> Result<()> {
+    let test = TestMarket::new().await?;
+    let buyer = test.context(test.wallet(1)).await?;
+    let seller = test.context(test.wallet(2)).await?;
+    let info = &buyer.market_info;
+
+    let amount = info.base.parse("0.5")?.raw();
+    let price = info.parse_price("3000")?.raw();
+    let quote = test.quote.units(10_000)?.raw();
+
+    buyer.market.deposit(quote, test.quote.asset_id).await?;
+    seller.market.deposit(amount, test.base.asset_id).await?;
+    seller.market.deposit(quote, test.quote.asset_id).await?;
+    let buy = buyer
+        .market
+        .open_order(amount, OrderType::Buy, price)
+        .await?
+        .value;
+    let sell = seller
+        .market
+        .open_order(amount, OrderType::Sell, price)
+        .await?
+        .value;
+    let stale = buyer
+        .market
+        .open_order(amount, OrderType::Buy, price / 2)
+        .await?
+        .value;
+    buyer.market.cancel_order(stale).await?;
+    test.market(test.wallet(3))
+        .await
+        .match_order_pair(buy, sell)
+        .await?;
+
+    let store = EventStore::in_memory(test.market_id)?;
+    let mut indexer = Indexer::new(&buyer.market, test.provider.clone(), store).with_page_size(3);
+    let stats = indexer.sync(0).await?;
+    assert!(stats.events >= 7);
+
+    let store = indexer.store();
+    let buyer_id = identity(test.wallet(1));
+    assert_eq!(store.transfers(None, 0)?.len(), 3);
+    assert_eq!(store.opened(Some(&buyer_id), 0)?.len(), 2);
+    assert_eq!(store.cancels(None, 0)?.len(), 1);
+
+    let trades = store.trades(Some(&buyer_id), 0)?;
+    assert_eq!(trades.len(), 1);
+    assert_eq!(trades[0].size, amount);
+    assert_eq!(trades[0].price, price);
+
+    let balance = store
+        .latest_balance(&buyer_id)?
+        .expect("buyer has a balance");
+    assert_eq!(balance.liquid_base, amount);
+
+    // A second sync only looks at new blocks
+    let height = store.cursor()?;
+    assert_eq!(indexer.sync(0).await?.events, 0);
+    assert_eq!(indexer.store().cursor()?, height);
+    Ok(())
+}
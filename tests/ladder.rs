@@ -0,0 +1,133 @@
+mod harness;
+
+use anyhow::Result;
+use harness::described_eth_usdc;
+use spark_examples::{amount::Quantity, Distribution, LadderBuilder};
+use spark_market_sdk::OrderType;
+
+fn close(a: f64, b: f64) -> bool {
+    (a - b).abs() < 1e-9
+}
+
+#[test]
+fn distributions_weigh_levels_by_distance() -> Result<()> {
+    let positions = [0.0, 0.5, 1.0];
+
+    let linear = Distribution::Linear { edge: 0.5 }.weights(&positions)?;
+    assert_eq!(linear, vec![1.0, 0.75, 0.5]);
+
+    let halving = Distribution::Exponential {
+        rate: std::f64::consts::LN_2,
+    }
+    .weights(&positions)?;
+    assert!(close(halving[0], 1.0));
+    assert!(close(halving[1], 0.5f64.sqrt()));
+    assert!(close(halving[2], 0.5));
+
+    let normal = Distribution::normal().weights(&positions)?;
+    assert!(close(normal[0], 1.0));
+    assert!(close(normal[2], (-4.5f64).exp()));
+
+    assert_eq!(Distribution::Flat.weights(&positions)?, vec![1.0; 3]);
+    assert!(Distribution::Custom(vec![1.0, 2.0])
+        .weights(&positions)
+        .is_err());
+    assert!(Distribution::Custom(vec![1.0, -1.0, 1.0])
+        .weights(&positions)
+        .is_err());
+    Ok(())
+}
+
+#[test]
+fn flat_ladder_splits_budgets_evenly() -> Result<()> {
+    let market = described_eth_usdc();
+    let orders = LadderBuilder::new(&market, market.parse_price("3000")?)
+        .range(market.parse_price("50")?)
+        .step(market.parse_price("10")?)
+        .base_budget(market.base.parse("1")?)
+        .quote_budget(market.quote.parse("3000")?)
+        .build()?;
+    assert_eq!(orders.len(), 10);
+
+    let asks: Vec<_> = orders
+        .iter()
+        .filter(|o| o.side == OrderType::Sell)
+        .collect();
+    let bids: Vec<_> = orders.iter().filter(|o| o.side == OrderType::Buy).collect();
+    assert_eq!(asks[0].price, market.parse_price("3010")?);
+    assert_eq!(asks[4].price, market.parse_price("3050")?);
+    assert_eq!(bids[0].price, market.parse_price("2990")?);
+    assert_eq!(bids[4].price, market.parse_price("2950")?);
+    for ask in &asks {
+        assert_eq!(ask.amount, market.base.parse("0.2")?);
+    }
+
+    // Each bid spends at most a fifth of the quote budget
+    let mut spent = Quantity::zero(market.quote.decimals);
+    for bid in &bids {
+        let value = bid.quote_value(&market)?;
+        assert!(value <= market.quote.parse("600")?);
+        spent = spent.checked_add(value)?;
+    }
+    assert!(spent <= market.quote.parse("3000")?);
+    Ok(())
+}
+
+#[test]
+fn normal_ladder_thins_out_and_respects_min_size() -> Result<()> {
+    let market = described_eth_usdc();
+    let ladder = LadderBuilder::new(&market, market.parse_price("3000")?)
+        .range(market.parse_price("500")?)
+        .levels(10)
+        .distribution(Distribution::normal())
+        .base_budget(market.base.parse("1")?);
+
+    let asks = ladder.build()?;
+    assert_eq!(asks.len(), 10);
+    assert!(asks.iter().all(|o| o.side == OrderType::Sell));
+    assert!(asks.windows(2).all(|w| w[0].amount > w[1].amount));
+
+    let trimmed = ladder.min_size(market.base.parse("0.05")?).build()?;
+    assert!(trimmed.len() < asks.len());
+    assert!(trimmed
+        .iter()
+        .all(|o| o.amount >= market.base.parse("0.05").unwrap()));
+    assert_eq!(trimmed[..], asks[..trimmed.len()]);
+    Ok(())
+}
+
+#[test]
+fn ladder_rejects_bad_input() -> Result<()> {
+    let market = described_eth_usdc();
+    let mid = market.parse_price("30")?;
+
+    // Bids stop above zero
+    let bids = LadderBuilder::new(&market, mid)
+        .range(market.parse_price("50")?)
+        .step(market.parse_price("10")?)
+        .quote_budget(market.quote.parse("100")?)
+        .build()?;
+    assert_eq!(bids.len(), 2);
+
+    // Amounts must use the asset's decimals
+    let wrong_decimals = LadderBuilder::new(&market, mid)
+        .range(market.parse_price("10")?)
+        .levels(2)
+        .quote_budget(market.base.parse("100")?)
+        .build();
+    assert!(wrong_decimals.is_err());
+
+    // A range, a spacing and matching custom weights are required
+    assert!(LadderBuilder::new(&market, mid).levels(2).build().is_err());
+    assert!(LadderBuilder::new(&market, mid)
+        .range(market.parse_price("10")?)
+        .build()
+        .is_err());
+    assert!(LadderBuilder::new(&market, mid)
+        .range(market.parse_price("10")?)
+        .levels(3)
+        .distribution(Distribution::Custom(vec![1.0, 2.0]))
+        .build()
+        .is_err());
+    Ok(())
+}
@@ -0,0 +1,272 @@
+mod harness;
+
+use anyhow::Result;
+use fuels::types::{Bits256, Identity};
+use harness::{described_eth_usdc, TestMarket};
+use spark_examples::{
+    amount::{Price, Quantity, Rounding},
+    maker::{
+        BandStrategy, DesiredOrder, Inventory, InventorySkew, MakerConfig, MarketMaker, QuoteInput,
+        QuoteStrategy, Reconciler, Tolerance,
+    },
+    oracle::StaticPrice,
+    orderbook::BookOrder,
+};
+use spark_market_sdk::OrderType;
+
+fn desired(side: OrderType, amount: u64, price: u64) -> DesiredOrder {
+    DesiredOrder {
+        side,
+        amount: Quantity::from_raw(amount, 9),
+        price: Price::from_raw(price, 9),
+    }
+}
+
+fn live(id: u8, side: OrderType, amount: u64, price: u64) -> BookOrder {
+    BookOrder {
+        id: Bits256([id; 32]),
+        side,
+        amount: Quantity::from_raw(amount, 9),
+        price: Price::from_raw(price, 9),
+        owner: Identity::Address(Default::default()),
+    }
+}
+
+#[test]
+fn reconciler_keeps_orders_within_tolerance() {
+    let reconciler = Reconciler::new(Tolerance {
+        price_bps: 10,
+        amount_bps: 500,
+    });
+    let wanted = [
+        desired(OrderType::Buy, 1_000, 2_997_000),
+        desired(OrderType::Buy, 1_000, 2_985_000),
+        desired(OrderType::Sell, 1_000, 3_003_000),
+    ];
+    let resting = [
+        // 0.03% below, partially filled: kept for the first bid
+        live(1, OrderType::Buy, 960, 2_996_100),
+        // A second order on the same level: cancelled
+        live(2, OrderType::Buy, 1_000, 2_996_000),
+        // Right price, wrong side: cancelled
+        live(3, OrderType::Sell, 1_000, 2_985_000),
+        // Exact ask
+        live(4, OrderType::Sell, 1_000, 3_003_000),
+    ];
+
+    let plan = reconciler.plan(&wanted, &resting);
+    let kept: Vec<_> = plan.keep.iter().map(|(id, _)| id.0[0]).collect();
+    let cancelled: Vec<_> = plan.cancel.iter().map(|id| id.0[0]).collect();
+    assert_eq!(kept, vec![1, 4]);
+    assert_eq!(cancelled, vec![2, 3]);
+    assert_eq!(plan.open, vec![wanted[1].clone()]);
+
+    // Nothing survives an exact comparison but the exact ask
+    let plan = Reconciler::exact().plan(&wanted, &resting);
+    assert_eq!(plan.keep.len(), 1);
+    assert_eq!(plan.cancel.len(), 3);
+    assert_eq!(plan.open.len(), 2);
+    assert!(!plan.is_empty());
+}
+
+#[test]
+fn band_strategy_quotes_both_sides_outside_the_spread() -> Result<()> {
+    let market = described_eth_usdc();
+    let info = &market;
+
+    let fair_value = info.parse_price("3000")?;
+    let strategy = BandStrategy::new(3, 10, 20, 50.0);
+    let quotes = strategy.quotes(&QuoteInput {
+        market: info,
+        fair_value,
+        inventory: Inventory {
+            base: info.base.quantity(0),
+            quote: info.quote.quantity(0),
+        },
+    })?;
+
+    assert_eq!(quotes.len(), 6);
+    let bids: Vec<_> = quotes.iter().filter(|q| q.side == OrderType::Buy).collect();
+    let asks: Vec<_> = quotes
+        .iter()
+        .filter(|q| q.side == OrderType::Sell)
+        .collect();
+    assert_eq!(bids[0].price, info.parse_price("2997")?);
+    assert_eq!(bids[2].price, info.parse_price("2985")?);
+    assert_eq!(asks[0].price, info.parse_price("3003")?);
+    assert_eq!(asks[2].price, info.parse_price("3015")?);
+    for quote in &quotes {
+        assert!(quote.quote_value(info)? <= info.quote.parse("50.000001")?);
+    }
+    Ok(())
+}
+
+#[test]
+fn inventory_skew_leans_against_the_heavy_side() -> Result<()> {
+    let market = described_eth_usdc();
+    let info = &market;
+    let fair_value = info.parse_price("3000")?;
+
+    let band = BandStrategy::new(1, 10, 0, 300.0);
+    let skewed = InventorySkew::new(band.clone())
+        .max_price_shift_bps(10)
+        .max_size_skew_bps(5_000);
+    let quotes = |strategy: &dyn QuoteStrategy, base: &str, quote: &str| {
+        strategy.quotes(&QuoteInput {
+            market: info,
+            fair_value,
+            inventory: Inventory {
+                base: info.base.parse(base)?,
+                quote: info.quote.parse(quote)?,
+            },
+        })
+    };
+
+    // Half the value in each asset: the band as it is
+    assert_eq!(quotes(&skewed, "1", "3000")?, quotes(&band, "1", "3000")?);
+
+    // Three quarters in base: half the maximum skew, 5 bps lower and 25% towards selling
+    let plain = quotes(&band, "3", "3000")?;
+    let leaning = quotes(&skewed, "3", "3000")?;
+    assert_eq!(leaning[0].side, OrderType::Buy);
+    assert_eq!(
+        leaning[0].price,
+        plain[0].price.offset_bps(-5, Rounding::Down)?
+    );
+    assert_eq!(leaning[0].amount.raw(), plain[0].amount.raw() * 3 / 4);
+    assert_eq!(leaning[1].side, OrderType::Sell);
+    assert_eq!(
+        leaning[1].price,
+        plain[1].price.offset_bps(-5, Rounding::Up)?
+    );
+    assert_eq!(leaning[1].amount.raw(), plain[1].amount.raw() * 5 / 4);
+
+    // Past the 90% limit only sells are quoted, below 10% only buys
+    let heavy = quotes(&skewed, "10", "1000")?;
+    assert!(!heavy.is_empty());
+    assert!(heavy.iter().all(|q| q.side == OrderType::Sell));
+    let light = quotes(&skewed, "0.01", "3000")?;
+    assert!(!light.is_empty());
+    assert!(light.iter().all(|q| q.side == OrderType::Buy));
+    Ok(())
+}
+
+#[tokio::test]
+async fn maker_opens_quotes_then_keeps_them() -> Result<()> {
+    let test = TestMarket::new().await?;
+    let ctx = test.context(test.wallet(1)).await?;
+    let maker = MarketMaker::new(
+        &ctx,
+        StaticPrice::new("fixed", 3000.0),
+        BandStrategy::new(2, 10, 20, 100.0),
+    );
+
+    let first = maker.cycle().await?;
+    assert_eq!(first.opened.len(), 4);
+    assert!(first.cancelled.is_empty());
+    assert!(first.deposited_base > 0);
+    assert!(first.deposited_quote > 0);
+    assert_eq!(
+        ctx.market.user_orders(ctx.wallet_id()).await?.value.len(),
+        4
+    );
+
+    // Same fair value, same quotes: nothing to do
+    let second = maker.cycle().await?;
+    assert_eq!(second.kept, 4);
+    assert_eq!(second.opened.len(), 0);
+    assert!(second.cancelled.is_empty());
+    assert!(second.tx_ids.is_empty());
+
+    // A new fair value replaces every order
+    let moved = MarketMaker::new(
+        &ctx,
+        StaticPrice::new("fixed", 3100.0),
+        BandStrategy::new(2, 10, 20, 100.0),
+    );
+    let third = moved.cycle().await?;
+    assert_eq!(third.cancelled.len(), 4);
+    assert_eq!(third.opened.len(), 4);
+    assert_eq!(
+        ctx.market.user_orders(ctx.wallet_id()).await?.value.len(),
+        4
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn maker_dry_run_sends_nothing() -> Result<()> {
+    let test = TestMarket::new().await?;
+    let ctx = test.context(test.wallet(1)).await?;
+    let maker = MarketMaker::new(
+        &ctx,
+        StaticPrice::new("fixed", 3000.0),
+        BandStrategy::new(2, 10, 20, 100.0),
+    )
+    .with_config(MakerConfig {
+        dry_run: true,
+        ..MakerConfig::default()
+    });
+
+    // The deposits and opens fit one transaction, so the opens can use the deposits
+    let report = maker.cycle().await?;
+    assert_eq!(report.opened.len(), 4);
+    assert!(report.deposited_base > 0);
+    assert!(report.tx_ids.is_empty());
+    assert_eq!(report.simulations.len(), 1);
+    assert!(
+        report.simulations[0].is_success(),
+        "{}",
+        report.simulations[0]
+    );
+    assert_eq!(report.simulations[0].opened().len(), 4);
+
+    assert!(ctx
+        .market
+        .user_orders(ctx.wallet_id())
+        .await?
+        .value
+        .is_empty());
+    let account = ctx.market.account(ctx.wallet_id()).await?.value;
+    assert_eq!(account.liquid.base + account.locked.base, 0);
+    Ok(())
+}
+
+#[tokio::test]
+async fn maker_leaves_orders_alone_on_small_moves() -> Result<()> {
+    let test = TestMarket::new().await?;
+    let ctx = test.context(test.wallet(1)).await?;
+    let config = MakerConfig {
+        tolerance: Tolerance {
+            price_bps: 5,
+            amount_bps: 500,
+        },
+        ..MakerConfig::default()
+    };
+    let strategy = BandStrategy::new(2, 10, 20, 100.0);
+
+    let first = MarketMaker::new(&ctx, StaticPrice::new("fixed", 3000.0), strategy.clone())
+        .with_config(config.clone())
+        .cycle()
+        .await?;
+    assert_eq!(first.opened.len(), 4);
+
+    // 0.03% is inside the tolerance: no transactions at all
+    let nudged = MarketMaker::new(&ctx, StaticPrice::new("fixed", 3000.9), strategy.clone())
+        .with_config(config.clone())
+        .cycle()
+        .await?;
+    assert_eq!(nudged.kept, 4);
+    assert!(nudged.cancelled.is_empty());
+    assert!(nudged.tx_ids.is_empty());
+
+    // 1% is not
+    let moved = MarketMaker::new(&ctx, StaticPrice::new("fixed", 3030.0), strategy)
+        .with_config(config)
+        .cycle()
+        .await?;
+    assert_eq!(moved.kept, 0);
+    assert_eq!(moved.cancelled.len(), 4);
+    assert_eq!(moved.opened.len(), 4);
+    Ok(())
+}
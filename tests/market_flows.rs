@@ -0,0 +1,154 @@
+//! Deposit, open, cancel, match and withdraw against a local node.
+//!
+//! Run with `cargo test`; fuel-core is embedded, no network or mnemonic needed.
+
+mod harness;
+
+use anyhow::Result;
+use fuels::accounts::ViewOnlyAccount;
+use harness::{identity, TestMarket, MAKER_FEE_BPS, MATCHER_FEE, TAKER_FEE_BPS};
+use spark_examples::amount::Rounding;
+use spark_market_sdk::{AssetType, OrderType};
+
+#[tokio::test]
+async fn context_describes_deployed_market() -> Result<()> {
+    let test = TestMarket::new().await?;
+    let ctx = test.context(test.wallet(1)).await?;
+
+    let info = &ctx.market_info;
+    assert_eq!(info.contract_id, test.market_id);
+    assert_eq!(info.base.asset_id, test.base.asset_id);
+    assert_eq!(info.base.decimals, test.base.decimals);
+    assert_eq!(info.quote.asset_id, test.quote.asset_id);
+    assert_eq!(info.quote.decimals, test.quote.decimals);
+    assert!(info.registered);
+    assert_eq!(info.matcher_fee, MATCHER_FEE);
+    assert_eq!(info.protocol_fee[0].maker_fee, MAKER_FEE_BPS);
+    assert_eq!(info.protocol_fee[0].taker_fee, TAKER_FEE_BPS);
+    Ok(())
+}
+
+#[tokio::test]
+async fn deposit_and_withdraw() -> Result<()> {
+    let test = TestMarket::new().await?;
+    let wallet = test.wallet(1);
+    let ctx = test.context(wallet).await?;
+    let before = wallet.get_asset_balance(&test.base.asset_id).await?;
+
+    let amount = test.base.parse("1.5")?.raw();
+    ctx.market.deposit(amount, test.base.asset_id).await?;
+
+    let account = ctx.market.account(ctx.wallet_id()).await?.value;
+    assert_eq!(account.liquid.base, amount);
+    assert_eq!(account.liquid.quote, 0);
+    assert_eq!(
+        wallet.get_asset_balance(&test.base.asset_id).await?,
+        before - amount
+    );
+
+    ctx.market.withdraw(amount, AssetType::Base).await?;
+
+    let account = ctx.market.account(ctx.wallet_id()).await?.value;
+    assert_eq!(account.liquid.base, 0);
+    assert_eq!(wallet.get_asset_balance(&test.base.asset_id).await?, before);
+    Ok(())
+}
+
+#[tokio::test]
+async fn open_and_cancel() -> Result<()> {
+    let test = TestMarket::new().await?;
+    let ctx = test.context(test.wallet(1)).await?;
+    let info = &ctx.market_info;
+
+    let deposit = test.quote.units(10_000)?.raw();
+    ctx.market.deposit(deposit, test.quote.asset_id).await?;
+
+    let amount = info.base.parse("0.5")?;
+    let price = info.parse_price("3000")?;
+    let id = ctx
+        .market
+        .open_order(amount.raw(), OrderType::Buy, price.raw())
+        .await?
+        .value;
+
+    let orders = ctx.market.user_orders(ctx.wallet_id()).await?.value;
+    assert_eq!(orders, vec![id]);
+    let order = ctx.market.order(id).await?.value.expect("order is open");
+    assert_eq!(order.amount, amount.raw());
+    assert_eq!(order.price, price.raw());
+    let account = ctx.market.account(ctx.wallet_id()).await?.value;
+    assert!(account.locked.quote > 0);
+
+    ctx.market.cancel_order(id).await?;
+
+    assert!(ctx.market.order(id).await?.value.is_none());
+    assert!(ctx
+        .market
+        .user_orders(ctx.wallet_id())
+        .await?
+        .value
+        .is_empty());
+    let account = ctx.market.account(ctx.wallet_id()).await?.value;
+    assert_eq!(account.locked.quote, 0);
+    assert_eq!(account.liquid.quote, deposit);
+    Ok(())
+}
+
+#[tokio::test]
+async fn match_order_pair() -> Result<()> {
+    let test = TestMarket::new().await?;
+    let buyer = test.context(test.wallet(1)).await?;
+    let seller = test.context(test.wallet(2)).await?;
+    let matcher = test.market(test.wallet(3)).await;
+    let info = &buyer.market_info;
+
+    let amount = info.base.parse("0.25")?;
+    let price = info.parse_price("3200")?;
+
+    buyer
+        .market
+        .deposit(test.quote.units(10_000)?.raw(), test.quote.asset_id)
+        .await?;
+    // Fees are paid in quote, so the seller brings some along
+    let seller_quote = test.quote.units(100)?.raw();
+    seller
+        .market
+        .deposit(amount.raw(), test.base.asset_id)
+        .await?;
+    seller
+        .market
+        .deposit(seller_quote, test.quote.asset_id)
+        .await?;
+
+    let buy = buyer
+        .market
+        .open_order(amount.raw(), OrderType::Buy, price.raw())
+        .await?
+        .value;
+    let sell = seller
+        .market
+        .open_order(amount.raw(), OrderType::Sell, price.raw())
+        .await?
+        .value;
+
+    matcher.match_order_pair(buy, sell).await?;
+
+    assert!(buyer.market.order(buy).await?.value.is_none());
+    assert!(buyer.market.order(sell).await?.value.is_none());
+
+    let bought = buyer.market.account(identity(test.wallet(1))).await?.value;
+    assert_eq!(bought.liquid.base, amount.raw());
+    assert_eq!(bought.locked.quote, 0);
+
+    // The seller is paid the quote value less fees
+    let sold = seller.market.account(identity(test.wallet(2))).await?.value;
+    let value = info.quote_for(amount, price, Rounding::Down)?;
+    assert_eq!(sold.liquid.base, 0);
+    assert!(sold.liquid.quote > seller_quote);
+    assert!(sold.liquid.quote <= seller_quote + value.raw());
+
+    // and the matcher earns its fee
+    let matched = matcher.account(identity(test.wallet(3))).await?.value;
+    assert!(matched.liquid.quote >= MATCHER_FEE);
+    Ok(())
+}
@@ -0,0 +1,242 @@
+mod harness;
+
+use anyhow::Result;
+use fuels::types::{Bits256, Identity};
+use harness::TestMarket;
+use spark_examples::{
+    amount::{Price, Quantity},
+    indexer::{EventStore, Indexer},
+    matcher::{crossings, BatchMatcher, Matcher, MatcherConfig, PairStatus},
+    oracle::StaticPrice,
+    orderbook::BookOrder,
+    SparkContext, SparkError,
+};
+use spark_market_sdk::OrderType;
+
+fn order(id: u8, side: OrderType, amount: u64, price: u64) -> BookOrder {
+    BookOrder {
+        id: Bits256([id; 32]),
+        side,
+        amount: Quantity::from_raw(amount, 9),
+        price: Price::from_raw(price, 9),
+        owner: Identity::Address(Default::default()),
+    }
+}
+
+async fn open(ctx: &SparkContext, side: OrderType, amount: &str, price: &str) -> Result<Bits256> {
+    let info = &ctx.market_info;
+    let amount = info.base.parse(amount)?.raw();
+    let price = info.parse_price(price)?.raw();
+    Ok(ctx.market.open_order(amount, side, price).await?.value)
+}
+
+#[test]
+fn crossings_follow_price_time_priority() {
+    let orders = vec![
+        order(1, OrderType::Buy, 5, 100),
+        order(2, OrderType::Buy, 5, 101),
+        order(3, OrderType::Buy, 5, 101),
+        order(4, OrderType::Sell, 8, 99),
+        order(5, OrderType::Sell, 5, 102),
+    ];
+    // Order 3 was placed before order 2 at the same price.
+    let placed = |id: &Bits256| match id.0[0] {
+        3 => Some(0),
+        2 => Some(1),
+        _ => None,
+    };
+
+    let found = crossings(&orders, placed);
+    assert_eq!(found.len(), 2);
+    assert_eq!(found[0].buy, Bits256([3; 32]));
+    assert_eq!(found[0].sell, Bits256([4; 32]));
+    assert_eq!(found[0].amount.raw(), 5);
+    assert_eq!(found[1].buy, Bits256([2; 32]));
+    assert_eq!(found[1].amount.raw(), 3);
+    assert_eq!(found[1].sell_remaining.raw(), 3);
+}
+
+#[test]
+fn nothing_crosses_when_bids_are_below_asks() {
+    let orders = vec![
+        order(1, OrderType::Buy, 5, 100),
+        order(2, OrderType::Sell, 5, 101),
+    ];
+    assert!(crossings(&orders, |_| None).is_empty());
+}
+
+#[tokio::test]
+async fn matches_crossing_orders_it_finds_in_the_events() -> Result<()> {
+    let test = TestMarket::new().await?;
+    let buyer = test.context(test.wallet(1)).await?;
+    let seller = test.context(test.wallet(2)).await?;
+    let matcher = test.context(test.wallet(3)).await?;
+    let info = &buyer.market_info;
+
+    let amount = info.base.parse("0.5")?.raw();
+    let price = info.parse_price("3000")?.raw();
+    buyer
+        .market
+        .deposit(test.quote.units(10_000)?.raw(), test.quote.asset_id)
+        .await?;
+    seller.market.deposit(amount, test.base.asset_id).await?;
+    let buy = buyer
+        .market
+        .open_order(amount, OrderType::Buy, price)
+        .await?
+        .value;
+    let sell = seller
+        .market
+        .open_order(amount, OrderType::Sell, price)
+        .await?
+        .value;
+
+    let store = EventStore::in_memory(test.market_id)?;
+    let indexer = Indexer::new(&matcher.market, test.provider.clone(), store);
+    let mut bot = Matcher::new(&matcher, indexer, StaticPrice::new("gas", 0.0));
+    let report = bot.cycle().await?;
+    assert_eq!(report.crossings, 1);
+    assert_eq!(report.matched.len(), 1, "{:?}", report.failed);
+    assert_eq!(report.matched[0].crossing.buy, buy);
+    assert_eq!(report.matched[0].crossing.sell, sell);
+
+    let report = bot.cycle().await?;
+    assert_eq!(report.orders, 0);
+    assert!(report.matched.is_empty());
+    Ok(())
+}
+
+#[tokio::test]
+async fn dry_run_simulates_the_matches() -> Result<()> {
+    let test = TestMarket::new().await?;
+    let buyer = test.context(test.wallet(1)).await?;
+    let seller = test.context(test.wallet(2)).await?;
+    let matcher = test.context(test.wallet(3)).await?;
+    buyer
+        .market
+        .deposit(test.quote.units(10_000)?.raw(), test.quote.asset_id)
+        .await?;
+    seller
+        .market
+        .deposit(test.base.units(1)?.raw(), test.base.asset_id)
+        .await?;
+    open(&buyer, OrderType::Buy, "0.5", "3000").await?;
+    open(&seller, OrderType::Sell, "0.5", "3000").await?;
+
+    let store = EventStore::in_memory(test.market_id)?;
+    let indexer = Indexer::new(&matcher.market, test.provider.clone(), store);
+    let mut bot =
+        Matcher::new(&matcher, indexer, StaticPrice::new("gas", 0.0)).with_config(MatcherConfig {
+            dry_run: true,
+            ..MatcherConfig::default()
+        });
+    let report = bot.cycle().await?;
+    assert_eq!(report.crossings, 1);
+    assert!(report.matched.is_empty());
+    assert!(report.failed.is_empty(), "{:?}", report.failed);
+    assert_eq!(report.simulations.len(), 1);
+    assert_eq!(report.simulations[0].trades().len(), 1);
+
+    // Both orders are still there to match
+    let report = bot.cycle().await?;
+    assert_eq!(report.orders, 2);
+    assert_eq!(report.crossings, 1);
+    Ok(())
+}
+
+#[tokio::test]
+async fn leaves_crossings_the_fee_does_not_pay_for() -> Result<()> {
+    let test = TestMarket::new().await?;
+    let buyer = test.context(test.wallet(1)).await?;
+    let seller = test.context(test.wallet(2)).await?;
+    let matcher = test.context(test.wallet(3)).await?;
+    let info = &buyer.market_info;
+
+    let amount = info.base.parse("0.5")?.raw();
+    let price = info.parse_price("3000")?.raw();
+    buyer
+        .market
+        .deposit(test.quote.units(10_000)?.raw(), test.quote.asset_id)
+        .await?;
+    seller.market.deposit(amount, test.base.asset_id).await?;
+    buyer
+        .market
+        .open_order(amount, OrderType::Buy, price)
+        .await?;
+    seller
+        .market
+        .open_order(amount, OrderType::Sell, price)
+        .await?;
+
+    let store = EventStore::in_memory(test.market_id)?;
+    let indexer = Indexer::new(&matcher.market, test.provider.clone(), store);
+    let mut bot = Matcher::new(&matcher, indexer, StaticPrice::new("gas", 1e12));
+    let report = bot.cycle().await?;
+    assert_eq!(report.crossings, 1);
+    assert_eq!(report.unprofitable, 1);
+    assert!(report.matched.is_empty());
+    Ok(())
+}
+
+#[tokio::test]
+async fn batch_matches_pairs_and_skips_closed_orders() -> Result<()> {
+    let test = TestMarket::new().await?;
+    let buyer = test.context(test.wallet(1)).await?;
+    let seller = test.context(test.wallet(2)).await?;
+    let matcher = test.context(test.wallet(3)).await?;
+    let info = &buyer.market_info;
+
+    buyer
+        .market
+        .deposit(test.quote.units(50_000)?.raw(), test.quote.asset_id)
+        .await?;
+    seller
+        .market
+        .deposit(info.base.units(5)?.raw(), test.base.asset_id)
+        .await?;
+    let buy = open(&buyer, OrderType::Buy, "1", "3000").await?;
+    let sell = open(&seller, OrderType::Sell, "0.4", "2990").await?;
+    let second_sell = open(&seller, OrderType::Sell, "1", "2995").await?;
+    let cancelled = open(&seller, OrderType::Sell, "0.5", "2980").await?;
+    let expensive = open(&seller, OrderType::Sell, "0.5", "3100").await?;
+    seller.market.cancel_order(cancelled).await?;
+
+    let mut batch = BatchMatcher::new(&matcher).max_calls(2);
+    batch.push(buy, expensive);
+    batch.push(sell, buy);
+    batch.push(buy, second_sell);
+    batch.push(buy, cancelled);
+    batch.push(buy, second_sell);
+    let report = batch.submit().await?;
+
+    assert_eq!(report.matched(), 2);
+    assert_eq!(report.skipped(), 3);
+    // The buy is still open in full, but its bid is below the ask
+    assert!(matches!(
+        report.pairs[0].status,
+        PairStatus::Skipped {
+            reason: SparkError::NotMatchable(_)
+        }
+    ));
+    assert_eq!(report.pairs[1].buy, buy);
+    assert_eq!(report.pairs[1].filled(), Some(info.base.parse("0.4")?));
+    assert_eq!(report.pairs[2].filled(), Some(info.base.parse("0.6")?));
+    assert_eq!(report.filled(info.base.decimals)?, info.base.parse("1")?);
+    // The seller cancelled this one before the batch
+    assert!(matches!(
+        report.pairs[3].status,
+        PairStatus::Skipped {
+            reason: SparkError::OrderNotFound(_)
+        }
+    ));
+    // The buy is filled by pairs 1 and 2
+    assert!(matches!(
+        report.pairs[4].status,
+        PairStatus::Skipped {
+            reason: SparkError::OrderNotFound(_)
+        }
+    ));
+    assert_eq!(report.batch.tx_ids().len(), 1);
+    assert!(buyer.market.order(buy).await?.value.is_none());
+    Ok(())
+}
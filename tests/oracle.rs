@@ -0,0 +1,209 @@
+use std::{
+    io::{BufRead, BufReader, Write},
+    net::TcpListener,
+    thread,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::Result;
+use spark_examples::oracle::{
+    Binance, CoinGecko, FilePrice, Oracle, PriceSource, Pyth, StaticPrice,
+};
+
+const ETH_FEED: &str = "ff61491a931112ddf1bd8147cd1b641375f79f5825126d665480874634fd0ace";
+
+/// Serves canned JSON on a local port: the first route whose prefix matches the
+/// request path answers, anything else is a 404.
+fn mock_server(routes: Vec<(&'static str, String)>) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            let mut stream = stream.unwrap();
+            let mut request_line = String::new();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            reader.read_line(&mut request_line).unwrap();
+            // Drain the headers
+            let mut line = String::new();
+            while reader.read_line(&mut line).unwrap() > 2 {
+                line.clear();
+            }
+
+            let path = request_line.split_whitespace().nth(1).unwrap_or("/");
+            let response = match routes.iter().find(|(prefix, _)| path.starts_with(prefix)) {
+                Some((_, body)) => format!(
+                    "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    body.len(),
+                    body
+                ),
+                None => "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+                    .to_string(),
+            };
+            let _ = stream.write_all(response.as_bytes());
+        }
+    });
+    url
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
+
+#[tokio::test]
+async fn reads_http_sources() -> Result<()> {
+    let url = mock_server(vec![
+        (
+            "/api/v3/simple/price",
+            format!(
+                r#"{{"ethereum":{{"usd":3201.5,"last_updated_at":{}}}}}"#,
+                now_secs()
+            ),
+        ),
+        (
+            "/api/v3/ticker/price",
+            r#"{"symbol":"ETHUSDT","price":"3202.25000000"}"#.to_string(),
+        ),
+        (
+            "/v2/updates/price/latest",
+            format!(
+                r#"{{"parsed":[{{"id":"{}","price":{{"price":"320050000000","conf":"1","expo":-8,"publish_time":{}}}}}]}}"#,
+                ETH_FEED,
+                now_secs()
+            ),
+        ),
+    ]);
+
+    let coingecko = CoinGecko::new("ethereum", "usd").with_base_url(&url);
+    assert_eq!(coingecko.price().await?.price, 3201.5);
+    let binance = Binance::new("ETHUSDT").with_base_url(&url);
+    assert_eq!(binance.price().await?.price, 3202.25);
+    let pyth = Pyth::new(ETH_FEED).with_base_url(&url);
+    assert!((pyth.price().await?.price - 3200.5).abs() < 1e-9);
+
+    let consensus = Oracle::new()
+        .source(coingecko)
+        .source(binance)
+        .source(pyth)
+        .min_sources(3)
+        .consensus()
+        .await?;
+    assert_eq!(consensus.price, 3201.5);
+    assert!(consensus.rejected.is_empty());
+    Ok(())
+}
+
+#[tokio::test]
+async fn fails_over_to_healthy_sources() -> Result<()> {
+    // Nothing is routed, so every HTTP source gets a 404
+    let url = mock_server(vec![]);
+
+    let consensus = Oracle::new()
+        .source(CoinGecko::new("ethereum", "usd").with_base_url(&url))
+        .source(StaticPrice::new("fallback", 3000.0))
+        .consensus()
+        .await?;
+    assert_eq!(consensus.price, 3000.0);
+    assert_eq!(consensus.rejected.len(), 1);
+    assert_eq!(consensus.rejected[0].source, "coingecko");
+
+    let failed = Oracle::new()
+        .source(CoinGecko::new("ethereum", "usd").with_base_url(&url))
+        .source(Binance::new("ETHUSDT").with_base_url(&url))
+        .consensus()
+        .await;
+    assert!(failed.is_err());
+    Ok(())
+}
+
+#[tokio::test]
+async fn drops_stale_prices() -> Result<()> {
+    let url = mock_server(vec![(
+        "/api/v3/simple/price",
+        format!(
+            r#"{{"ethereum":{{"usd":2500.0,"last_updated_at":{}}}}}"#,
+            now_secs() - 3600
+        ),
+    )]);
+
+    let consensus = Oracle::new()
+        .source(CoinGecko::new("ethereum", "usd").with_base_url(&url))
+        .source(StaticPrice::new("fresh", 3000.0))
+        .max_age(Duration::from_secs(60))
+        .consensus()
+        .await?;
+    assert_eq!(consensus.price, 3000.0);
+    assert!(consensus.rejected[0].reason.starts_with("stale"));
+    Ok(())
+}
+
+#[tokio::test]
+async fn drops_outliers_from_the_median() -> Result<()> {
+    let consensus = Oracle::new()
+        .source(StaticPrice::new("a", 3000.0))
+        .source(StaticPrice::new("b", 3010.0))
+        .source(StaticPrice::new("c", 3020.0))
+        .source(StaticPrice::new("broken", 30.0))
+        .max_deviation_bps(100)
+        .consensus()
+        .await?;
+    assert_eq!(consensus.price, 3010.0);
+    assert_eq!(consensus.used.len(), 3);
+    assert_eq!(consensus.rejected[0].source, "broken");
+
+    let disagreeing = Oracle::new()
+        .source(StaticPrice::new("a", 3000.0))
+        .source(StaticPrice::new("b", 4000.0))
+        .max_deviation_bps(100)
+        .min_sources(2)
+        .consensus()
+        .await;
+    assert!(disagreeing.is_err());
+    Ok(())
+}
+
+#[tokio::test]
+async fn two_sources_fail_over_to_the_first() -> Result<()> {
+    // Close enough: both are used
+    let consensus = Oracle::new()
+        .source(StaticPrice::new("a", 3000.0))
+        .source(StaticPrice::new("b", 3020.0))
+        .max_deviation_bps(100)
+        .consensus()
+        .await?;
+    assert_eq!(consensus.price, 3010.0);
+    assert_eq!(consensus.used.len(), 2);
+    assert!(consensus.rejected.is_empty());
+
+    // Too far apart, even past twice the deviation, so the first source wins
+    // rather than both being dropped or averaged
+    for broken in [3100.0, 30.0] {
+        let consensus = Oracle::new()
+            .source(StaticPrice::new("primary", 3000.0))
+            .source(StaticPrice::new("secondary", broken))
+            .max_deviation_bps(100)
+            .consensus()
+            .await?;
+        assert_eq!(consensus.price, 3000.0);
+        assert_eq!(consensus.used.len(), 1);
+        assert_eq!(consensus.rejected.len(), 1);
+        assert_eq!(consensus.rejected[0].source, "secondary");
+    }
+    Ok(())
+}
+
+#[tokio::test]
+async fn reads_price_files() -> Result<()> {
+    let path = std::env::temp_dir().join(format!("spark-price-{}.txt", std::process::id()));
+    std::fs::write(&path, "3123.45\n")?;
+
+    let point = FilePrice::new(&path).price().await?;
+    assert_eq!(point.price, 3123.45);
+
+    std::fs::write(&path, "not a price")?;
+    assert!(FilePrice::new(&path).price().await.is_err());
+    std::fs::remove_file(&path)?;
+    Ok(())
+}
@@ -0,0 +1,64 @@
+mod harness;
+
+use anyhow::Result;
+use fuels::types::Bits256;
+use harness::TestMarket;
+use spark_examples::{OrderBook, SparkContext};
+use spark_market_sdk::OrderType;
+
+async fn open(ctx: &SparkContext, side: OrderType, amount: &str, price: &str) -> Result<Bits256> {
+    let info = &ctx.market_info;
+    let amount = info.base.parse(amount)?.raw();
+    let price = info.parse_price(price)?.raw();
+    Ok(ctx.market.open_order(amount, side, price).await?.value)
+}
+
+#[tokio::test]
+async fn aggregates_levels_across_makers() -> Result<()> {
+    let test = TestMarket::new().await?;
+    let alice = test.context(test.wallet(1)).await?;
+    let bob = test.context(test.wallet(2)).await?;
+    for ctx in [&alice, &bob] {
+        ctx.market
+            .deposit(test.base.units(10)?.raw(), test.base.asset_id)
+            .await?;
+        ctx.market
+            .deposit(test.quote.units(100_000)?.raw(), test.quote.asset_id)
+            .await?;
+    }
+
+    open(&alice, OrderType::Buy, "1", "3000").await?;
+    open(&bob, OrderType::Buy, "0.5", "3000").await?;
+    open(&bob, OrderType::Buy, "2", "2990").await?;
+    open(&alice, OrderType::Sell, "0.25", "3010").await?;
+    let cancelled = open(&bob, OrderType::Sell, "1", "3020").await?;
+    bob.market.cancel_order(cancelled).await?;
+
+    let book = OrderBook::builder(&alice.market, &alice.market_info)
+        .maker(alice.wallet_id())
+        .maker(bob.wallet_id())
+        .order_ids([cancelled])
+        .build()
+        .await?;
+
+    assert_eq!(book.orders.len(), 4);
+    assert_eq!(book.bids.len(), 2);
+    assert_eq!(book.asks.len(), 1);
+
+    let info = &alice.market_info;
+    let best_bid = book.best_bid().unwrap();
+    assert_eq!(best_bid.price, info.parse_price("3000")?);
+    assert_eq!(best_bid.amount, info.base.parse("1.5")?);
+    assert_eq!(best_bid.orders, 2);
+    assert_eq!(book.bids[1].depth, info.base.parse("3.5")?);
+    assert_eq!(book.best_ask().unwrap().price, info.parse_price("3010")?);
+
+    assert_eq!(book.spread(), Some(info.parse_price("10")?));
+    assert_eq!(book.mid(), Some(info.parse_price("3005")?));
+    assert!(!book.is_crossed());
+
+    let ladder = book.ladder(5);
+    assert_eq!(ladder.lines().count(), 5);
+    assert!(ladder.contains("spread 10.000000000, mid 3005.000000000"));
+    Ok(())
+}
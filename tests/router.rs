@@ -0,0 +1,167 @@
+mod harness;
+
+use std::path::Path;
+
+use anyhow::Result;
+use fuels::types::{AssetId, Bits256, ContractId, Identity};
+use harness::{described_market, TestMarket, EVENTS_DB};
+use spark_examples::{
+    assets::AssetInfo,
+    orderbook::BookOrder,
+    router::{Router, Venue},
+    MarketInfo, OrderBook,
+};
+use spark_market_sdk::OrderType;
+
+fn eth() -> AssetInfo {
+    AssetInfo::new("ETH", AssetId::new([1; 32]), 9)
+}
+
+fn usdc() -> AssetInfo {
+    AssetInfo::new("USDC", AssetId::new([2; 32]), 6)
+}
+
+fn trmp() -> AssetInfo {
+    AssetInfo::new("TRMP", AssetId::new([3; 32]), 9)
+}
+
+/// A fee-free market with one resting order per `(id, side, amount, price)`.
+fn venue(
+    id: u8,
+    base: AssetInfo,
+    quote: AssetInfo,
+    orders: &[(u8, OrderType, &str, &str)],
+) -> Result<Venue> {
+    let info = MarketInfo {
+        contract_id: ContractId::new([id; 32]),
+        ..described_market(base, quote)
+    };
+    let mut book = Vec::new();
+    for (order, side, amount, price) in orders {
+        book.push(BookOrder {
+            id: Bits256([*order; 32]),
+            side: side.clone(),
+            amount: info.base.parse(amount)?,
+            price: info.parse_price(price)?,
+            owner: Identity::Address(Default::default()),
+        });
+    }
+    let book = OrderBook::from_orders(&info, book)?;
+    Ok(Venue { info, book })
+}
+
+#[test]
+fn walks_the_asks_of_a_direct_market() -> Result<()> {
+    let router = Router::new(vec![venue(
+        1,
+        eth(),
+        usdc(),
+        &[
+            (1, OrderType::Sell, "0.3", "3000"),
+            (2, OrderType::Sell, "1", "3100"),
+            (3, OrderType::Buy, "1", "2900"),
+        ],
+    )?]);
+
+    let plan = router.plan(&usdc(), &eth(), eth().parse("0.5")?)?;
+    assert_eq!(plan.routes.len(), 1);
+    let leg = &plan.routes[0].legs[0];
+    assert_eq!(leg.side, OrderType::Buy);
+    assert_eq!(leg.base, eth().parse("0.5")?);
+    assert_eq!(
+        leg.limit_price,
+        router.venues()[0].info.parse_price("3100")?
+    );
+    assert_eq!(leg.orders.len(), 2);
+    // 0.3 at 3000 and 0.2 at 3100
+    assert_eq!(plan.input()?, usdc().units(1520)?);
+    assert!((plan.average_price()? - 3040.0).abs() < 1e-9);
+    Ok(())
+}
+
+#[test]
+fn routes_through_a_middle_asset_and_splits_across_paths() -> Result<()> {
+    let router = Router::new(vec![
+        venue(1, eth(), usdc(), &[(1, OrderType::Sell, "10", "3000")])?,
+        venue(2, trmp(), usdc(), &[(2, OrderType::Buy, "10000", "2")])?,
+        venue(3, eth(), trmp(), &[(3, OrderType::Sell, "0.2", "1400")])?,
+    ]);
+
+    let plan = router.plan(&trmp(), &eth(), eth().parse("0.5")?)?;
+    assert_eq!(plan.routes.len(), 2);
+    assert_eq!(plan.output()?, eth().parse("0.5")?);
+
+    // The direct market is cheaper until it runs dry
+    let direct = plan
+        .routes
+        .iter()
+        .find(|route| route.legs.len() == 1)
+        .unwrap();
+    assert_eq!(direct.output(), eth().parse("0.2")?);
+    assert_eq!(direct.input(), trmp().units(280)?);
+
+    // The rest sells TRMP for USDC, then buys ETH with it
+    let hops = plan
+        .routes
+        .iter()
+        .find(|route| route.legs.len() == 2)
+        .unwrap();
+    assert_eq!(hops.legs[0].side, OrderType::Sell);
+    assert_eq!(hops.legs[0].output, usdc().units(900)?);
+    assert_eq!(hops.legs[1].side, OrderType::Buy);
+    assert_eq!(hops.input(), trmp().units(450)?);
+
+    assert_eq!(plan.input()?, trmp().units(730)?);
+    assert!((plan.average_price()? - 1460.0).abs() < 1e-9);
+    Ok(())
+}
+
+#[test]
+fn fails_without_enough_liquidity_or_a_path() -> Result<()> {
+    let router = Router::new(vec![venue(
+        1,
+        eth(),
+        usdc(),
+        &[(1, OrderType::Sell, "0.1", "3000")],
+    )?]);
+    assert!(router.plan(&usdc(), &eth(), eth().parse("1")?).is_err());
+    assert!(router.plan(&trmp(), &eth(), eth().parse("0.05")?).is_err());
+    Ok(())
+}
+
+#[tokio::test]
+async fn discovers_registered_markets_and_executes_a_route() -> Result<()> {
+    let test = TestMarket::new().await?;
+    let seller = test.context(test.wallet(1)).await?;
+    let buyer = test.context(test.wallet(2)).await?;
+    let info = &seller.market_info;
+
+    seller
+        .market
+        .deposit(info.base.units(2)?.raw(), info.base.asset_id)
+        .await?;
+    for price in ["3000", "3010"] {
+        seller
+            .market
+            .open_order(
+                info.base.parse("0.3")?.raw(),
+                OrderType::Sell,
+                info.parse_price(price)?.raw(),
+            )
+            .await?;
+    }
+
+    // The seller's orders are found through the market's events
+    let router = Router::discover(&buyer, Path::new(EVENTS_DB), &[]).await?;
+    assert_eq!(router.venues().len(), 1);
+    let plan = router.plan(&test.quote, &test.base, test.base.parse("0.5")?)?;
+    let report = plan.execute(&buyer, 100).await?;
+
+    assert_eq!(report.legs.len(), 1);
+    assert_eq!(report.received, test.base.parse("0.5")?);
+    assert!(report.legs[0].tx_id.is_some());
+    // 0.3 at 3000 and 0.2 at 3010 is 3004, plus the fees
+    let price = report.average_price();
+    assert!(price > 3004.0 && price < 3012.0, "{}", price);
+    Ok(())
+}
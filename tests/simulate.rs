@@ -0,0 +1,143 @@
+mod harness;
+
+use std::path::Path;
+
+use anyhow::Result;
+use fuels::types::Bits256;
+use harness::{identity, TestMarket, EVENTS_DB};
+use spark_examples::{batch::BatchSubmitter, simulate::Simulator, SparkError, Swap};
+use spark_market_sdk::OrderType;
+
+#[tokio::test]
+async fn simulates_an_order_without_sending_it() -> Result<()> {
+    let test = TestMarket::new().await?;
+    let ctx = test.context(test.wallet(1)).await?;
+    let info = &ctx.market_info;
+    ctx.market
+        .deposit(info.base.units(1)?.raw(), info.base.asset_id)
+        .await?;
+
+    let amount = info.base.parse("0.4")?;
+    let simulation = Simulator::new(&ctx)
+        .call(ctx.market.get_instance().methods().open_order(
+            amount.raw(),
+            OrderType::Sell,
+            info.parse_price("3000")?.raw(),
+        ))
+        .await?;
+    assert!(simulation.is_success());
+    assert!(simulation.gas_used > 0);
+    assert!(simulation.fee.is_some());
+    let opened = simulation.opened();
+    assert_eq!(opened.len(), 1);
+    assert_eq!(opened[0].amount, amount.raw());
+
+    // A sell locks the base it offers
+    let change = simulation.balance(&ctx.wallet_id(), &info.base).unwrap();
+    assert_eq!(change.liquid_delta(), -(amount.raw() as i128));
+    assert_eq!(change.locked_delta(), amount.raw() as i128);
+    assert!(simulation.balance(&ctx.wallet_id(), &info.quote).is_none());
+
+    // Nothing was sent
+    assert!(ctx
+        .market
+        .user_orders(ctx.wallet_id())
+        .await?
+        .value
+        .is_empty());
+    let account = ctx.market.account(ctx.wallet_id()).await?.value;
+    assert_eq!(account.liquid.base, info.base.units(1)?.raw());
+    assert_eq!(account.locked.base, 0);
+    Ok(())
+}
+
+#[tokio::test]
+async fn simulates_a_cancel_all_and_reports_reverts() -> Result<()> {
+    let test = TestMarket::new().await?;
+    let ctx = test.context(test.wallet(1)).await?;
+    let info = &ctx.market_info;
+    ctx.market
+        .deposit(info.base.units(1)?.raw(), info.base.asset_id)
+        .await?;
+    for price in ["3000", "3001"] {
+        ctx.market
+            .open_order(
+                info.base.parse("0.1")?.raw(),
+                OrderType::Sell,
+                info.parse_price(price)?.raw(),
+            )
+            .await?;
+    }
+    let ids = ctx.market.user_orders(ctx.wallet_id()).await?.value;
+
+    let mut batch = BatchSubmitter::new(&ctx);
+    for id in &ids {
+        batch.push(ctx.market.get_instance().methods().cancel_order(*id));
+    }
+    let simulations = batch.simulate().await?;
+    assert_eq!(simulations.len(), 1);
+    assert_eq!(simulations[0].cancelled().len(), 2);
+    let change = simulations[0]
+        .balance(&ctx.wallet_id(), &info.base)
+        .unwrap();
+    assert_eq!(change.locked_after, 0);
+    assert_eq!(
+        ctx.market.user_orders(ctx.wallet_id()).await?.value.len(),
+        2
+    );
+
+    // Nobody opened this one
+    let simulation = Simulator::new(&ctx)
+        .call(
+            ctx.market
+                .get_instance()
+                .methods()
+                .cancel_order(Bits256([7; 32])),
+        )
+        .await?;
+    assert!(matches!(
+        simulation.error,
+        Some(SparkError::OrderNotFound(_))
+    ));
+    assert!(simulation.events.is_empty());
+    assert!(simulation.balances.is_empty());
+    Ok(())
+}
+
+#[tokio::test]
+async fn simulates_a_swap_with_its_trades() -> Result<()> {
+    let test = TestMarket::new().await?;
+    let seller = test.context(test.wallet(1)).await?;
+    let buyer = test.context(test.wallet(2)).await?;
+    let info = &seller.market_info;
+    seller
+        .market
+        .deposit(info.base.units(1)?.raw(), info.base.asset_id)
+        .await?;
+    seller
+        .market
+        .open_order(
+            info.base.parse("0.5")?.raw(),
+            OrderType::Sell,
+            info.parse_price("3000")?.raw(),
+        )
+        .await?;
+    buyer
+        .market
+        .deposit(info.quote.units(2_000)?.raw(), info.quote.asset_id)
+        .await?;
+
+    let swap = Swap::load(&buyer, Path::new(EVENTS_DB), &[identity(test.wallet(1))]).await?;
+    let preview = swap.preview(OrderType::Buy, info.base.parse("0.2")?, 50)?;
+    let simulation = swap.simulate(&preview).await?;
+    assert!(simulation.is_success());
+    let trades = simulation.trades();
+    assert_eq!(trades.len(), 1);
+    assert_eq!(trades[0].trade_size, info.base.parse("0.2")?.raw());
+    let bought = simulation.balance(&buyer.wallet_id(), &info.base).unwrap();
+    assert_eq!(bought.liquid_delta(), info.base.parse("0.2")?.raw() as i128);
+
+    let account = buyer.market.account(buyer.wallet_id()).await?.value;
+    assert_eq!(account.liquid.base, 0);
+    Ok(())
+}
@@ -0,0 +1,124 @@
+mod harness;
+
+use std::path::Path;
+
+use anyhow::Result;
+use harness::{identity, TestMarket, EVENTS_DB};
+use spark_examples::{SparkContext, SparkError, Swap};
+use spark_market_sdk::{LimitType, OrderType};
+
+/// Rests sells of 0.3 ETH at 3000 and 3010 and 1 ETH at 3100.
+async fn asks(seller: &SparkContext) -> Result<()> {
+    let info = &seller.market_info;
+    seller
+        .market
+        .deposit(info.base.units(2)?.raw(), info.base.asset_id)
+        .await?;
+    for (amount, price) in [("0.3", "3000"), ("0.3", "3010"), ("1", "3100")] {
+        seller
+            .market
+            .open_order(
+                info.base.parse(amount)?.raw(),
+                OrderType::Sell,
+                info.parse_price(price)?.raw(),
+            )
+            .await?;
+    }
+    Ok(())
+}
+
+#[tokio::test]
+async fn previews_fill_fees_and_price_impact() -> Result<()> {
+    let test = TestMarket::new().await?;
+    let seller = test.context(test.wallet(1)).await?;
+    let buyer = test.context(test.wallet(2)).await?;
+    asks(&seller).await?;
+    let info = &buyer.market_info;
+
+    // Every indexed order is taken into account, and none of a wallet without any
+    let swap = Swap::load(&buyer, Path::new(EVENTS_DB), &[]).await?;
+    assert_eq!(swap.book().orders.len(), 3);
+    let nobody = Swap::load(&buyer, Path::new(EVENTS_DB), &[identity(test.wallet(2))]).await?;
+    assert!(nobody.book().orders.is_empty());
+
+    let preview = swap.preview(OrderType::Buy, info.base.parse("0.5")?, 50)?;
+    assert!(preview.is_complete());
+    assert_eq!(preview.orders.len(), 2);
+    assert_eq!(preview.best_price, info.parse_price("3000")?);
+    assert_eq!(preview.limit_price, info.parse_price("3015")?);
+    // 0.3 at 3000 and 0.2 at 3010
+    assert_eq!(preview.value, info.quote.units(1502)?);
+    // 15 bps taker fee plus the 0.01 USDC matcher fee
+    assert_eq!(preview.fees, info.quote.parse("2.263")?);
+    assert_eq!(preview.input, info.quote.parse("1504.263")?);
+    assert_eq!(preview.output, info.base.parse("0.5")?);
+    assert!((preview.average_price - 3004.0).abs() < 1e-9);
+    assert!((preview.price_impact_bps - 13.333).abs() < 0.01);
+
+    // The ask at 3100 is past the slippage
+    let preview = swap.preview(OrderType::Buy, info.base.parse("1")?, 50)?;
+    assert!(!preview.is_complete());
+    assert_eq!(preview.fill, info.base.parse("0.6")?);
+
+    assert!(swap
+        .preview(OrderType::Sell, info.base.parse("1")?, 50)
+        .is_err());
+    Ok(())
+}
+
+#[tokio::test]
+async fn fill_or_kill_is_not_sent_when_it_cannot_fill() -> Result<()> {
+    let test = TestMarket::new().await?;
+    let seller = test.context(test.wallet(1)).await?;
+    let buyer = test.context(test.wallet(2)).await?;
+    asks(&seller).await?;
+    let info = &buyer.market_info;
+    buyer
+        .market
+        .deposit(info.quote.units(10_000)?.raw(), info.quote.asset_id)
+        .await?;
+
+    let swap = Swap::load(&buyer, Path::new(EVENTS_DB), &[identity(test.wallet(1))])
+        .await?
+        .limit_type(LimitType::FOK);
+    let error = swap
+        .swap(OrderType::Buy, info.base.parse("1")?, 50)
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        SparkError::from(&error),
+        SparkError::NotMatchable(_)
+    ));
+    let account = buyer.market.account(buyer.wallet_id()).await?.value;
+    assert_eq!(account.liquid.base, 0);
+    Ok(())
+}
+
+#[tokio::test]
+async fn immediate_or_cancel_takes_the_previewed_orders() -> Result<()> {
+    let test = TestMarket::new().await?;
+    let seller = test.context(test.wallet(1)).await?;
+    let buyer = test.context(test.wallet(2)).await?;
+    asks(&seller).await?;
+    let info = &buyer.market_info;
+
+    let swap = Swap::load(&buyer, Path::new(EVENTS_DB), &[identity(test.wallet(1))]).await?;
+    let preview = swap.preview(OrderType::Buy, info.base.parse("0.5")?, 50)?;
+    // Nothing deposited yet
+    let error = swap.execute(&preview).await.unwrap_err();
+    assert!(matches!(
+        SparkError::from(&error),
+        SparkError::InsufficientBalance(_)
+    ));
+
+    buyer
+        .market
+        .deposit(info.quote.units(2_000)?.raw(), info.quote.asset_id)
+        .await?;
+    let result = swap.execute(&preview).await?;
+    assert!(result.tx_id.is_some());
+    assert_eq!(result.received, info.base.parse("0.5")?);
+    assert!(result.spent.raw() <= preview.input.raw());
+    assert!(result.spent.raw() >= preview.value.raw());
+    Ok(())
+}
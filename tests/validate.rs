@@ -0,0 +1,161 @@
+mod harness;
+
+use anyhow::Result;
+use harness::{described_eth_usdc, TestMarket};
+use spark_examples::{
+    maker::DesiredOrder,
+    validate::{Liquid, OnViolation, OrderRules, OrderValidator, Violation},
+    MarketInfo, SparkError,
+};
+use spark_market_sdk::{OrderType, ProtocolFee};
+
+// Validation is pure, so a described market is enough for most of it
+fn eth_usdc() -> MarketInfo {
+    MarketInfo {
+        protocol_fee: vec![ProtocolFee {
+            maker_fee: 10,
+            taker_fee: 15,
+            volume_threshold: 0,
+        }],
+        matcher_fee: 10_000,
+        min_order_size: 1_000_000,
+        min_order_price: 1_000_000_000,
+        ..described_eth_usdc()
+    }
+}
+
+fn order(market: &MarketInfo, side: OrderType, amount: &str, price: &str) -> DesiredOrder {
+    DesiredOrder {
+        side,
+        amount: market.base.parse(amount).unwrap(),
+        price: market.parse_price(price).unwrap(),
+    }
+}
+
+#[test]
+fn rejects_orders_below_the_market_minimums() {
+    let market = eth_usdc();
+    let validator = OrderValidator::new(&market);
+
+    let tiny = order(&market, OrderType::Sell, "0.0005", "0.5");
+    let rejected = validator.check(&tiny, None).unwrap_err();
+    assert_eq!(
+        rejected.violations,
+        vec![
+            Violation::BelowMinSize {
+                amount: tiny.amount,
+                min: market.base.parse("0.001").unwrap(),
+            },
+            Violation::BelowMinPrice {
+                price: tiny.price,
+                min: market.parse_price("1").unwrap(),
+            },
+        ]
+    );
+    assert!(matches!(
+        SparkError::from(&rejected),
+        SparkError::OrderTooSmall(_)
+    ));
+
+    let zero = order(&market, OrderType::Buy, "0", "3000");
+    assert_eq!(
+        validator.check(&zero, None).unwrap_err().violations,
+        vec![Violation::ZeroAmount]
+    );
+
+    let fine = order(&market, OrderType::Sell, "0.001", "1");
+    assert_eq!(validator.check(&fine, None).unwrap().order, fine);
+}
+
+#[test]
+fn rounds_onto_tick_and_lot_only_when_asked() -> Result<()> {
+    let market = eth_usdc();
+    let rules = OrderRules {
+        tick: market.parse_price("0.5")?,
+        lot: market.base.parse("0.001")?,
+        ..OrderRules::from_market(&market)
+    };
+    let buy = order(&market, OrderType::Buy, "0.0125", "3000.3");
+    let sell = order(&market, OrderType::Sell, "0.0125", "3000.3");
+
+    let strict = OrderValidator::new(&market).rules(rules);
+    let rejected = strict.check(&buy, None).unwrap_err();
+    assert!(matches!(rejected.violations[0], Violation::OffTick { .. }));
+    assert!(matches!(rejected.violations[1], Violation::OffLot { .. }));
+
+    let rounding = strict.on_violation(OnViolation::Round);
+    let valid_buy = rounding.check(&buy, None).unwrap();
+    assert_eq!(valid_buy.order.price, market.parse_price("3000")?);
+    assert_eq!(valid_buy.order.amount, market.base.parse("0.012")?);
+    assert_eq!(valid_buy.rounded.len(), 2);
+    let valid_sell = rounding.check(&sell, None).unwrap();
+    assert_eq!(valid_sell.order.price, market.parse_price("3000.5")?);
+    Ok(())
+}
+
+#[test]
+fn buys_need_their_quote_plus_fees_in_the_liquid_balance() -> Result<()> {
+    let market = eth_usdc();
+    let validator = OrderValidator::new(&market);
+    let buy = order(&market, OrderType::Buy, "1", "3000");
+
+    // 3000 USDC, 0.15% taker fee and the 0.01 USDC matcher fee
+    let (_, quote, fees) = validator.locks(&buy)?;
+    assert_eq!(quote, market.quote.parse("3004.51")?);
+    assert_eq!(fees, market.quote.parse("4.51")?);
+
+    let short = Liquid {
+        base: market.base.parse("0")?,
+        quote: market.quote.parse("3004.50")?,
+    };
+    let rejected = validator.check(&buy, Some(&short)).unwrap_err();
+    assert_eq!(
+        rejected.violations,
+        vec![Violation::InsufficientBalance {
+            symbol: "USDC".to_string(),
+            needed: quote,
+            liquid: short.quote,
+        }]
+    );
+
+    // Each accepted order uses up the balance for the next one
+    let enough = Liquid {
+        quote: market.quote.parse("3004.51")?,
+        ..short
+    };
+    let checked = validator.check_all(&[buy.clone(), buy], Some(enough));
+    assert!(checked[0].is_ok());
+    assert!(checked[1].is_err());
+    Ok(())
+}
+
+#[tokio::test]
+async fn reads_limits_from_the_market() -> Result<()> {
+    let test = TestMarket::new().await?;
+    let owner = test.market(&test.owner).await;
+    owner.set_min_order_size(5_000_000).await?;
+    owner.set_min_order_price(1_000 * 10u64.pow(9)).await?;
+
+    let ctx = test.context(test.wallet(1)).await?;
+    let market = &ctx.market_info;
+    assert_eq!(market.min_order_size, 5_000_000);
+    assert_eq!(market.min_order_price, 1_000 * 10u64.pow(9));
+
+    let validator = OrderValidator::for_wallet(&ctx).await?;
+    let liquid = Liquid::load(&ctx).await?;
+    let rejected = validator
+        .check(
+            &order(market, OrderType::Sell, "0.001", "3000"),
+            Some(&liquid),
+        )
+        .unwrap_err();
+    assert!(matches!(
+        rejected.violations[0],
+        Violation::BelowMinSize { .. }
+    ));
+    assert!(matches!(
+        rejected.violations[1],
+        Violation::InsufficientBalance { .. }
+    ));
+    Ok(())
+}
@@ -0,0 +1,80 @@
+use anyhow::Result;
+use serde::Serialize;
+use spark_examples::views::{render, render_one, BookLevelView, Format};
+
+#[derive(Default, Serialize)]
+struct Row {
+    name: String,
+    amount: Option<u64>,
+    note: String,
+}
+
+fn row(name: &str, amount: Option<u64>, note: &str) -> Row {
+    Row {
+        name: name.to_string(),
+        amount,
+        note: note.to_string(),
+    }
+}
+
+#[test]
+fn csv_quotes_the_fields_that_need_it() -> Result<()> {
+    let rows = [
+        row("plain", Some(1), "nothing to escape"),
+        row("a,b", None, "say \"hi\""),
+        row("multi", Some(20), "two\nlines"),
+    ];
+    assert_eq!(
+        render(&rows, Format::Csv)?,
+        "name,amount,note\n\
+         plain,1,nothing to escape\n\
+         \"a,b\",,\"say \"\"hi\"\"\"\n\
+         multi,20,\"two\nlines\""
+    );
+    Ok(())
+}
+
+#[test]
+fn csv_has_a_header_without_rows() -> Result<()> {
+    assert_eq!(render::<Row>(&[], Format::Csv)?, "name,amount,note");
+    assert_eq!(
+        render::<BookLevelView>(&[], Format::Csv)?,
+        "side,price,price_raw,amount,amount_raw,depth,depth_raw,orders"
+    );
+    assert_eq!(render::<Row>(&[], Format::Table)?, "No results");
+    assert_eq!(render::<Row>(&[], Format::Json)?, "[]");
+    Ok(())
+}
+
+#[test]
+fn table_aligns_columns_to_the_widest_cell() -> Result<()> {
+    let rows = [row("eth", Some(1_000), "x"), row("usdc", None, "")];
+    assert_eq!(
+        render(&rows, Format::Table)?,
+        "name  amount  note\n\
+         eth   1000    x\n\
+         usdc"
+    );
+    Ok(())
+}
+
+#[test]
+fn render_one_gives_a_single_row() -> Result<()> {
+    let one = row("eth", Some(5), "a,b");
+    assert_eq!(
+        render_one(&one, Format::Csv)?,
+        "name,amount,note\neth,5,\"a,b\""
+    );
+    assert_eq!(
+        render_one(&one, Format::Table)?,
+        "name  amount  note\neth   5       a,b"
+    );
+
+    // An object rather than a one element array
+    let json: serde_json::Value = serde_json::from_str(&render_one(&one, Format::Json)?)?;
+    assert_eq!(json["name"], "eth");
+    assert_eq!(json["amount"], 5);
+    let json: serde_json::Value = serde_json::from_str(&render(&[one], Format::Json)?)?;
+    assert_eq!(json[0]["note"], "a,b");
+    Ok(())
+}